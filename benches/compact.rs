@@ -0,0 +1,73 @@
+//! Compares the estimated heap size of many documents kept as `serde_json::Value` and as `CompactValue`.
+//! Run with `cargo bench --features compact_value,test_utils`.
+extern crate quickxml_to_serde;
+extern crate serde_json;
+
+use quickxml_to_serde::fixture::{generate_xml, FixtureSpec};
+use quickxml_to_serde::{xml_str_to_compact_value, xml_str_to_json, CompactValue, Config, KeyInterner};
+use serde_json::Value;
+use std::mem::size_of;
+use std::time::Instant;
+
+const DOCUMENTS: u64 = 2_000;
+
+/// The heap bytes owned by the value, not counting allocator overhead.
+fn value_heap_size(value: &Value) -> usize {
+    match value {
+        Value::String(s) => s.capacity(),
+        Value::Array(items) => {
+            items.capacity() * size_of::<Value>() + items.iter().map(value_heap_size).sum::<usize>()
+        }
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| k.capacity() + size_of::<(String, Value)>() + value_heap_size(v))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// The heap bytes owned by the value, the shared keys are counted once by the interner.
+fn compact_heap_size(value: &CompactValue) -> usize {
+    match value {
+        CompactValue::String(s) => s.capacity(),
+        CompactValue::Array(items) => {
+            items.capacity() * size_of::<CompactValue>() + items.iter().map(compact_heap_size).sum::<usize>()
+        }
+        CompactValue::Object(props) => {
+            props.capacity() * size_of::<(std::sync::Arc<str>, CompactValue)>()
+                + props.iter().map(|(_, v)| compact_heap_size(v)).sum::<usize>()
+        }
+        _ => 0,
+    }
+}
+
+fn main() {
+    let spec = FixtureSpec {
+        depth: 3,
+        breadth: 4,
+        repeat: 1..=3,
+        max_attributes: 3,
+        ..FixtureSpec::default()
+    };
+    let config = Config::new_with_defaults();
+    let documents: Vec<String> = (0..DOCUMENTS).map(|seed| generate_xml(&spec, seed)).collect();
+
+    let start = Instant::now();
+    let values: Vec<Value> = documents.iter().map(|xml| xml_str_to_json(xml, &config).unwrap()).collect();
+    let size: usize = values.iter().map(value_heap_size).sum();
+    println!("Value:        {:?}, ~{} KiB", start.elapsed(), size / 1024);
+
+    let start = Instant::now();
+    let mut interner = KeyInterner::new();
+    let compact: Vec<CompactValue> = documents
+        .iter()
+        .map(|xml| xml_str_to_compact_value(xml, &config, &mut interner).unwrap())
+        .collect();
+    let size: usize = compact.iter().map(compact_heap_size).sum();
+    println!(
+        "CompactValue: {:?}, ~{} KiB with {} shared keys",
+        start.elapsed(),
+        size / 1024,
+        interner.len()
+    );
+}
@@ -0,0 +1,38 @@
+//! Compares converting a document with 50 JSON type overrides with and without `Config::compile`.
+//! Run with `cargo bench --features json_types`.
+extern crate quickxml_to_serde;
+
+use quickxml_to_serde::{xml_string_to_json, xml_string_to_json_fast, Config, JsonArray, JsonType};
+use std::time::Instant;
+
+const RECORDS: usize = 20_000;
+const OVERRIDES: usize = 50;
+
+fn main() {
+    let mut xml = String::from("<records>");
+    for i in 0..RECORDS {
+        xml.push_str(&format!(
+            r#"<record id="{}"><name>n{}</name><amount>{}.5</amount><code>00{}</code><flag>true</flag></record>"#,
+            i, i, i, i % 100
+        ));
+    }
+    xml.push_str("</records>");
+
+    let mut config = Config::new_with_defaults()
+        .add_json_type_override("/records/record/code", JsonArray::Infer(JsonType::AlwaysString));
+    for i in 1..OVERRIDES {
+        config = config.add_json_type_override(
+            format!("/records/record/field{}", i).as_str(),
+            JsonArray::Infer(JsonType::AlwaysString),
+        );
+    }
+
+    let start = Instant::now();
+    xml_string_to_json(xml.as_str(), &config).unwrap();
+    println!("config:   {:?} for {} records", start.elapsed(), RECORDS);
+
+    let compiled = config.compile();
+    let start = Instant::now();
+    xml_string_to_json_fast(xml, &compiled).unwrap();
+    println!("compiled: {:?} for {} records", start.elapsed(), RECORDS);
+}
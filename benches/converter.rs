@@ -0,0 +1,26 @@
+//! Compares converting many small documents with the free functions against a reused `Converter`.
+//! Run with `cargo bench`.
+extern crate quickxml_to_serde;
+
+use quickxml_to_serde::{xml_str_to_json, Config, Converter};
+use std::time::Instant;
+
+const ITERATIONS: usize = 10_000;
+const XML: &str = r#"<users><user id="1"><name>Andrew</name><card_number>000156</card_number></user><user id="2"><name>John</name><card_number>100263</card_number></user></users>"#;
+
+fn main() {
+    let config = Config::new_with_defaults();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        xml_str_to_json(XML, &config).unwrap();
+    }
+    println!("free function:    {:?} for {} conversions", start.elapsed(), ITERATIONS);
+
+    let mut converter = Converter::new(&config);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        converter.convert_str(XML).unwrap();
+    }
+    println!("reused Converter: {:?} for {} conversions", start.elapsed(), ITERATIONS);
+}
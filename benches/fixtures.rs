@@ -0,0 +1,60 @@
+//! Measures the conversion of generated documents of different shapes.
+//! Run with `cargo bench --features test_utils`.
+extern crate quickxml_to_serde;
+
+use quickxml_to_serde::fixture::{generate_xml, FixtureSpec};
+use quickxml_to_serde::{Config, Converter};
+use std::time::Instant;
+
+const SEED: u64 = 42;
+
+fn main() {
+    let shapes = [
+        (
+            "wide table",
+            FixtureSpec {
+                depth: 2,
+                breadth: 8,
+                repeat: 20_000..=20_000,
+                ..FixtureSpec::default()
+            },
+        ),
+        (
+            "deep nesting",
+            FixtureSpec {
+                depth: 12,
+                breadth: 2,
+                repeat: 1..=2,
+                ..FixtureSpec::default()
+            },
+        ),
+        (
+            "mixed content",
+            FixtureSpec {
+                depth: 5,
+                breadth: 4,
+                mixed_content: true,
+                ..FixtureSpec::default()
+            },
+        ),
+        (
+            "namespace soup",
+            FixtureSpec {
+                depth: 5,
+                breadth: 4,
+                namespaces: 8,
+                max_attributes: 6,
+                ..FixtureSpec::default()
+            },
+        ),
+    ];
+
+    let config = Config::new_with_defaults();
+    let mut converter = Converter::new(&config);
+    for (name, spec) in &shapes {
+        let xml = generate_xml(spec, SEED);
+        let start = Instant::now();
+        converter.convert_str(&xml).unwrap();
+        println!("{:15} {:?} for {} bytes", name, start.elapsed(), xml.len());
+    }
+}
@@ -0,0 +1,26 @@
+//! Compares converting a large point cloud into objects against converting it with a tuple rule.
+//! Run with `cargo bench --features json_types`.
+extern crate quickxml_to_serde;
+
+use quickxml_to_serde::{Config, Converter};
+use std::time::Instant;
+
+const POINTS: usize = 200_000;
+
+fn main() {
+    let mut xml = String::from("<cloud>");
+    for i in 0..POINTS {
+        xml.push_str(&format!(r#"<point x="{}" y="{}.5" z="-{}"/>"#, i, i % 1000, i % 77));
+    }
+    xml.push_str("</cloud>");
+
+    let config = Config::new_with_defaults();
+    let start = Instant::now();
+    Converter::new(&config).convert_str(&xml).unwrap();
+    println!("objects: {:?} for {} points", start.elapsed(), POINTS);
+
+    let config = Config::new_with_defaults().add_tuple_rule("/cloud/point", &["x", "y", "z"]);
+    let start = Instant::now();
+    Converter::new(&config).convert_str(&xml).unwrap();
+    println!("tuples:  {:?} for {} points", start.elapsed(), POINTS);
+}
@@ -0,0 +1,27 @@
+extern crate quickxml_to_serde;
+extern crate serde_json;
+use quickxml_to_serde::{xml_string_to_json, Config, NodeKind, ValueInterceptor};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Upper-cases all strings in the `debug` element and its descendants.
+struct UppercaseDebug;
+
+impl ValueInterceptor for UppercaseDebug {
+    fn on_value(&self, path: &str, _kind: NodeKind, _raw: &str, parsed: Value) -> Value {
+        match parsed {
+            Value::String(s) if path == "/debug" || path.starts_with("/debug/") => {
+                Value::String(s.to_uppercase())
+            }
+            v => v,
+        }
+    }
+}
+
+fn main() {
+    let xml = r#"<debug level="warn"><message>disk almost full</message><code>28</code></debug>"#;
+    let mut conf = Config::new_with_defaults();
+    conf.injection.interceptor = Some(Arc::new(UppercaseDebug));
+    let json = xml_string_to_json(xml, &conf);
+    println!("{}", json.expect("Malformed XML"));
+}
@@ -10,8 +10,8 @@ fn main() {
     let conf = Config::new_with_defaults()
         .add_json_type_override("/a/b/@attr1", JsonArray::Infer(JsonType::AlwaysString))
         .add_json_type_override("/a/b", JsonArray::Infer(JsonType::AlwaysString));
-    let json = xml_string_to_json(String::from(xml), &conf);
-    println!("{}", json.expect("Malformed XML").to_string());
+    let json = xml_string_to_json(xml, &conf);
+    println!("{}", json.expect("Malformed XML"));
 }
 
 #[cfg(not(feature = "json_types"))]
@@ -0,0 +1,223 @@
+//! Conversion of documents read from asynchronous readers, e.g. network sockets, without blocking the executor.
+//! Requires `async` feature.
+//!
+//! The futures and streams only hold buffers, so dropping them at any point, e.g. on a timeout,
+//! simply discards the data read so far.
+
+use futures_core::Stream;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::progress::ProgressTracker;
+use super::{xml_string_to_json_resume, Config, ConversionError, Converter, XmlConversionCursor};
+
+/// The number of bytes requested from the reader at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads the entire XML document from the asynchronous reader and converts it into `serde::Value`.
+/// The returned future resolves to the same value as `xml_str_to_json` would return for the document.
+/// E.g. `let json = xml_async_reader_to_json(socket, &config).await?;`
+pub fn xml_async_reader_to_json<R: AsyncRead + Unpin>(reader: R, config: &Config) -> ReadToJson<'_, R> {
+    ReadToJson {
+        reader,
+        config,
+        input: Vec::new(),
+        progress: ProgressTracker::new(config),
+    }
+}
+
+/// Converts the XML document read from the asynchronous reader into a stream of the child elements
+/// of the root element, see `xml_string_to_json_resume` for their format. The document is read chunk
+/// by chunk and only as far as needed to produce the next record, so the consumer controls the pace
+/// of reading. The stream ends after the end tag of the root element.
+pub fn xml_async_reader_to_records<R: AsyncRead + Unpin>(reader: R, config: &Config) -> Records<'_, R> {
+    Records {
+        reader,
+        config,
+        cursor: XmlConversionCursor::new(),
+        input: Vec::new(),
+        records: VecDeque::new(),
+        done: false,
+        progress: ProgressTracker::new(config),
+        emitted: 0,
+    }
+}
+
+/// Converts the XML document into a stream of the child elements of the root element, for consumers driven by
+/// streams of events. The records are the same as `xml_string_to_json_resume` returns for the whole document.
+///
+/// The stream implements `futures_core::Stream`, the trait `futures::Stream` re-exports, instead of being built
+/// with `futures::stream::iter`, so the crate depends on `futures-core` only. The document is converted
+/// synchronously on the first poll, so creating the stream doesn't block, but the poll does, until the entire
+/// document is converted. Run large conversions on a blocking thread, e.g. with `tokio::task::spawn_blocking`,
+/// or read them with `xml_async_reader_to_records`, which converts the document chunk by chunk.
+pub fn xml_string_to_json_stream_async(
+    xml: String,
+    config: Arc<Config>,
+) -> impl Stream<Item = Result<Value, ConversionError>> + Unpin {
+    StringRecords {
+        xml: Some(xml),
+        config,
+        records: VecDeque::new(),
+    }
+}
+
+/// The future returned by `xml_async_reader_to_json`.
+pub struct ReadToJson<'a, R> {
+    reader: R,
+    config: &'a Config,
+    input: Vec<u8>,
+    progress: ProgressTracker,
+}
+
+impl<'a, R: AsyncRead + Unpin> Future for ReadToJson<'a, R> {
+    type Output = Result<Value, ConversionError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            match poll_read_chunk(&mut this.reader, &mut this.input, cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
+                Poll::Ready(Ok(0)) => break,
+                Poll::Ready(Ok(len)) => this.progress.add_bytes(len),
+            }
+        }
+
+        let mut converter = Converter::new(this.config);
+        let result = converter.convert_bytes(&this.input);
+        if result.is_ok() {
+            this.progress.finish(converter.elements(), 1);
+        }
+        Poll::Ready(result)
+    }
+}
+
+/// The stream returned by `xml_async_reader_to_records`.
+pub struct Records<'a, R> {
+    reader: R,
+    config: &'a Config,
+    cursor: XmlConversionCursor,
+    /// Bytes not passed to the cursor yet, i.e. an incomplete UTF-8 sequence at the end of a chunk
+    input: Vec<u8>,
+    /// Records converted from the last chunk, but not taken yet
+    records: VecDeque<Value>,
+    /// Set once the stream returned an error or the whole document was converted
+    done: bool,
+    progress: ProgressTracker,
+    /// The number of records returned so far
+    emitted: u64,
+}
+
+impl<'a, R: AsyncRead + Unpin> Stream for Records<'a, R> {
+    type Item = Result<Value, ConversionError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(record) = this.records.pop_front() {
+                this.emitted += 1;
+                this.progress.set_converted(this.cursor.elements, this.emitted);
+                return Poll::Ready(Some(Ok(record)));
+            }
+            if this.done || this.cursor.is_finished() {
+                if !this.done {
+                    this.done = true;
+                    this.progress.finish(this.cursor.elements, this.emitted);
+                }
+                return Poll::Ready(None);
+            }
+
+            let result = match poll_read_chunk(&mut this.reader, &mut this.input, cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(0)) => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the document ended before the end tag of the root element",
+                )
+                .into()),
+                Poll::Ready(Ok(len)) => {
+                    this.progress.add_bytes(len);
+                    this.convert_input()
+                }
+                Poll::Ready(Err(e)) => Err(e.into()),
+            };
+
+            match result {
+                Ok(records) => this.records.extend(records),
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+/// The stream returned by `xml_string_to_json_stream_async`.
+struct StringRecords {
+    /// The document, until it's converted on the first poll
+    xml: Option<String>,
+    config: Arc<Config>,
+    records: VecDeque<Result<Value, ConversionError>>,
+}
+
+impl Stream for StringRecords {
+    type Item = Result<Value, ConversionError>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(xml) = this.xml.take() {
+            let mut cursor = XmlConversionCursor::new();
+            match xml_string_to_json_resume(&xml, &this.config, &mut cursor) {
+                Ok(records) => {
+                    this.records.extend(records.into_iter().map(Ok));
+                    if !cursor.is_finished() {
+                        let e = io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "the document ended before the end tag of the root element",
+                        );
+                        this.records.push_back(Err(e.into()));
+                    }
+                }
+                Err(e) => this.records.push_back(Err(e)),
+            }
+        }
+        Poll::Ready(this.records.pop_front())
+    }
+}
+
+impl<'a, R> Records<'a, R> {
+    /// Passes the complete UTF-8 sequences of the input to the cursor.
+    fn convert_input(&mut self) -> Result<Vec<Value>, ConversionError> {
+        let valid_len = match std::str::from_utf8(&self.input) {
+            Ok(_) => self.input.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(e.into()),
+        };
+        let chunk = std::str::from_utf8(&self.input[..valid_len]).expect("valid UTF-8");
+        let records = xml_string_to_json_resume(chunk, self.config, &mut self.cursor)?;
+        self.input.drain(..valid_len);
+        Ok(records)
+    }
+}
+
+/// Appends the next chunk of the reader to the buffer and returns its length, 0 at the end of the input.
+fn poll_read_chunk<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+    cx: &mut Context,
+) -> Poll<io::Result<usize>> {
+    let len = buffer.len();
+    buffer.resize(len + CHUNK_SIZE, 0);
+    let mut read_buf = ReadBuf::new(&mut buffer[len..]);
+    let result = Pin::new(reader).poll_read(cx, &mut read_buf);
+    let filled = read_buf.filled().len();
+    buffer.truncate(len + filled);
+    result.map_ok(|()| filled)
+}
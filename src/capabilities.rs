@@ -0,0 +1,116 @@
+//! The optional features of the crate enabled at compile time, so applications can check their requirements.
+
+use super::{Config, ConversionError, DupAttr};
+
+/// The optional features of the crate, one flag per Cargo feature, see `capabilities`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// JSON type overrides, enum mappings and tuple rules
+    pub json_types: bool,
+    /// Regex paths of JSON type overrides
+    pub regex_path: bool,
+    /// Functions accepting and returning `minidom` types
+    pub minidom_interop: bool,
+    /// `xml_string_to_json_transformed`
+    pub jq: bool,
+    /// `NumberParser::ArbitraryPrecision`
+    pub arbitrary_precision: bool,
+    /// `xml_string_to_cbor`
+    pub cbor: bool,
+    /// `xml_string_to_msgpack`
+    pub msgpack: bool,
+    /// The asynchronous readers and streams, `async` feature
+    pub async_io: bool,
+    /// `QuickXmlParser`, `quickxml-parser` feature
+    pub quickxml_parser: bool,
+    /// `extract_values`
+    pub extract: bool,
+    /// `xml_string_to_validated_json`
+    pub schema_validation: bool,
+    /// `xml_string_to_json_memoized`
+    pub memoize: bool,
+    /// `xml_str_to_compact_value`
+    pub compact_value: bool,
+    /// The snapshot testing helpers and the fixture generator
+    pub test_utils: bool,
+    /// `xml_string_to_raw_json`
+    pub raw_value: bool,
+    /// `xml_string_to_json_encoded`
+    pub encoding: bool,
+    /// `xml_to_json_writer` and `OutputOptions::spill`
+    pub spill: bool,
+}
+
+impl Capabilities {
+    /// Returns the names of the enabled Cargo features, e.g. `["json_types", "quickxml-parser"]`.
+    pub fn features(&self) -> Vec<&'static str> {
+        let features = [
+            ("json_types", self.json_types),
+            ("regex_path", self.regex_path),
+            ("minidom_interop", self.minidom_interop),
+            ("jq", self.jq),
+            ("arbitrary_precision", self.arbitrary_precision),
+            ("cbor", self.cbor),
+            ("msgpack", self.msgpack),
+            ("async", self.async_io),
+            ("quickxml-parser", self.quickxml_parser),
+            ("extract", self.extract),
+            ("schema_validation", self.schema_validation),
+            ("memoize", self.memoize),
+            ("compact_value", self.compact_value),
+            ("test_utils", self.test_utils),
+            ("raw_value", self.raw_value),
+            ("encoding", self.encoding),
+            ("spill", self.spill),
+        ];
+        features.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect()
+    }
+}
+
+/// Returns the optional features the crate was compiled with, e.g. to fail at startup instead of
+/// converting documents without the expected options.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        json_types: cfg!(feature = "json_types"),
+        regex_path: cfg!(feature = "regex_path"),
+        minidom_interop: cfg!(feature = "minidom_interop"),
+        jq: cfg!(feature = "jq"),
+        arbitrary_precision: cfg!(feature = "arbitrary_precision"),
+        cbor: cfg!(feature = "cbor"),
+        msgpack: cfg!(feature = "msgpack"),
+        async_io: cfg!(feature = "async"),
+        quickxml_parser: cfg!(feature = "quickxml-parser"),
+        extract: cfg!(feature = "extract"),
+        schema_validation: cfg!(feature = "schema_validation"),
+        memoize: cfg!(feature = "memoize"),
+        compact_value: cfg!(feature = "compact_value"),
+        test_utils: cfg!(feature = "test_utils"),
+        raw_value: cfg!(feature = "raw_value"),
+        encoding: cfg!(feature = "encoding"),
+        spill: cfg!(feature = "spill"),
+    }
+}
+
+impl Config {
+    /// Checks that the options don't depend on disabled features. The options which only exist with a feature,
+    /// e.g. `json_type_overrides`, can't be set without it, but some options are always available and have
+    /// no effect without their feature, e.g. `duplicate_source_attributes` without `quickxml-parser`.
+    /// Returns `ConversionError::MissingFeature` for the first such option.
+    pub fn validate(&self) -> Result<(), ConversionError> {
+        let caps = capabilities();
+        if self.attributes.duplicate_source_attributes != DupAttr::Error && !caps.quickxml_parser {
+            return Err(missing_feature("duplicate_source_attributes", "quickxml-parser"));
+        }
+        if self.values.force_float_format && !caps.arbitrary_precision {
+            return Err(missing_feature("force_float_format", "arbitrary_precision"));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn missing_feature(option: &str, feature: &str) -> ConversionError {
+    ConversionError::MissingFeature {
+        option: option.to_owned(),
+        feature: feature.to_owned(),
+    }
+}
@@ -0,0 +1,16 @@
+//! Conversion into [CBOR](https://cbor.io/), a compact binary alternative to JSON.
+//! Requires `cbor` feature which uses [ciborium](https://github.com/enarx/ciborium).
+
+use super::{xml_string_to_json, Config, ConversionError};
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and
+/// serializes the result into CBOR bytes. Decoding the bytes gives back the same JSON value.
+/// With `arbitrary_precision` feature the numbers are encoded the way `serde_json` serializes them,
+/// as maps holding the text of the number, which only `serde_json::Value` can decode.
+pub fn xml_string_to_cbor(xml: String, config: &Config) -> Result<Vec<u8>, ConversionError> {
+    let value = xml_string_to_json(xml, config)?;
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&value, &mut bytes)
+        .map_err(|e| ConversionError::SerializationError(e.to_string()))?;
+    Ok(bytes)
+}
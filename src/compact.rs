@@ -0,0 +1,137 @@
+//! A memory-efficient output for keeping many converted documents in memory.
+//! Requires `compact_value` feature.
+
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::{xml_str_to_json, Config, ConversionError};
+
+/// A JSON value like `serde_json::Value`, but the property names are `Arc<str>` shared through a `KeyInterner`,
+/// so documents with the same element and attribute names store every name only once.
+/// It serializes into the same JSON as `serde_json::Value` and converts into it with `Value::from`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum CompactValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<CompactValue>),
+    /// The properties in the order of `serde_json::Map`
+    Object(Vec<(Arc<str>, CompactValue)>),
+}
+
+/// A set of shared property names. Use one interner for all documents kept together
+/// to share the names between them, or a new one for every document.
+#[derive(Debug, Default, Clone)]
+pub struct KeyInterner {
+    keys: HashSet<Arc<str>>,
+}
+
+impl KeyInterner {
+    pub fn new() -> Self {
+        KeyInterner::default()
+    }
+
+    /// Returns the shared copy of the key, adding it on first use.
+    pub fn intern(&mut self, key: &str) -> Arc<str> {
+        match self.keys.get(key) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared: Arc<str> = Arc::from(key);
+                self.keys.insert(shared.clone());
+                shared
+            }
+        }
+    }
+
+    /// Returns the number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl CompactValue {
+    /// Moves the value into a `CompactValue`, interning its property names.
+    pub fn from_value(value: Value, interner: &mut KeyInterner) -> Self {
+        match value {
+            Value::Null => CompactValue::Null,
+            Value::Bool(b) => CompactValue::Bool(b),
+            Value::Number(n) => CompactValue::Number(n),
+            Value::String(s) => CompactValue::String(s),
+            Value::Array(items) => CompactValue::Array(
+                items
+                    .into_iter()
+                    .map(|item| CompactValue::from_value(item, interner))
+                    .collect(),
+            ),
+            Value::Object(map) => CompactValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (interner.intern(&k), CompactValue::from_value(v, interner)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<CompactValue> for Value {
+    fn from(value: CompactValue) -> Self {
+        match value {
+            CompactValue::Null => Value::Null,
+            CompactValue::Bool(b) => Value::Bool(b),
+            CompactValue::Number(n) => Value::Number(n),
+            CompactValue::String(s) => Value::String(s),
+            CompactValue::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            CompactValue::Object(props) => {
+                let map: Map<String, Value> = props
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), Value::from(v)))
+                    .collect();
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+impl Serialize for CompactValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            CompactValue::Null => serializer.serialize_unit(),
+            CompactValue::Bool(b) => serializer.serialize_bool(*b),
+            CompactValue::Number(n) => n.serialize(serializer),
+            CompactValue::String(s) => serializer.serialize_str(s),
+            CompactValue::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            CompactValue::Object(props) => {
+                let mut map = serializer.serialize_map(Some(props.len()))?;
+                for (k, v) in props {
+                    map.serialize_entry(&**k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Converts the given XML string into a `CompactValue` using settings from `Config` struct.
+/// The property names are shared through the interner, e.g. one interner for all documents of a cache.
+/// The document is converted into `serde::Value` first and moved into the compact form, so the saving
+/// applies to the documents kept in memory, not to the conversion itself.
+pub fn xml_str_to_compact_value(
+    xml: &str,
+    config: &Config,
+    interner: &mut KeyInterner,
+) -> Result<CompactValue, ConversionError> {
+    let value = xml_str_to_json(xml, config)?;
+    Ok(CompactValue::from_value(value, interner))
+}
@@ -0,0 +1,54 @@
+//! A frozen config with an index of its JSON type overrides for documents with many overrides.
+
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::{Config, ConversionError, Converter};
+
+/// A copy of the config with the last steps of its `json_type_overrides` paths, e.g. `@c` of `/a/b/@c`.
+/// The override lookup hashes the whole XML path of every node, and with `PathAnchor::Anywhere` all its suffixes,
+/// while most nodes have no override. With the index only the nodes whose name ends an override path
+/// are looked up. The config can't be changed afterwards, create a new one with `Config::compile` instead.
+/// Requires `json_types` feature.
+#[derive(Debug, Clone)]
+pub struct CompiledConfig {
+    config: Config,
+    override_names: HashSet<String>,
+}
+
+impl CompiledConfig {
+    /// The config the index was built from.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub(crate) fn override_names(&self) -> &HashSet<String> {
+        &self.override_names
+    }
+}
+
+impl Config {
+    /// Freezes a copy of the config for `xml_string_to_json_fast`, see `CompiledConfig`.
+    /// Requires `json_types` feature.
+    pub fn compile(&self) -> CompiledConfig {
+        // a wildcard path ending with `*` adds `*`, which stands for every element name
+        let override_names = self
+            .json_type_overrides
+            .keys()
+            .chain(self.json_type_wildcard_overrides.iter().map(|(path, _)| path))
+            .filter_map(|path| path.rsplit('/').next())
+            .map(str::to_owned)
+            .collect();
+        CompiledConfig {
+            config: self.clone(),
+            override_names,
+        }
+    }
+}
+
+/// Same as `xml_string_to_json` with the config of `CompiledConfig`, but looks up the JSON type overrides
+/// only for the nodes which may have one. The output is the same.
+/// Requires `json_types` feature.
+pub fn xml_string_to_json_fast(xml: String, compiled: &CompiledConfig) -> Result<Value, ConversionError> {
+    Converter::new(compiled.config()).convert_str_compiled(&xml, compiled)
+}
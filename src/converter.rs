@@ -0,0 +1,272 @@
+//! A reusable converter for hot paths where many documents are converted with the same `Config`.
+
+use minidom::Element;
+use serde_json::{Map, Value};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::AtomicBool;
+
+#[cfg(feature = "json_types")]
+use super::{CompiledConfig, JsonType};
+use super::progress::CountingReader;
+use super::{
+    parse_xml, parse_xml_with, prolog_nodes, xml_into_map, xml_to_map, CommentHandling, Config, Context,
+    ConversionError, ElementProgress, Hooks, Prolog, Warning, XmlParser,
+};
+
+/// Converts XML documents with the same `Config` while reusing its scratch buffers, e.g. the XML path
+/// of the current node, between conversions. It is cheap to call repeatedly and is `Send`,
+/// so each worker thread can own one.
+///
+/// The converter can own the config, `Converter::new(config)`, or borrow it, `Converter::new(&config)`.
+/// The free functions like `xml_str_to_json` are thin wrappers around a temporary converter.
+/// # Example
+/// ```
+/// use quickxml_to_serde::{Config, Converter};
+///
+/// let mut converter = Converter::new(Config::new_with_defaults());
+/// for xml in &["<a>1</a>", "<a>2</a>"] {
+///     let json = converter.convert_str(xml).expect("Malformed XML");
+///     println!("{}", json);
+/// }
+/// ```
+pub struct Converter<C: Borrow<Config> = Config> {
+    config: C,
+    ctx: Context,
+    /// A buffer for documents read from readers and byte slices
+    input: String,
+}
+
+impl<C: Borrow<Config>> Converter<C> {
+    /// Creates a converter that uses the given config for all conversions.
+    pub fn new(config: C) -> Self {
+        Converter {
+            config,
+            ctx: Context::default(),
+            input: String::new(),
+        }
+    }
+
+    /// Returns the config used by this converter.
+    pub fn config(&self) -> &Config {
+        self.config.borrow()
+    }
+
+    /// Converts the given XML string into `serde::Value`.
+    pub fn convert_str(&mut self, xml: &str) -> Result<Value, ConversionError> {
+        self.convert_str_with_warnings(xml).map(|(value, _)| value)
+    }
+
+    /// Same as `convert_str`, but also returns the list of non-fatal issues found during the conversion.
+    pub fn convert_str_with_warnings(
+        &mut self,
+        xml: &str,
+    ) -> Result<(Value, Vec<Warning>), ConversionError> {
+        self.convert_with_hooks(xml, Hooks::default())
+    }
+
+    /// Same as `convert_str`, but the name of the source is used for `InjectedValue::SourceName` fields.
+    pub fn convert_str_with_source(
+        &mut self,
+        xml: &str,
+        source_name: &str,
+    ) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            source_name: Some(source_name),
+            ..Hooks::default()
+        };
+        self.convert_with_hooks(xml, hooks).map(|(value, _)| value)
+    }
+
+    /// Same as `convert_str`, but reports the number of converted elements,
+    /// see `xml_string_to_json_with_progress`.
+    pub fn convert_str_with_progress(
+        &mut self,
+        xml: &str,
+        progress_fn: &dyn Fn(usize, usize),
+    ) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            progress: Some(ElementProgress::new(progress_fn, xml)),
+            ..Hooks::default()
+        };
+        let (value, _) = self.convert_with_hooks(xml, hooks)?;
+        let processed = self.ctx.elements as usize;
+        progress_fn(processed, processed);
+        Ok(value)
+    }
+
+    /// Same as `convert_str`, but adds the output to the given map, see `xml_string_into_map`.
+    pub fn convert_str_into_map(&mut self, xml: &str, map: &mut Map<String, Value>) -> Result<(), ConversionError> {
+        let doc = parse_xml(xml, self.config.borrow())?;
+        self.ctx.reset();
+        let hooks = Hooks {
+            side_table: Some(&doc.side_table),
+            ..Hooks::default()
+        };
+        xml_into_map(&doc.root, self.config.borrow(), &mut self.ctx, &hooks, map)
+    }
+
+    /// Same as `convert_str`, but the elements on the priority paths are converted first,
+    /// see `xml_string_to_json_focused`.
+    pub fn convert_str_focused(&mut self, xml: &str, priority_paths: &[&str]) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            priority_paths,
+            ..Hooks::default()
+        };
+        self.convert_with_hooks(xml, hooks).map(|(value, _)| value)
+    }
+
+    /// Same as `convert_str`, but also returns the XML declaration of the document.
+    pub fn convert_str_with_prolog(&mut self, xml: &str) -> Result<(Value, Prolog), ConversionError> {
+        let value = self.convert_str(xml)?;
+        Ok((value, Prolog::from_xml(xml)))
+    }
+
+    /// Same as `convert_str`, but adds the context properties to the output,
+    /// see `xml_string_to_json_with_context`.
+    pub fn convert_str_with_context(
+        &mut self,
+        xml: &str,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            context: Some(context),
+            ..Hooks::default()
+        };
+        self.convert_with_hooks(xml, hooks).map(|(value, _)| value)
+    }
+
+    /// Same as `convert_str`, but stops with `ConversionError::Canceled` once the `cancel` flag is set.
+    /// The flag is checked before converting each element.
+    pub fn convert_str_cancelable(
+        &mut self,
+        xml: &str,
+        cancel: &AtomicBool,
+    ) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            cancel: Some(cancel),
+            ..Hooks::default()
+        };
+        self.convert_with_hooks(xml, hooks).map(|(value, _)| value)
+    }
+
+    /// Same as `convert_str`, but the JSON types of the listed XML paths take precedence over
+    /// all overrides from the config for this call only, see `xml_string_to_json_with_overrides`.
+    /// Requires `json_types` feature.
+    #[cfg(feature = "json_types")]
+    pub fn convert_str_with_overrides(
+        &mut self,
+        xml: &str,
+        overrides: &[(String, JsonType)],
+    ) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            overrides,
+            ..Hooks::default()
+        };
+        self.convert_with_hooks(xml, hooks).map(|(value, _)| value)
+    }
+
+    /// Same as `convert_str` with the index of the config, see `xml_string_to_json_fast`.
+    #[cfg(feature = "json_types")]
+    pub(crate) fn convert_str_compiled(
+        &mut self,
+        xml: &str,
+        compiled: &CompiledConfig,
+    ) -> Result<Value, ConversionError> {
+        let hooks = Hooks {
+            override_names: Some(compiled.override_names()),
+            ..Hooks::default()
+        };
+        self.convert_with_hooks(xml, hooks).map(|(value, _)| value)
+    }
+
+    fn convert_with_hooks(
+        &mut self,
+        xml: &str,
+        hooks: Hooks,
+    ) -> Result<(Value, Vec<Warning>), ConversionError> {
+        let config = self.config.borrow();
+        let doc = parse_xml(xml, config)?;
+        // the parsers drop the nodes before the root element
+        let keeps_prolog = config.text.comment_handling != CommentHandling::Ignore
+            || config.text.processing_instructions_key.is_some();
+        let (comments, instructions) = if keeps_prolog {
+            prolog_nodes(xml)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        let hooks = Hooks {
+            prolog_comments: &comments,
+            prolog_instructions: &instructions,
+            side_table: Some(&doc.side_table),
+            ..hooks
+        };
+        self.convert_parsed(&doc.root, hooks)
+    }
+
+    /// Same as `convert_str`, but the document is parsed with the given parser,
+    /// see `xml_string_to_json_with_parser`.
+    pub fn convert_str_with_parser<P: XmlParser>(&mut self, xml: &str) -> Result<Value, ConversionError> {
+        let doc = parse_xml_with::<P>(xml, self.config.borrow())?;
+        let hooks = Hooks {
+            side_table: Some(&doc.side_table),
+            ..Hooks::default()
+        };
+        self.convert_parsed(&doc.root, hooks).map(|(value, _)| value)
+    }
+
+    /// Converts an already parsed `minidom` element into `serde::Value`.
+    /// Requires `minidom_interop` feature.
+    #[cfg(feature = "minidom_interop")]
+    pub fn convert_element(&mut self, el: &Element) -> Result<Value, ConversionError> {
+        self.convert_parsed(el, Hooks::default()).map(|(value, _)| value)
+    }
+
+    fn convert_parsed(
+        &mut self,
+        root: &Element,
+        hooks: Hooks,
+    ) -> Result<(Value, Vec<Warning>), ConversionError> {
+        hooks.check_canceled()?;
+        self.ctx.reset();
+        let value = xml_to_map(root, self.config.borrow(), &mut self.ctx, &hooks)?;
+        Ok((value, self.ctx.take_warnings()))
+    }
+
+    /// The number of elements converted by the last conversion, for `DiagnosticOptions::progress`.
+    #[cfg(feature = "async")]
+    pub(crate) fn elements(&self) -> u64 {
+        self.ctx.elements
+    }
+
+    /// Converts an XML document encoded as UTF-8 bytes into `serde::Value`.
+    /// Documents starting with a UTF-16 byte order mark fail with `ConversionError::UnsupportedEncoding`.
+    pub fn convert_bytes(&mut self, xml: &[u8]) -> Result<Value, ConversionError> {
+        if xml.starts_with(&[0xFF, 0xFE]) || xml.starts_with(&[0xFE, 0xFF]) {
+            return Err(ConversionError::UnsupportedEncoding {
+                encoding: "UTF-16".to_owned(),
+            });
+        }
+        let xml = std::str::from_utf8(xml)?;
+        self.convert_str(xml)
+    }
+
+    /// Reads the entire XML document from the reader and converts it into `serde::Value`.
+    /// Reports the progress to `DiagnosticOptions::progress`, if set.
+    pub fn convert_reader<R: Read>(&mut self, reader: R) -> Result<Value, ConversionError> {
+        let mut reader = CountingReader::new(reader, self.config.borrow());
+        // the buffer is taken out temporarily to be able to borrow `self` mutably for the conversion
+        let mut input = std::mem::take(&mut self.input);
+        input.clear();
+        let result = match reader.read_to_string(&mut input) {
+            Ok(_) => self.convert_str(&input),
+            Err(e) => Err(e.into()),
+        };
+        self.input = input;
+        if result.is_ok() {
+            reader.tracker.finish(self.ctx.elements, 1);
+        }
+        result
+    }
+}
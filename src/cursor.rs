@@ -0,0 +1,254 @@
+//! Incremental conversion of documents read in chunks, e.g. very large files or network streams.
+//!
+//! Only the elements buffered since the last complete child element of the root are kept in memory.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{
+    convert_key, convert_node, element_key, finish_output, parse_xml, synthetic_ids, Config, Context,
+    ConversionError, Hooks,
+};
+
+/// Keeps the state of an incremental conversion between the calls of `xml_string_to_json_resume`.
+/// Use a new cursor for every document.
+#[derive(Default)]
+pub struct XmlConversionCursor {
+    /// The unprocessed part of the document
+    buffer: String,
+    state: State,
+    /// The number of converted children of the root by name, for synthetic node ids
+    sibling_counts: HashMap<String, usize>,
+    ctx: Context,
+    /// The number of converted elements, for `DiagnosticOptions::progress`
+    pub(crate) elements: u64,
+}
+
+#[derive(Debug, PartialEq, Default)]
+enum State {
+    /// Before the start tag of the root element
+    #[default]
+    Prolog,
+    /// Inside the root element, with its start tag and name
+    Root { start_tag: String, name: String },
+    /// After the end tag of the root element
+    Finished,
+}
+
+impl XmlConversionCursor {
+    /// Creates a cursor positioned at the start of a document.
+    pub fn new() -> Self {
+        XmlConversionCursor::default()
+    }
+
+    /// Returns `true` once the end tag of the root element was processed.
+    pub fn is_finished(&self) -> bool {
+        self.state == State::Finished
+    }
+}
+
+/// Converts the next chunk of an XML document and returns the child elements of the root element
+/// completed by this chunk, e.g. `{"item":{"@id":1}}` for `<item id="1"/>` in `<feed><item id="1"/>...</feed>`.
+/// The rest of the chunk is kept in the cursor until the following chunks complete it.
+/// The chunks can be split anywhere, even in the middle of a tag.
+///
+/// The children are converted with the same rules and paths as when converting the whole document,
+/// e.g. JSON type overrides for `/feed/item/@id` apply. Namespaces declared on the root element are
+/// available to the children, but the attributes of the root element and text directly inside it
+/// are not converted. Use `XmlConversionCursor::is_finished` to check if the whole document was received.
+pub fn xml_string_to_json_resume(
+    chunk: &str,
+    config: &Config,
+    cursor: &mut XmlConversionCursor,
+) -> Result<Vec<Value>, ConversionError> {
+    cursor.buffer.push_str(chunk);
+
+    let mut values = Vec::new();
+    let mut pos = 0;
+
+    while let Some(start) = find_markup(&cursor.buffer, pos) {
+        let (end, markup) = match scan_markup(&cursor.buffer, start) {
+            Some(scanned) => scanned,
+            None => break,
+        };
+
+        match (&cursor.state, markup) {
+            (State::Prolog, Markup::StartTag { self_closing: true, .. }) => {
+                cursor.state = State::Finished;
+            }
+            (State::Prolog, Markup::StartTag { name, .. }) => {
+                cursor.state = State::Root {
+                    start_tag: cursor.buffer[start..end].to_owned(),
+                    name: name.to_owned(),
+                };
+            }
+            (State::Root { .. }, Markup::StartTag { self_closing, .. }) => {
+                let element_end = if self_closing {
+                    Some(end)
+                } else {
+                    element_end(&cursor.buffer, end)
+                };
+                match element_end {
+                    Some(element_end) => {
+                        values.push(convert_child(cursor, start, element_end, config)?);
+                        pos = element_end;
+                        continue;
+                    }
+                    // wait for the rest of the element
+                    None => break,
+                }
+            }
+            (State::Root { .. }, Markup::EndTag) => cursor.state = State::Finished,
+            // prolog, comments, processing instructions and anything after the root element
+            _ => (),
+        }
+
+        pos = end;
+    }
+
+    // text outside of markup is not converted, so it's safe to drop it too
+    let consumed = if cursor.state == State::Finished {
+        cursor.buffer.len()
+    } else {
+        find_markup(&cursor.buffer, pos).unwrap_or(cursor.buffer.len())
+    };
+    cursor.buffer.drain(..consumed);
+
+    Ok(values)
+}
+
+/// Converts the child element of the root at `start..end` of the buffer into `{"name": value}`.
+fn convert_child(
+    cursor: &mut XmlConversionCursor,
+    start: usize,
+    end: usize,
+    config: &Config,
+) -> Result<Value, ConversionError> {
+    let (start_tag, root_name) = match cursor.state {
+        State::Root { ref start_tag, ref name } => (start_tag, name),
+        _ => unreachable!("children are only converted inside the root element"),
+    };
+
+    // the root start tag brings the namespace declarations into scope
+    let xml = [start_tag, &cursor.buffer[start..end], "</", root_name, ">"].concat();
+    let doc = parse_xml(&xml, config)?;
+    let root = &doc.root;
+    let child = match root.children().next() {
+        Some(child) => child,
+        None => return Ok(Value::Null),
+    };
+
+    let ctx = &mut cursor.ctx;
+    ctx.reset();
+    ctx.marks = doc.side_table.resolve(root);
+    ctx.path.push('/');
+    ctx.path.push_str(root.name());
+    if synthetic_ids(config) {
+        let count = cursor.sibling_counts.entry(child.name().to_owned()).or_insert(0);
+        ctx.id_path = format!("/{}/{}[{}]", root.name(), child.name(), count);
+        *count += 1;
+    }
+
+    let path_len = ctx.path.len();
+    ctx.path.push('/');
+    ctx.path.push_str(child.name());
+    let name = convert_key(element_key(child, config), config, ctx)?;
+    ctx.path.truncate(path_len);
+
+    let mut data = Map::new();
+    data.insert(
+        name,
+        convert_node(child, config, ctx, &Hooks::default())?.unwrap_or(Value::Null),
+    );
+    cursor.elements += cursor.ctx.elements;
+    let mut value = Value::Object(data);
+    finish_output(&mut value, config);
+    Ok(value)
+}
+
+/// The kinds of markup the cursor needs to tell apart.
+enum Markup<'a> {
+    StartTag { name: &'a str, self_closing: bool },
+    EndTag,
+    /// Comments, CDATA sections, processing instructions and DOCTYPE
+    Other,
+}
+
+/// Returns the position of the next `<` at or after `pos`.
+fn find_markup(buffer: &str, pos: usize) -> Option<usize> {
+    buffer[pos..].find('<').map(|i| pos + i)
+}
+
+/// Returns the end position and the kind of the markup starting at `start`,
+/// or `None` if the markup is not complete yet.
+fn scan_markup(buffer: &str, start: usize) -> Option<(usize, Markup<'_>)> {
+    let rest = &buffer[start..];
+
+    // `<!` and `<?` may be incomplete prefixes of the longer openings
+    for (open, close) in &[("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")] {
+        if let Some(inner) = rest.strip_prefix(open) {
+            return inner
+                .find(close)
+                .map(|i| (start + open.len() + i + close.len(), Markup::Other));
+        }
+        if open.starts_with(rest) {
+            return None;
+        }
+    }
+    if rest.starts_with("<!") {
+        // DOCTYPE with an optional internal subset in square brackets
+        let subset_end = match (rest.find('['), rest.find('>')) {
+            (Some(open), Some(close)) if open < close => rest.find("]")?,
+            _ => 0,
+        };
+        return rest[subset_end..]
+            .find('>')
+            .map(|i| (start + subset_end + i + 1, Markup::Other));
+    }
+
+    // start and end tags, `>` may appear in attribute values
+    let mut quote = None;
+    for (i, c) in rest.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => {
+                let markup = if rest.starts_with("</") {
+                    Markup::EndTag
+                } else {
+                    let name_end = rest[1..]
+                        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                        .map_or(i, |n| n + 1);
+                    Markup::StartTag {
+                        name: &rest[1..name_end],
+                        self_closing: rest[..i].ends_with('/'),
+                    }
+                };
+                return Some((start + i + 1, markup));
+            }
+            None => (),
+        }
+    }
+
+    None
+}
+
+/// Returns the position after the end tag of the element whose start tag ends at `pos`,
+/// or `None` if the element is not complete yet.
+fn element_end(buffer: &str, mut pos: usize) -> Option<usize> {
+    let mut depth = 1;
+
+    while depth > 0 {
+        let start = find_markup(buffer, pos)?;
+        let (end, markup) = scan_markup(buffer, start)?;
+        match markup {
+            Markup::StartTag { self_closing: false, .. } => depth += 1,
+            Markup::EndTag => depth -= 1,
+            _ => (),
+        }
+        pos = end;
+    }
+
+    Some(pos)
+}
@@ -0,0 +1,158 @@
+//! Structural comparison of two converted documents, e.g. yesterday's and today's versions of the same feed.
+//!
+//! Unlike generic JSON diff tools it understands the conventions of this crate: a single XML element
+//! converts into a scalar or an object while repeated elements convert into an array, so `X` and `[X]`
+//! may describe the same data. Paths use the same syntax as `Config::json_type_overrides`, with array
+//! indices added in square brackets, e.g. `/feed/item[1]/@updated`.
+
+use serde_json::Value;
+
+/// The kind of difference found at a path.
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiffKind {
+    /// The property exists only in the second document.
+    Added,
+    /// The property exists only in the first document.
+    Removed,
+    /// Both values have the same JSON type, but different values.
+    Changed,
+    /// The values have different JSON types, e.g. a number and a string or a string and an object.
+    TypeChanged,
+    /// One of the values is an array and the other one is not, or the arrays can't be compared element by element.
+    ShapeChanged,
+}
+
+/// A single difference between two documents.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiffEntry {
+    /// The path of the property, e.g. `/a/b[0]/@c`
+    pub path: String,
+    pub kind: DiffKind,
+    /// The value from the first document, `None` for `DiffKind::Added`
+    pub old: Option<Value>,
+    /// The value from the second document, `None` for `DiffKind::Removed`
+    pub new: Option<Value>,
+}
+
+/// Tells `diff` how to compare the documents.
+#[derive(Debug, Default)]
+pub struct DiffOptions {
+    /// Treat a value `X` and an array with a single element `[X]` as equal.
+    /// It is common for `JsonArray::Infer` nodes, which become arrays only if repeated.
+    /// Defaults to `false`.
+    pub scalar_array_equivalence: bool,
+    /// Paths excluded from the comparison, including everything under them. Array indices are not part of
+    /// these paths, e.g. `/feed/item/@updated` excludes `@updated` in every `item`.
+    pub ignored_paths: Vec<String>,
+}
+
+impl DiffOptions {
+    /// Strict comparison with no ignored paths.
+    pub fn new() -> Self {
+        DiffOptions::default()
+    }
+
+    /// Excludes the path from the comparison. The leading slash is added if missing.
+    pub fn ignore_path(self, path: &str) -> Self {
+        let mut options = self;
+        options.ignored_paths.push(super::with_leading_slash(path));
+        options
+    }
+}
+
+/// Returns the list of differences between two converted documents.
+/// An empty list means the documents are equal according to `options`.
+pub fn diff(a: &Value, b: &Value, options: &DiffOptions) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_values(a, b, "", "", options, &mut entries);
+    entries
+}
+
+/// Compares two values at the same path. `path` has array indices and `match_path` has not.
+fn diff_values(
+    a: &Value,
+    b: &Value,
+    path: &str,
+    match_path: &str,
+    options: &DiffOptions,
+    entries: &mut Vec<DiffEntry>,
+) {
+    if options.ignored_paths.iter().any(|p| p == match_path) {
+        return;
+    }
+
+    match (a, b) {
+        (Value::Object(a_map), Value::Object(b_map)) => {
+            for (key, a_val) in a_map {
+                let child_path = [path, "/", key].concat();
+                let child_match_path = [match_path, "/", key].concat();
+                match b_map.get(key) {
+                    Some(b_val) => {
+                        diff_values(a_val, b_val, &child_path, &child_match_path, options, entries)
+                    }
+                    None => {
+                        if !options.ignored_paths.contains(&child_match_path) {
+                            entries.push(entry(child_path, DiffKind::Removed, Some(a_val), None));
+                        }
+                    }
+                }
+            }
+            for (key, b_val) in b_map {
+                let child_match_path = [match_path, "/", key].concat();
+                if !a_map.contains_key(key) && !options.ignored_paths.contains(&child_match_path) {
+                    let child_path = [path, "/", key].concat();
+                    entries.push(entry(child_path, DiffKind::Added, None, Some(b_val)));
+                }
+            }
+        }
+        (Value::Array(a_vec), Value::Array(b_vec)) => {
+            for (i, a_val) in a_vec.iter().enumerate() {
+                let child_path = format!("{}[{}]", path, i);
+                match b_vec.get(i) {
+                    Some(b_val) => diff_values(a_val, b_val, &child_path, match_path, options, entries),
+                    None => entries.push(entry(child_path, DiffKind::Removed, Some(a_val), None)),
+                }
+            }
+            for (i, b_val) in b_vec.iter().enumerate().skip(a_vec.len()) {
+                let child_path = format!("{}[{}]", path, i);
+                entries.push(entry(child_path, DiffKind::Added, None, Some(b_val)));
+            }
+        }
+        // `X` vs `[X]`
+        (Value::Array(a_vec), b_val) if options.scalar_array_equivalence && a_vec.len() == 1 => {
+            diff_values(&a_vec[0], b_val, path, match_path, options, entries)
+        }
+        (a_val, Value::Array(b_vec)) if options.scalar_array_equivalence && b_vec.len() == 1 => {
+            diff_values(a_val, &b_vec[0], path, match_path, options, entries)
+        }
+        (Value::Array(_), _) | (_, Value::Array(_)) => {
+            entries.push(entry(path.to_owned(), DiffKind::ShapeChanged, Some(a), Some(b)))
+        }
+        (a_val, b_val) if a_val == b_val => (),
+        (a_val, b_val) if type_name(a_val) == type_name(b_val) => {
+            entries.push(entry(path.to_owned(), DiffKind::Changed, Some(a), Some(b)))
+        }
+        _ => entries.push(entry(path.to_owned(), DiffKind::TypeChanged, Some(a), Some(b))),
+    }
+}
+
+fn entry(path: String, kind: DiffKind, old: Option<&Value>, new: Option<&Value>) -> DiffEntry {
+    DiffEntry {
+        path,
+        kind,
+        old: old.cloned(),
+        new: new.cloned(),
+    }
+}
+
+/// Returns the name of the JSON type of the value.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,109 @@
+//! Output of the JSON text in other byte encodings than UTF-8, for legacy consumers.
+//! Requires `encoding` feature.
+
+use encoding_rs::{EncoderResult, Encoding, UTF_8, WINDOWS_1252};
+
+use super::{xml_str_to_json, xml_string_to_json_bytes, Config, ConversionError};
+
+/// The byte encoding of the JSON text written by `xml_string_to_json_encoded`, see `OutputOptions::output_encoding`.
+/// The characters the encoding has no bytes for are written as JSON escapes, e.g. `€` as `\u20ac`,
+/// so the text is still valid JSON with the same value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// UTF-8, like all other output. This is the default.
+    Utf8,
+    /// ISO-8859-1 (Latin-1), with one byte per character up to U+00FF. The characters U+0080 to U+009F
+    /// are escaped as well, so the text reads the same as windows-1252, which most software means by Latin-1.
+    Iso8859_1,
+    /// Another encoding of `encoding_rs`, e.g. `OutputEncoding::for_label("shift_jis")`. Only encodings
+    /// writing ASCII as single bytes are supported, others like UTF-16 fail with
+    /// `ConversionError::UnsupportedEncoding`.
+    Other(&'static Encoding),
+}
+
+impl OutputEncoding {
+    /// Returns the encoding with the label, e.g. `windows-1252` or `gbk`, or `None` if the label is unknown.
+    /// The labels are the ones of the WHATWG Encoding Standard, which reads `latin1` and `iso-8859-1`
+    /// as windows-1252, use `OutputEncoding::Iso8859_1` for ISO-8859-1 itself.
+    pub fn for_label(label: &str) -> Option<OutputEncoding> {
+        match Encoding::for_label(label.as_bytes())? {
+            encoding if encoding == UTF_8 => Some(OutputEncoding::Utf8),
+            encoding => Some(OutputEncoding::Other(encoding)),
+        }
+    }
+
+    /// The name of the encoding, e.g. `UTF-8`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputEncoding::Utf8 => UTF_8.name(),
+            OutputEncoding::Iso8859_1 => "ISO-8859-1",
+            OutputEncoding::Other(encoding) => encoding.name(),
+        }
+    }
+}
+
+/// Converts the given XML string into the bytes of the JSON text in the encoding of `OutputOptions::output_encoding`.
+/// With `OutputEncoding::Utf8` the output is the same as by `xml_string_to_json_bytes`.
+pub fn xml_string_to_json_encoded(xml: String, config: &Config) -> Result<Vec<u8>, ConversionError> {
+    let encoding = match config.output.output_encoding {
+        OutputEncoding::Utf8 => return xml_string_to_json_bytes(xml, config),
+        // the characters up to U+00FF which are not escaped have the same bytes in windows-1252
+        OutputEncoding::Iso8859_1 => WINDOWS_1252,
+        OutputEncoding::Other(encoding) => {
+            if !encoding.is_ascii_compatible() || encoding.output_encoding() != encoding {
+                return Err(ConversionError::UnsupportedEncoding {
+                    encoding: encoding.name().to_owned(),
+                });
+            }
+            encoding
+        }
+    };
+
+    let value = xml_str_to_json(&xml, config)?;
+    let mut json = serde_json::to_string(&value).map_err(|e| ConversionError::SerializationError(e.to_string()))?;
+    if config.output.output_encoding == OutputEncoding::Iso8859_1 {
+        json = escape_chars(&json, |c| ('\u{80}'..'\u{a0}').contains(&c) || c > '\u{ff}');
+    }
+    Ok(encode(&json, encoding))
+}
+
+/// Encodes the JSON text with escapes for the characters the encoding has no bytes for. Such characters
+/// can only be in strings, because the JSON syntax itself is ASCII, so escaping them keeps the value.
+fn encode(json: &str, encoding: &'static Encoding) -> Vec<u8> {
+    let mut encoder = encoding.new_encoder();
+    let mut bytes = Vec::with_capacity(json.len());
+    let mut rest = json;
+    loop {
+        let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut bytes, true);
+        rest = &rest[read..];
+        match result {
+            EncoderResult::InputEmpty => return bytes,
+            EncoderResult::OutputFull => bytes.reserve(rest.len() + 16),
+            EncoderResult::Unmappable(c) => push_escape(&mut bytes, c),
+        }
+    }
+}
+
+/// Replaces the characters matching `escaped` with JSON escapes.
+fn escape_chars(json: &str, escaped: impl Fn(char) -> bool) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut bytes = Vec::new();
+    for c in json.chars() {
+        if escaped(c) {
+            bytes.clear();
+            push_escape(&mut bytes, c);
+            out.extend(bytes.iter().map(|b| *b as char));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Writes the character as `\uXXXX`, or two of them for a surrogate pair.
+fn push_escape(bytes: &mut Vec<u8>, c: char) {
+    let mut units = [0; 2];
+    for unit in c.encode_utf16(&mut units) {
+        bytes.extend_from_slice(format!("\\u{:04x}", unit).as_bytes());
+    }
+}
@@ -0,0 +1,213 @@
+//! Extraction of a few values from large documents without converting the whole document.
+//! Requires `extract` feature, which uses [quick-xml](https://github.com/tafia/quick-xml) to read the events.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+
+use super::{
+    convert_attr_value, convert_text, get_json_type, trim_around_cdata, Config, Context, ConversionError, Hooks,
+    NullValue, XmlError,
+};
+
+/// Reads the document from the reader and returns the values at the given XML paths, e.g. `/order/@id` or
+/// `/order/status`, without building the output for the rest of the document. The values are converted
+/// with the same rules as by `xml_str_to_json`, including JSON type overrides of the paths.
+/// Every path maps to all its values in document order, or an empty list if the document has none.
+///
+/// The leading `/` is optional and `*` matches any element name, e.g. `/order/*/price`. Attributes can only
+/// be the last step. Elements give the value of their text, so elements with child elements and no text of
+/// their own have no value, and empty elements follow `Config::empty_element_handling`.
+///
+/// With `OutputOptions::stop_after_priority_paths` the reading stops once every path has a value, unless a path has
+/// a wildcard. The rest of the document is not checked in that case.
+pub fn extract_values<R: Read>(
+    reader: R,
+    paths: &[&str],
+    config: &Config,
+) -> Result<HashMap<String, Vec<Value>>, ConversionError> {
+    let targets: Vec<Target> = paths.iter().map(|path| Target::new(path)).collect();
+    let mut values: HashMap<String, Vec<Value>> =
+        paths.iter().map(|path| ((*path).to_owned(), Vec::new())).collect();
+    let stop_early = config.output.stop_after_priority_paths && !paths.iter().any(|path| path.contains('*'));
+
+    let mut reader = Reader::from_reader(BufReader::new(reader));
+    let mut buf = Vec::new();
+    let mut ctx = Context::default();
+    // the names of the open elements, from the root down
+    let mut stack: Vec<String> = Vec::new();
+    // the open elements matching the paths, in the order they were opened
+    let mut open: Vec<OpenElement> = Vec::new();
+
+    loop {
+        let event = reader.read_event(&mut buf).map_err(xml_error)?;
+        if let Event::Start(ref e) | Event::Empty(ref e) = event {
+            open.iter_mut()
+                .filter(|el| el.depth == stack.len())
+                .for_each(|el| el.has_children = true);
+            stack.push(std::str::from_utf8(e.local_name())?.to_owned());
+
+            for (index, target) in targets.iter().enumerate() {
+                if !target.matches(&stack) {
+                    continue;
+                }
+                let attr_name = match target.attr {
+                    Some(attr_name) => attr_name,
+                    None => {
+                        open.push(OpenElement {
+                            depth: stack.len(),
+                            target: index,
+                            text: String::new(),
+                            has_cdata: false,
+                            cdata: None,
+                            has_children: false,
+                        });
+                        continue;
+                    }
+                };
+                for attr in e.attributes() {
+                    let attr = attr.map_err(xml_error)?;
+                    if attr.key != attr_name.as_bytes() {
+                        continue;
+                    }
+                    let value = attr.unescape_and_decode_value(&reader).map_err(xml_error)?;
+                    set_path(&mut ctx, &stack, Some(attr_name));
+                    let (_, json_type) = get_json_type(config, &Hooks::default(), &ctx.path);
+                    let value = convert_attr_value(attr_name, &value, json_type, config, &mut ctx)?;
+                    if let Some(values) = values.get_mut(paths[index]) {
+                        values.push(value);
+                    }
+                }
+            }
+        }
+
+        match event {
+            // empty elements are closed right away
+            Event::End(_) | Event::Empty(_) => {
+                while open.last().is_some_and(|el| el.depth == stack.len()) {
+                    let el = open.pop().expect("an open element");
+                    set_path(&mut ctx, &stack, None);
+                    let target = paths[el.target];
+                    if let Some(value) = element_value(el, config, &mut ctx)? {
+                        if let Some(values) = values.get_mut(target) {
+                            values.push(value);
+                        }
+                    }
+                }
+                stack.pop();
+                if stack.is_empty() {
+                    break;
+                }
+            }
+            Event::Text(ref e) if open.iter().any(|el| el.depth == stack.len()) => {
+                let text = e.unescape_and_decode(&reader).map_err(xml_error)?;
+                add_text(&mut open, stack.len(), &text, false);
+            }
+            Event::CData(ref e) if open.iter().any(|el| el.depth == stack.len()) => {
+                let text = reader.decode(e).map_err(xml_error)?;
+                add_text(&mut open, stack.len(), text, config.text.cdata_as_string);
+            }
+            Event::Eof => return Err(ConversionError::Xml(XmlError(minidom::Error::EndOfDocument))),
+            _ => (),
+        }
+
+        if stop_early && values.values().all(|values| !values.is_empty()) {
+            break;
+        }
+        buf.clear();
+    }
+
+    Ok(values)
+}
+
+/// A path passed to `extract_values`, split into the element steps and the attribute name.
+struct Target<'a> {
+    steps: Vec<&'a str>,
+    attr: Option<&'a str>,
+}
+
+impl<'a> Target<'a> {
+    fn new(path: &'a str) -> Self {
+        let mut steps: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let attr = steps.last().copied().and_then(|step| step.strip_prefix('@'));
+        if attr.is_some() {
+            steps.pop();
+        }
+        Target { steps, attr }
+    }
+
+    /// Returns `true` if the steps match the names of the open elements.
+    fn matches(&self, stack: &[String]) -> bool {
+        self.steps.len() == stack.len()
+            && self.steps.iter().zip(stack).all(|(step, name)| *step == "*" || step == name)
+    }
+}
+
+/// An open element matching a path, collecting its text until it's closed.
+struct OpenElement {
+    /// The number of open elements including this one
+    depth: usize,
+    /// The index of the matching path
+    target: usize,
+    text: String,
+    /// Whether the element has CDATA sections with `TextOptions::cdata_as_string`
+    has_cdata: bool,
+    /// The range from the start of the first to the end of the last CDATA section in the text
+    cdata: Option<(usize, usize)>,
+    has_children: bool,
+}
+
+fn add_text(open: &mut [OpenElement], depth: usize, text: &str, cdata: bool) {
+    for el in open.iter_mut().filter(|el| el.depth == depth) {
+        if cdata {
+            let start = el.cdata.map_or(el.text.len(), |(start, _)| start);
+            el.has_cdata = true;
+            if !text.is_empty() {
+                el.cdata = Some((start, el.text.len() + text.len()));
+            }
+        }
+        el.text.push_str(text);
+    }
+}
+
+/// Sets the XML path of the context to the open element or its attribute.
+fn set_path(ctx: &mut Context, stack: &[String], attr: Option<&str>) {
+    ctx.path.clear();
+    for name in stack {
+        ctx.path.push('/');
+        ctx.path.push_str(name);
+    }
+    if let Some(attr) = attr {
+        ctx.path.push_str("/@");
+        ctx.path.push_str(attr);
+    }
+}
+
+/// Converts the text of the element the way `convert_node` does.
+fn element_value(el: OpenElement, config: &Config, ctx: &mut Context) -> Result<Option<Value>, ConversionError> {
+    let text = if config.text.trim_text || el.has_children {
+        trim_around_cdata(&el.text, el.cdata)
+    } else {
+        el.text.as_str()
+    };
+    // an empty CDATA section is an empty string
+    if !text.is_empty() || el.has_cdata {
+        let (_, json_type) = get_json_type(config, &Hooks::default(), &ctx.path);
+        return convert_text(text, el.has_cdata, json_type, config, ctx).map(Some);
+    }
+    if el.has_children {
+        return Ok(None);
+    }
+    match config.empty_element_handling {
+        NullValue::Ignore => Ok(None),
+        NullValue::Null => Ok(Some(Value::Null)),
+        NullValue::EmptyObject => Ok(Some(Value::Object(Map::new()))),
+        NullValue::EmptyString => Ok(Some(Value::String(String::new()))),
+    }
+}
+
+fn xml_error(e: quick_xml::Error) -> ConversionError {
+    ConversionError::Xml(XmlError(e.into()))
+}
@@ -0,0 +1,170 @@
+//! Deterministic generator of XML documents for benchmarks and tests, e.g. wide tables, deep nesting,
+//! mixed content or many namespaces, so large fixtures don't have to be committed to the repository.
+//! The same spec and seed always produce the same document. Requires `test_utils` feature.
+
+use std::ops::RangeInclusive;
+
+/// The shape of the documents produced by `generate_xml`.
+/// The number of elements grows with `breadth * repeat` to the power of `depth`, so keep them small for deep documents.
+#[derive(Debug, Clone)]
+pub struct FixtureSpec {
+    /// The number of levels of elements below the root element. Elements at the last level hold text only.
+    pub depth: usize,
+    /// The number of distinct child element names of every element above the last level.
+    pub breadth: usize,
+    /// The number of times each child element is repeated, picked for every element.
+    pub repeat: RangeInclusive<usize>,
+    /// The most attributes an element may have, the actual number is picked for every element.
+    pub max_attributes: usize,
+    /// The number of characters of every text and attribute value, picked for every value.
+    pub text_len: RangeInclusive<usize>,
+    /// Put text between the child elements, not only into the elements at the last level.
+    pub mixed_content: bool,
+    /// The number of namespace prefixes declared on the root element and used by elements and attributes.
+    /// `0` produces a document without namespaces.
+    pub namespaces: usize,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        FixtureSpec {
+            depth: 3,
+            breadth: 3,
+            repeat: 1..=3,
+            max_attributes: 2,
+            text_len: 1..=12,
+            mixed_content: false,
+            namespaces: 0,
+        }
+    }
+}
+
+/// Generates an XML document of the given shape. Element and attribute names, the repetitions, and
+/// the texts are picked by a pseudo-random generator seeded with `seed`. The texts are a mix of words,
+/// integers, decimals and booleans to exercise the type inference.
+/// # Example
+/// ```
+/// use quickxml_to_serde::fixture::{generate_xml, FixtureSpec};
+///
+/// let spec = FixtureSpec { depth: 1, breadth: 100, repeat: 1..=1, ..FixtureSpec::default() };
+/// assert_eq!(generate_xml(&spec, 7), generate_xml(&spec, 7));
+/// ```
+pub fn generate_xml(spec: &FixtureSpec, seed: u64) -> String {
+    let mut generator = Generator {
+        spec,
+        rng: Rng(seed),
+        xml: String::new(),
+    };
+
+    generator.xml.push_str("<root");
+    for ns in 0..spec.namespaces {
+        generator
+            .xml
+            .push_str(&format!(r#" xmlns:n{0}="urn:fixture:{0}""#, ns));
+    }
+    generator.attributes();
+    generator.xml.push('>');
+    generator.children(spec.depth);
+    generator.xml.push_str("</root>");
+    generator.xml
+}
+
+struct Generator<'a> {
+    spec: &'a FixtureSpec,
+    rng: Rng,
+    xml: String,
+}
+
+impl<'a> Generator<'a> {
+    /// Writes the child elements of an element with `levels` levels of elements below it.
+    fn children(&mut self, levels: usize) {
+        if levels == 0 {
+            self.text();
+            return;
+        }
+
+        for child in 0..self.spec.breadth {
+            let name = format!("{}e{}", self.prefix(), child);
+            for _ in 0..self.rng.pick(&self.spec.repeat) {
+                if self.spec.mixed_content {
+                    self.text();
+                }
+                self.xml.push('<');
+                self.xml.push_str(&name);
+                self.attributes();
+                self.xml.push('>');
+                self.children(levels - 1);
+                self.xml.push_str("</");
+                self.xml.push_str(&name);
+                self.xml.push('>');
+            }
+        }
+    }
+
+    fn attributes(&mut self) {
+        let count = self.rng.pick(&(0..=self.spec.max_attributes));
+        for attr in 0..count {
+            let prefix = self.prefix();
+            self.xml.push_str(&format!(" {}a{}=\"", prefix, attr));
+            self.text();
+            self.xml.push('"');
+        }
+    }
+
+    /// Writes a text that needs no escaping.
+    fn text(&mut self) {
+        let len = self.rng.pick(&self.spec.text_len);
+        let text = match self.rng.below(4) {
+            0 => (0..len)
+                .map(|_| (b'0' + self.rng.below(10) as u8) as char)
+                .collect(),
+            1 if len > 2 => {
+                let int = self.rng.below(1_000_000).to_string();
+                let fraction: String = (0..len - 2)
+                    .map(|_| (b'0' + self.rng.below(10) as u8) as char)
+                    .collect();
+                [int, fraction].join(".")
+            }
+            2 if len >= 4 => ["true", "false"][self.rng.below(2) as usize].to_owned(),
+            _ => (0..len)
+                .map(|_| (b'a' + self.rng.below(26) as u8) as char)
+                .collect(),
+        };
+        self.xml.push_str(&text);
+    }
+
+    /// Returns a namespace prefix like `n1:` for a name, or nothing for an unqualified name.
+    fn prefix(&mut self) -> String {
+        match self.rng.below(self.spec.namespaces as u64 + 1) {
+            0 => String::new(),
+            ns => format!("n{}:", ns - 1),
+        }
+    }
+}
+
+/// SplitMix64, small and good enough for picking shapes and texts.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// Returns a number in the range, or its start if the range is empty.
+    fn pick(&mut self, range: &RangeInclusive<usize>) -> usize {
+        let (start, end) = (*range.start(), *range.end());
+        if end <= start {
+            return start;
+        }
+        start + self.below((end - start) as u64 + 1) as usize
+    }
+}
@@ -0,0 +1,123 @@
+//! Projection of converted records into flat rows, e.g. for writing them as CSV.
+//!
+//! Nested objects become dotted column names like `item.price` or `item.@currency`, so every cell holds
+//! a scalar JSON value. The rows stay `serde_json::Value`s and can be passed to any CSV or spreadsheet writer.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Defines how arrays inside the records are turned into cells.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArrayPolicy {
+    /// Each element gets its own numbered column, e.g. `item.0.price` and `item.1.price`.
+    /// Arrays with more elements than `limit` are stringified into a single column named after the array.
+    Explode { limit: usize },
+    /// The whole array is written into a single column as a JSON string, e.g. `[1,2]`.
+    Stringify,
+}
+
+/// Tells `records_to_rows` how to flatten the records.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FlattenOptions {
+    /// Separates the names of nested properties and array indices in column names. Defaults to `.`.
+    pub separator: String,
+    /// Defaults to `ArrayPolicy::Explode { limit: 10 }`.
+    pub arrays: ArrayPolicy,
+}
+
+impl FlattenOptions {
+    /// Dotted column names with arrays of up to 10 elements exploded into numbered columns.
+    pub fn new() -> Self {
+        FlattenOptions::default()
+    }
+
+    /// Sets the policy for arrays.
+    pub fn arrays(self, arrays: ArrayPolicy) -> Self {
+        let mut options = self;
+        options.arrays = arrays;
+        options
+    }
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions {
+            separator: ".".to_owned(),
+            arrays: ArrayPolicy::Explode { limit: 10 },
+        }
+    }
+}
+
+/// Flattens the records into rows of cells and returns them with the column names.
+///
+/// The columns are the union of the columns of all records in the order of their first appearance.
+/// Cells missing from a record are `Value::Null`, as are empty objects and arrays.
+/// A record which is a scalar, e.g. a number, is placed in a column with an empty name.
+pub fn records_to_rows(records: &[Value], options: &FlattenOptions) -> (Vec<String>, Vec<Vec<Value>>) {
+    let mut columns = Vec::new();
+    let mut column_index = HashMap::new();
+    let mut flat_records = Vec::with_capacity(records.len());
+
+    for record in records {
+        let mut cells = Vec::new();
+        flatten_value(record, String::new(), options, &mut cells);
+
+        let cells: Vec<(usize, Value)> = cells
+            .into_iter()
+            .map(|(column, value)| {
+                let index = *column_index.entry(column.clone()).or_insert_with(|| {
+                    columns.push(column);
+                    columns.len() - 1
+                });
+                (index, value)
+            })
+            .collect();
+        flat_records.push(cells);
+    }
+
+    // the earlier records don't know about the columns added by the later ones
+    let rows = flat_records
+        .into_iter()
+        .map(|cells| {
+            let mut row = vec![Value::Null; columns.len()];
+            for (index, value) in cells {
+                row[index] = value;
+            }
+            row
+        })
+        .collect();
+
+    (columns, rows)
+}
+
+/// Adds the cells for the value and everything nested in it.
+fn flatten_value(value: &Value, column: String, options: &FlattenOptions, cells: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                flatten_value(v, join(&column, k, options), options, cells);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => match options.arrays {
+            ArrayPolicy::Explode { limit } if items.len() <= limit => {
+                for (i, v) in items.iter().enumerate() {
+                    flatten_value(v, join(&column, &i.to_string(), options), options, cells);
+                }
+            }
+            _ => cells.push((column, Value::String(value.to_string()))),
+        },
+        // an empty record has no cells at all
+        Value::Object(_) | Value::Array(_) if column.is_empty() => (),
+        Value::Object(_) | Value::Array(_) => cells.push((column, Value::Null)),
+        _ => cells.push((column, value.clone())),
+    }
+}
+
+/// Appends the property name or array index to the column name.
+fn join(column: &str, name: &str, options: &FlattenOptions) -> String {
+    if column.is_empty() {
+        name.to_owned()
+    } else {
+        [column, options.separator.as_str(), name].concat()
+    }
+}
@@ -0,0 +1,65 @@
+//! Reshaping of the converted JSON with [jq](https://jqlang.github.io/jq/) filters.
+//! Requires `jq` feature which uses [jaq](https://github.com/01mf02/jaq), a pure-Rust jq implementation.
+
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, Filter, Native, RcIter};
+use jaq_json::Val;
+use serde_json::Value;
+
+use super::{xml_string_to_json, Config, ConversionError};
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and
+/// applies the jq filter to the result. E.g. `.a | to_entries` turns `<a><b>1</b></a>` into
+/// `[{"key":"b","value":1}]`. The jq standard library is available.
+///
+/// A filter producing a single value returns it as-is. Filters producing no values or several
+/// values, e.g. `.a[]`, return them as a JSON array.
+/// Invalid filters and runtime errors return `ConversionError::TransformError`.
+pub fn xml_string_to_json_transformed(
+    xml: String,
+    config: &Config,
+    jq_filter: &str,
+) -> Result<Value, ConversionError> {
+    // an invalid filter fails before spending any time on the conversion
+    let filter = compile(jq_filter)?;
+    let value = xml_string_to_json(xml, config)?;
+    run(&filter, value)
+}
+
+/// Parses and compiles the filter with jq standard library definitions.
+fn compile(jq_filter: &str) -> Result<Filter<Native<Val>>, ConversionError> {
+    let program = File {
+        code: jq_filter,
+        path: (),
+    };
+    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+    let arena = Arena::default();
+
+    let modules = loader
+        .load(&arena, program)
+        .map_err(|e| ConversionError::TransformError(format!("invalid jq filter: {:?}", e)))?;
+
+    Compiler::default()
+        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+        .compile(modules)
+        .map_err(|e| ConversionError::TransformError(format!("invalid jq filter: {:?}", e)))
+}
+
+/// Runs the compiled filter on the value and collects its output.
+fn run(filter: &Filter<Native<Val>>, value: Value) -> Result<Value, ConversionError> {
+    let inputs = RcIter::new(core::iter::empty());
+    let mut output = Vec::new();
+
+    for val in filter.run((Ctx::new([], &inputs), Val::from(value))) {
+        match val {
+            Ok(val) => output.push(Value::from(val)),
+            Err(e) => return Err(ConversionError::TransformError(e.to_string())),
+        }
+    }
+
+    if output.len() == 1 {
+        Ok(output.remove(0))
+    } else {
+        Ok(Value::Array(output))
+    }
+}
@@ -24,11 +24,11 @@
 //! fn main() {
 //!    let xml = r#"<a attr1="1"><b><c attr2="001">some text</c></b></a>"#;
 //!    let conf = Config::new_with_defaults();
-//!    let json = xml_string_to_json(xml.to_owned(), &conf);
+//!    let json = xml_string_to_json(xml, &conf);
 //!    println!("{}", json.expect("Malformed XML").to_string());
 //!
 //!    let conf = Config::new_with_custom_values(true, "", "txt", NullValue::Null);
-//!    let json = xml_string_to_json(xml.to_owned(), &conf);
+//!    let json = xml_string_to_json(xml, &conf);
 //!    println!("{}", json.expect("Malformed XML").to_string());
 //! }
 //! ```
@@ -47,52 +47,188 @@
 //!            .add_json_type_override("/a/b/c/@attr2", JsonArray::Infer(JsonType::AlwaysString));
 //! ```
 //!
+//! ## The root element
+//! The output is always an object with the name of the root element as the only key, or next to the keys added
+//! by options like `AttributeOptions::promote_root_attrs`, so the functions converting whole documents never return
+//! a bare scalar. The root element itself is converted like any other element:
+//!
+//! | Root element | Output |
+//! |---|---|
+//! | `<a/>` | `{"a":{}}`, `{"a":null}` or `{"a":""}`, see `Config::empty_element_handling` |
+//! | `<a/>` with `NullValue::Ignore` | `{"a":null}`, as the root element can't be left out |
+//! | `<a>hi</a>` | `{"a":"hi"}` |
+//! | `<a x="1"/>` | `{"a":{"@x":1}}` |
+//! | `<a x="1">hi</a>` | `{"a":{"@x":1,"#text":"hi"}}` |
+//! | `<a x="1"/>`, `AttributeOptions::promote_root_attrs` | `{"@x":1,"a":{}}`, as an empty root element |
+//! | `<a x="1">hi</a>`, `AttributeOptions::promote_root_attrs` | `{"@x":1,"a":"hi"}` |
+//!
 //! ## Detailed documentation
-//! See [README](https://github.com/AlecTroemel/quickxml_to_serde) in the source repo for more examples, limitations and detailed behavior description.
+//! See [README](https://github.com/AlecTroemel/quickxml_to_serde) in the source repo for more examples, limitations and
+//! detailed behavior description.
 //!
 //! ## Testing your XML files
 //!
-//! If you want to see how your XML files are converted into JSON, place them into `./test_xml_files` directory
-//! and run `cargo test`. They will be converted into JSON and saved in the saved directory.
+//! The XML files in `./test_xml_files` are converted with several configs and compared with the JSON committed
+//! in `./test_xml_files/snapshots/<config name>/` by `cargo test`, which fails with a report of the differences.
+//! To see how your XML files are converted, place them into the directory and run `UPDATE_SNAPSHOTS=1 cargo test`,
+//! which writes the current output into the snapshots. Review the changes with `git diff` before committing them.
+//! With `test_utils` feature `snapshot::assert_snapshot_dir` runs the same checks on your own corpus.
 
+#[cfg(feature = "minidom_interop")]
+pub extern crate minidom;
+#[cfg(not(feature = "minidom_interop"))]
 extern crate minidom;
 extern crate serde_json;
 
 #[cfg(feature = "regex_path")]
 extern crate regex;
 
-use minidom::{Element, Error};
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "compact_value")]
+extern crate serde;
+#[cfg(feature = "async")]
+extern crate futures_core;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(feature = "jq")]
+extern crate jaq_core;
+#[cfg(feature = "jq")]
+extern crate jaq_json;
+#[cfg(feature = "jq")]
+extern crate jaq_std;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+#[cfg(any(feature = "quickxml-parser", feature = "extract"))]
+extern crate quick_xml;
+#[cfg(feature = "schema_validation")]
+extern crate jsonschema;
+#[cfg(feature = "memoize")]
+extern crate ahash;
+#[cfg(feature = "memoize")]
+extern crate dashmap;
+#[cfg(feature = "encoding")]
+pub extern crate encoding_rs;
+
+use minidom::{Element, Error, Node};
 use serde_json::{Map, Number, Value};
-#[cfg(feature = "json_types")]
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+#[cfg(feature = "arbitrary_precision")]
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "regex_path")]
 use regex::Regex;
 
+#[cfg(feature = "async")]
+mod async_io;
+mod capabilities;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "compact_value")]
+mod compact;
+#[cfg(feature = "json_types")]
+mod compiled;
+mod converter;
+mod cursor;
+pub mod diff;
+#[cfg(feature = "encoding")]
+mod encoding;
+#[cfg(feature = "extract")]
+mod extract;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod fixture;
+pub mod flatten;
+#[cfg(feature = "jq")]
+mod jq;
+#[cfg(feature = "memoize")]
+mod memoize;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod ndjson;
+mod parser;
+mod progress;
+mod provenance;
+mod reapply;
+#[cfg(feature = "schema_validation")]
+mod schema;
+#[cfg(feature = "spill")]
+mod spill;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod snapshot;
+mod template;
 #[cfg(test)]
 mod tests;
+mod writer;
+#[cfg(feature = "json_types")]
+mod xsd;
+
+#[cfg(feature = "async")]
+pub use async_io::{
+    xml_async_reader_to_json, xml_async_reader_to_records, xml_string_to_json_stream_async, ReadToJson, Records,
+};
+pub use capabilities::{capabilities, Capabilities};
+#[cfg(feature = "cbor")]
+pub use cbor::xml_string_to_cbor;
+#[cfg(feature = "compact_value")]
+pub use compact::{xml_str_to_compact_value, CompactValue, KeyInterner};
+#[cfg(feature = "json_types")]
+pub use compiled::{xml_string_to_json_fast, CompiledConfig};
+pub use converter::Converter;
+pub use cursor::{xml_string_to_json_resume, XmlConversionCursor};
+#[cfg(feature = "encoding")]
+pub use encoding::{xml_string_to_json_encoded, OutputEncoding};
+#[cfg(feature = "extract")]
+pub use extract::extract_values;
+#[cfg(feature = "jq")]
+pub use jq::xml_string_to_json_transformed;
+#[cfg(feature = "memoize")]
+pub use memoize::{xml_string_to_json_memoized, XmlToJsonCache};
+#[cfg(feature = "msgpack")]
+pub use msgpack::xml_string_to_msgpack;
+pub use ndjson::xml_to_ndjson;
+#[cfg(feature = "quickxml-parser")]
+pub use parser::QuickXmlParser;
+pub use parser::{MinidomParser, ParsedDocument, XmlParser};
+pub use progress::{Progress, ProgressConfig};
+pub use reapply::{reapply, ReapplyReport};
+#[cfg(feature = "schema_validation")]
+pub use schema::{xml_string_to_validated_json, ValidationError};
+#[cfg(feature = "spill")]
+pub use spill::{xml_to_json_writer, SpillConfig};
+pub use template::xml_string_to_json_template;
+pub use writer::json_to_xml;
+#[cfg(feature = "json_types")]
+pub use xsd::xml_string_to_json_schema_aware;
 
 /// Defines how empty elements like `<x />` should be handled.
-/// `Ignore` -> exclude from JSON, `Null` -> `"x":null`, EmptyObject -> `"x":{}`.
+/// `Ignore` -> exclude from JSON, `Null` -> `"x":null`, EmptyObject -> `"x":{}`, EmptyString -> `"x":""`.
 /// `EmptyObject` is the default option and is how it was handled prior to v.0.4
 /// Using `Ignore` on an XML document with an empty root element falls back to `Null` option.
 /// E.g. both `<a><x/></a>` and `<a/>` are converted into `{"a":null}`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum NullValue {
     Ignore,
     Null,
     EmptyObject,
+    EmptyString,
 }
 
 /// Defines how the values of this Node should be converted into a JSON array with the underlying types.
 /// * `Infer` - the nodes are converted into a JSON array only if there are multiple identical elements.
-/// E.g. `<a><b>1</b></a>` becomes a map `{"a": {"b": 1 }}` and `<a><b>1</b><b>2</b><b>3</b></a>` becomes
-/// an array `{"a": {"b": [1, 2, 3] }}`
+///   E.g. `<a><b>1</b></a>` becomes a map `{"a": {"b": 1 }}` and `<a><b>1</b><b>2</b><b>3</b></a>` becomes
+///   an array `{"a": {"b": [1, 2, 3] }}`
 /// * `Always` - the nodes are converted into a JSON array regardless of how many there are.
-/// E.g. `<a><b>1</b></a>` becomes an array with a single value `{"a": {"b": [1] }}` and
-/// `<a><b>1</b><b>2</b><b>3</b></a>` also becomes an array `{"a": {"b": [1, 2, 3] }}`
-#[derive(Debug)]
+///   E.g. `<a><b>1</b></a>` becomes an array with a single value `{"a": {"b": [1] }}` and
+///   `<a><b>1</b><b>2</b><b>3</b></a>` also becomes an array `{"a": {"b": [1, 2, 3] }}`.
+///   Parent elements converted into objects get an empty array if the nodes are missing, e.g. `<a/>` becomes
+///   `{"a": {"b": [] }}`. Absent parents follow `InjectionOptions::absent_field_policy`. Regex paths only apply to
+///   the existing nodes.
+#[derive(Debug, Clone)]
 pub enum JsonArray {
     /// Convert the nodes into a JSON array even if there is only one element
     Always(JsonType),
@@ -114,17 +250,59 @@ pub enum PathMatcher {
     Regex(Regex),
 }
 
+/// Defines where the paths of `Config::json_type_overrides` and `Config::enum_mappings` are anchored.
+#[cfg(feature = "json_types")]
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathAnchor {
+    /// The path must match the entire path of the node from the root, e.g. `/a/b/price`.
+    Root,
+    /// The path may also match the trailing segments of the node path, so `/price` applies to
+    /// `/a/price` and `/a/b/price`, and `/@id` applies to `id` attributes anywhere in the document.
+    /// Exact matches from the root take priority, then the longest matching suffix.
+    Anywhere,
+}
+
 // For retro-compatibility and for syntax's sake, a string may be coerced into an absolute path.
 impl From<&str> for PathMatcher {
     fn from(value: &str) -> Self {
-        let path_with_leading_slash = if value.starts_with("/") {
-            value.into()
-        } else {
-            ["/", value].concat()
-        };
+        PathMatcher::Absolute(with_leading_slash(value))
+    }
+}
+
+/// Adds the leading slash to the path if it's missing.
+fn with_leading_slash(path: &str) -> String {
+    if path.starts_with("/") {
+        path.into()
+    } else {
+        ["/", path].concat()
+    }
+}
+
+/// Replaces the `{name}` variables of the path with their values. Returns the path and `true`
+/// if all variables were replaced. Unknown variables are left as-is.
+#[cfg(feature = "json_types")]
+fn expand_path_template(template: &str, vars: &HashMap<String, String>) -> (String, bool) {
+    let mut path = String::with_capacity(template.len());
+    let mut resolved = true;
+    let mut rest = template;
 
-        PathMatcher::Absolute(path_with_leading_slash)
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        path.push_str(&rest[..start]);
+        match vars.get(&rest[start + 1..end]) {
+            Some(value) => path.push_str(value),
+            None => {
+                path.push_str(&rest[start..=end]);
+                resolved = false;
+            }
+        }
+        rest = &rest[end + 1..];
     }
+    path.push_str(rest);
+    (path, resolved)
 }
 
 // ... While a Regex may be coerced into a regex path.
@@ -145,353 +323,4247 @@ pub enum JsonType {
     /// Do not try to infer the type and convert the value to JSON string.
     /// E.g. convert `<a>1234</a>` into `{"a":"1234"}` or `<a>true</a>` into `{"a":"true"}`
     AlwaysString,
+    /// Convert the value to JSON string like `AlwaysString`. With `normalize`, decimal numbers are brought
+    /// into a canonical form by removing the trailing zeros of the fraction, a trailing dot and the sign of zero,
+    /// without converting them into floats. E.g. `1.500` and `1.5` both become `"1.5"` and `-0.0` becomes `"0"`.
+    /// Text that isn't a decimal number is left as it is.
+    DecimalString { normalize: bool },
     /// Convert values included in this member into JSON bool `true` and any other value into `false`.
     /// E.g. `Bool(vec!["True", "true", "TRUE"]) will result in any of these values to become JSON bool `true`.
     Bool(Vec<&'static str>),
+    /// Convert integers into JSON numbers and any other value into a string, e.g. `<a>12</a>` into `{"a":12}`,
+    /// but `<a>1.5</a>` into `{"a":"1.5"}`. Leading zeros are dropped, e.g. `007` becomes `7`.
+    AlwaysInt,
+    /// Convert numbers into JSON floats and any other value into a string, e.g. `<a>42</a>` into `{"a":42.0}`
+    /// and `<a>1.00</a>` into `{"a":1.0}`, but `<a>n/a</a>` into `{"a":"n/a"}`. Use `AlwaysInt` for integers.
+    /// `NaN` and infinities have no JSON form and remain strings.
+    AlwaysFloat,
+    /// Split a trailing unit off a measurement like `<len>15mm</len>` or `<temp>21.5 °C</temp>` and convert
+    /// the number with the standard inference. The longest of the `units` matching the end of the text is used,
+    /// so `15mm` has the unit `mm` with both `m` and `mm` listed. Whitespace before the unit is dropped.
+    /// Text without one of the units, or with something other than a number before it, remains a string.
+    /// See `MeasurementEmit` for the output.
+    Measurement { units: Vec<String>, emit: MeasurementEmit },
+    /// Convert the values included in this member into JSON `null` and any other value into a string.
+    /// E.g. `Null(vec!["N/A", "-"])` converts `<a>N/A</a>` into `{"a":null}`.
+    Null(Vec<&'static str>),
+    /// Try the types in order and use the first one that succeeds, e.g. `Chain(vec![JsonType::AlwaysInt,
+    /// JsonType::Null(vec!["N/A"]), JsonType::AlwaysString])` for a field that is usually an integer,
+    /// sometimes `N/A` and rarely anything else. `AlwaysInt` succeeds for integers only, `AlwaysFloat` for
+    /// numbers only, `Measurement` for numbers with a unit, `Bool` and `Null` for the listed values only,
+    /// `Infer` for values inferred as anything but a string, and `AlwaysString` and `DecimalString` always succeed.
+    /// A chain may contain chains, but chains nested deeper than that never succeed. Values no type succeeds for
+    /// remain strings.
+    Chain(Vec<JsonType>),
     /// Attempt to infer the type by looking at the single value of the node being converted.
+    /// Only the types enabled in `ValueOptions::inference` are inferred, everything else remains a string.
     /// Not guaranteed to be consistent across multiple nodes.
     /// E.g. convert `<a>1234</a>` and `<a>001234</a>` into `{"a":1234}`, or `<a>true</a>` into `{"a":true}`
     /// Check if your values comply with JSON data types (case, range, format) to produce the expected result.
     Infer,
 }
 
-/// Tells the converter how to perform certain conversions.
-/// See docs for individual fields for more info.
-#[derive(Debug)]
-pub struct Config {
-    /// Numeric values starting with 0 will be treated as strings.
-    /// E.g. convert `<agent>007</agent>` into `"agent":"007"` or `"agent":7`
-    /// Defaults to `false`.
-    pub leading_zero_as_string: bool,
-    /// Prefix XML attribute names with this value to distinguish them from XML elements.
-    /// E.g. set it to `@` for `<x a="Hello!" />` to become `{"x": {"@a":"Hello!"}}`
-    /// or set it to a blank string for `{"x": {"a":"Hello!"}}`
-    /// Defaults to `@`.
-    pub xml_attr_prefix: String,
-    /// A property name for XML text nodes.
-    /// E.g. set it to `text` for `<x a="Hello!">Goodbye!</x>` to become `{"x": {"@a":"Hello!", "text":"Goodbye!"}}`
-    /// XML nodes with text only and no attributes or no child elements are converted into JSON properties with the
-    /// name of the element. E.g. `<x>Goodbye!</x>` becomes `{"x":"Goodbye!"}`
-    /// Defaults to `#text`
-    pub xml_text_node_prop_name: String,
-    /// Defines how empty elements like `<x />` should be handled.
-    pub empty_element_handling: NullValue,
-    /// A map of XML paths with their JsonArray overrides. They take precedence over the document-wide `json_type`
-    /// property. The path syntax is based on xPath: literal element names and attribute names prefixed with `@`.
-    /// The path must start with a leading `/`. It is a bit of an inconvenience to remember about it, but it saves
-    /// an extra `if`-check in the code to improve the performance.
-    /// # Example
-    /// - **XML**: `<a><b c="123">007</b></a>`
-    /// - path for `c`: `/a/b/@c`
-    /// - path for `b` text node (007): `/a/b`
-    #[cfg(feature = "json_types")]
-    pub json_type_overrides: HashMap<String, JsonArray>,
-    /// A list of pairs of regex and JsonArray overrides. They take precedence over both the document-wide `json_type`
-    /// property and the `json_type_overrides` property. The path syntax is based on xPath just like `json_type_overrides`.
-    #[cfg(feature = "regex_path")]
-    pub json_regex_type_overrides: Vec<(Regex, JsonArray)>,
+/// Defines the output of `JsonType::Measurement` for text with one of the units.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MeasurementEmit {
+    /// Only the number, e.g. `<len>15mm</len>` becomes `{"len":15}`.
+    NumberOnly,
+    /// An object with the number and the unit, e.g. `{"len":{"value":15,"unit":"mm"}}` with `value_key`
+    /// set to `value` and `unit_key` set to `unit`.
+    Object { value_key: String, unit_key: String },
 }
 
-impl Config {
-    /// Numbers with leading zero will be treated as numbers.
-    /// Prefix XML Attribute names with `@`
-    /// Name XML text nodes `#text` for XML Elements with other children
-    pub fn new_with_defaults() -> Self {
-        Config {
-            leading_zero_as_string: false,
-            xml_attr_prefix: "@".to_owned(),
-            xml_text_node_prop_name: "#text".to_owned(),
-            empty_element_handling: NullValue::EmptyObject,
-            #[cfg(feature = "json_types")]
-            json_type_overrides: HashMap::new(),
-            #[cfg(feature = "regex_path")]
-            json_regex_type_overrides: Vec::new(),
-        }
-    }
-
-    /// Create a Config object with non-default values. See the `Config` struct docs for more info.
-    pub fn new_with_custom_values(
-        leading_zero_as_string: bool,
-        xml_attr_prefix: &str,
-        xml_text_node_prop_name: &str,
-        empty_element_handling: NullValue,
-    ) -> Self {
-        Config {
-            leading_zero_as_string,
-            xml_attr_prefix: xml_attr_prefix.to_owned(),
-            xml_text_node_prop_name: xml_text_node_prop_name.to_owned(),
-            empty_element_handling,
-            #[cfg(feature = "json_types")]
-            json_type_overrides: HashMap::new(),
-            #[cfg(feature = "regex_path")]
-            json_regex_type_overrides: Vec::new(),
-        }
-    }
+/// Defines what happens to string values with control characters that are illegal in JSON strings.
+/// Such characters are legal in XML 1.1 and may slip through character references like `&#0;`.
+/// `serde_json` escapes them on output, but many JSON consumers still choke on them.
+/// Tab, line feed and carriage return are not affected.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonStringPolicy {
+    /// Leave the values as they are. This is the default.
+    Keep,
+    /// Remove the control characters from the values.
+    StripControls,
+    /// Replace every control character with the given character.
+    ReplaceWith(char),
+    /// Stop the conversion with `ConversionError::IllegalCharacter`.
+    Error,
+}
 
-    /// Adds a single JSON Type override rule to the current config.
-    /// # Example
-    /// - **XML**: `<a><b c="123">007</b></a>`
-    /// - path for `c`: `/a/b/@c`
-    /// - path for `b` text node (007): `/a/b`
-    /// - regex path for any `element` node: `(\w/)*element$` [requires `regex_path` feature]
-    #[cfg(feature = "json_types")]
-    pub fn add_json_type_override<P>(self, path: P, json_type: JsonArray) -> Self
-    where
-        P: Into<PathMatcher>
-    {
-        let mut conf = self;
+/// Selects the JSON types `JsonType::Infer` may produce, e.g. to keep `true` or `null` texts as strings.
+/// Values that don't match any enabled type are converted into JSON strings.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct InferenceFlags {
+    /// Convert integers and floats into JSON numbers. Defaults to `true`.
+    pub numbers: bool,
+    /// Convert `true` and `false` into JSON bool. Defaults to `true`.
+    pub booleans: bool,
+    /// Convert `null` into JSON null. Defaults to `false`.
+    pub nulls: bool,
+}
 
-        match path.into() {
-            PathMatcher::Absolute(path) => {
-                conf.json_type_overrides.insert(path, json_type);
-            }
-            #[cfg(feature = "regex_path")]
-            PathMatcher::Regex(regex) => {
-                conf.json_regex_type_overrides.push((
-                    regex,
-                    json_type
-                ));
-            }
+impl Default for InferenceFlags {
+    fn default() -> Self {
+        InferenceFlags {
+            numbers: true,
+            booleans: true,
+            nulls: false,
         }
-
-        conf
     }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Config::new_with_defaults()
-    }
+/// Selects the conversion rules of a crate release, so that upgrading the crate doesn't change
+/// the output for the same XML and `Config`. Features added after the release are off by default
+/// and are not affected by the profile.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConversionProfile {
+    /// The rules of 0.6 releases:
+    /// * the child elements of elements with text are dropped, e.g. `<a>x<b>1</b></a>` becomes `{"a":"x"}`
+    /// * line breaks and tabs in attribute values are kept, and leading and trailing whitespace is trimmed
+    /// * negative integers become floats, e.g. `-1` becomes `-1.0`
+    V0_6,
+    /// The rules of the current release. This is the default.
+    /// * elements with text and child elements keep both, with the text stored in `Config::xml_text_node_prop_name`
+    /// * line breaks and tabs in attribute values are replaced with spaces, line breaks everywhere else
+    ///   are normalized into `\n` as required by the XML spec, and whitespace from character references
+    ///   like `&#10;` is not trimmed
+    /// * negative integers within the range of `i64` become integers
+    Latest,
 }
 
-/// Returns the text as one of `serde::Value` types: int, float, bool or string.
-fn parse_text(text: &str, leading_zero_as_string: bool, json_type: &JsonType) -> Value {
-    let text = text.trim();
+impl ConversionProfile {
+    /// Negative integers are parsed as floats.
+    fn negative_int_as_float(self) -> bool {
+        self != ConversionProfile::Latest
+    }
 
-    // enforce JSON String data type regardless of the underlying type
-    if json_type == &JsonType::AlwaysString {
-        return Value::String(text.into());
+    /// Elements with text and child elements keep the child elements.
+    fn keeps_mixed_content(self) -> bool {
+        self == ConversionProfile::Latest
     }
 
-    // enforce JSON Bool data type
-    #[cfg(feature = "json_types")]
-    if let JsonType::Bool(true_values) = json_type {
-        if true_values.contains(&text) {
-            // any values matching the `true` list are bool/true
-            return Value::Bool(true);
+    /// Line breaks and attribute values are normalized before parsing.
+    fn normalizes_whitespace(self) -> bool {
+        self == ConversionProfile::Latest
+    }
+
+    /// Trims an attribute value according to the rules of the profile.
+    fn trim_attr(self, value: &str) -> &str {
+        if self.normalizes_whitespace() {
+            // literal whitespace was normalized into spaces, so only spaces are trimmed
+            // to keep characters from references like `&#10;`
+            value.trim_matches(' ')
         } else {
-            // anything else is false
-            return Value::Bool(false);
+            value.trim()
         }
     }
+}
 
-    // ints
-    if let Ok(v) = text.parse::<u64>() {
-        // don't parse octal numbers and those with leading 0
-        // `text` value "0" will always be converted into number 0, "0000" may be converted
-        // into 0 or "0000" depending on `leading_zero_as_string`
-        if leading_zero_as_string && text.starts_with("0") && (v != 0 || text.len() > 1) {
-            return Value::String(text.into());
+/// Defines how `JsonType::Infer` values are parsed into JSON numbers.
+#[derive(Clone)]
+pub enum NumberParser {
+    /// Parse integers into `u64` or `i64` and everything else into `f64`. This is the default.
+    Standard,
+    /// Keep the digits of the values exactly as they are in the XML, e.g. `3.14159265358979323846`.
+    /// Values with leading zeros follow the `Standard` rules.
+    /// Requires `arbitrary_precision` feature, which enables the same feature of `serde_json`.
+    #[cfg(feature = "arbitrary_precision")]
+    ArbitraryPrecision,
+    /// Parse integers into `u128` or `i128`, everything else into `f64`. Integers beyond the range of
+    /// `u64` and `i64` remain strings, unless `arbitrary_precision` feature is enabled.
+    I128U128,
+    /// Parse the text with the function, which returns `None` if the text is not a number.
+    /// The function is responsible for `Config::leading_zero_as_string` handling.
+    #[allow(clippy::type_complexity)]
+    Custom(Arc<dyn Fn(&str) -> Option<Value> + Send + Sync>),
+}
+
+impl fmt::Debug for NumberParser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberParser::Standard => write!(f, "Standard"),
+            #[cfg(feature = "arbitrary_precision")]
+            NumberParser::ArbitraryPrecision => write!(f, "ArbitraryPrecision"),
+            NumberParser::I128U128 => write!(f, "I128U128"),
+            NumberParser::Custom(_) => write!(f, "Custom(..)"),
         }
-        return Value::Number(Number::from(v));
     }
+}
 
-    // floats
-    if let Ok(v) = text.parse::<f64>() {
-        if text.starts_with("0") && !text.starts_with("0.") {
-            return Value::String(text.into());
-        }
-        if let Some(val) = Number::from_f64(v) {
-            return Value::Number(val);
+/// Selects the attributes converted into properties without `Config::xml_attr_prefix`,
+/// as if they were child elements. E.g. promoting `id` turns `<user id="7"><name>x</name></user>`
+/// into `{"user":{"id":7,"name":"x"}}`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PromoteAttrs {
+    /// Keep the prefix for all attributes. This is the default.
+    None,
+    /// Promote all attributes.
+    All,
+    /// Promote the attributes with these names anywhere in the document.
+    Named(Vec<String>),
+    /// Promote the attributes at these absolute paths, e.g. `/user/@id`.
+    Paths(Vec<String>),
+}
+
+impl PromoteAttrs {
+    /// Returns `true` if the attribute with this name and XML path is promoted.
+    fn promotes(&self, name: &str, path: &str) -> bool {
+        match self {
+            PromoteAttrs::None => false,
+            PromoteAttrs::All => true,
+            PromoteAttrs::Named(names) => names.iter().any(|n| n == name),
+            PromoteAttrs::Paths(paths) => {
+                // the leading slash is optional, like in `Config::add_json_type_override`
+                let path = path.trim_start_matches('/');
+                paths.iter().any(|p| p.trim_start_matches('/') == path)
+            }
         }
     }
+}
 
-    // booleans
-    if let Ok(v) = text.parse::<bool>() {
-        return Value::Bool(v);
-    }
+/// A function applied to attribute values before the type inference, see `Config::add_attr_value_transform`.
+#[derive(Clone)]
+pub struct AttrValueTransform(pub Arc<dyn Fn(&str) -> String + Send + Sync>);
 
-    Value::String(text.into())
+impl fmt::Debug for AttrValueTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AttrValueTransform(..)")
+    }
 }
 
-/// Converts an XML Element into a JSON property
-fn convert_node(el: &Element, config: &Config, path: &String) -> Option<Value> {
-    // add the current node to the path
-    #[cfg(feature = "json_types")]
-    let path = [path, "/", el.name()].concat();
+/// A function computing a property from the converted attributes of an element, see `Config::add_virtual_attribute`.
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct VirtualAttribute(pub Arc<dyn Fn(&Map<String, Value>) -> Option<Value> + Send + Sync>);
 
-    // get the json_type for this node
-    let (_, json_type_value) = get_json_type(config, &path);
+impl fmt::Debug for VirtualAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VirtualAttribute(..)")
+    }
+}
 
-    // is it an element with text?
-    if el.text().trim() != "" {
-        // process node's attributes, if present
-        if el.attrs().count() > 0 {
-            Some(Value::Object(
-                el.attrs()
-                    .map(|(k, v)| {
-                        // add the current node to the path
-                        #[cfg(feature = "json_types")]
-                        let path = [path.clone(), "/@".to_owned(), k.to_owned()].concat();
-                        // get the json_type for this node
-                        #[cfg(feature = "json_types")]
-                        let (_, json_type_value) = get_json_type(config, &path);
-                        (
-                            [config.xml_attr_prefix.clone(), k.to_owned()].concat(),
-                            parse_text(&v, config.leading_zero_as_string, &json_type_value),
-                        )
-                    })
-                    .chain(vec![(
-                        config.xml_text_node_prop_name.clone(),
-                        parse_text(
-                            &el.text()[..],
-                            config.leading_zero_as_string,
-                            &json_type_value,
-                        ),
-                    )])
-                    .collect(),
-            ))
-        } else {
-            Some(parse_text(
-                &el.text()[..],
-                config.leading_zero_as_string,
-                &json_type_value,
-            ))
-        }
-    } else {
-        // this element has no text, but may have other child nodes
-        let mut data = Map::new();
+/// A function converting the text of every text node and attribute into its JSON value, see
+/// `ValueOptions::text_node_mapper`. It receives the XML path, e.g. `/a/b` or `/a/@c`, and the trimmed text.
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct TextNodeMapper(pub Arc<dyn Fn(&str, &str) -> Value + Send + Sync>);
 
-        for (k, v) in el.attrs() {
-            // add the current node to the path
-            #[cfg(feature = "json_types")]
-            let path = [path.clone(), "/@".to_owned(), k.to_owned()].concat();
-            // get the json_type for this node
-            #[cfg(feature = "json_types")]
-            let (_, json_type_value) = get_json_type(config, &path);
-            data.insert(
-                [config.xml_attr_prefix.clone(), k.to_owned()].concat(),
-                parse_text(&v, config.leading_zero_as_string, &json_type_value),
-            );
-        }
+impl fmt::Debug for TextNodeMapper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TextNodeMapper(..)")
+    }
+}
 
-        // process child element recursively
-        for child in el.children() {
-            match convert_node(child, config, &path) {
-                Some(val) => {
-                    let name = &child.name().to_string();
+/// A value added to the objects of elements at a path, see `InjectionOptions::injected_fields`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum InjectedValue {
+    /// The same value in every document.
+    Constant(Value),
+    /// The name of the source passed to `xml_str_to_json_with_source`, e.g. a file name.
+    /// `null` if the document was converted without one.
+    SourceName,
+    /// The UTC time of the conversion in RFC 3339 format, e.g. `2024-05-01T12:30:00Z`.
+    /// All objects of the same document get the same time.
+    TimestampRfc3339,
+}
 
-                    #[cfg(feature = "json_types")]
-                    let path = [path.clone(), "/".to_owned(), name.clone()].concat();
-                    let (json_type_array, _) = get_json_type(config, &path);
-                    // does it have to be an array?
-                    if json_type_array || data.contains_key(name) {
-                        // was this property converted to an array earlier?
-                        if data.get(name).unwrap_or(&Value::Null).is_array() {
-                            // add the new value to an existing array
-                            data.get_mut(name)
-                                .unwrap()
-                                .as_array_mut()
-                                .unwrap()
-                                .push(val);
-                        } else {
-                            // convert the property to an array with the existing and the new values
-                            let new_val = match data.remove(name) {
-                                None => vec![val],
-                                Some(temp) => vec![temp, val],
-                            };
-                            data.insert(name.clone(), Value::Array(new_val));
-                        }
-                    } else {
-                        // this is the first time this property is encountered and it doesn't
-                        // have to be an array, so add it as-is
-                        data.insert(name.clone(), val);
-                    }
-                }
-                _ => (),
-            }
-        }
+/// The kind of XML node a value passed to `ValueInterceptor::on_value` comes from.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NodeKind {
+    /// The value of an attribute, the path ends with `/@name`
+    Attribute,
+    /// The text of an element
+    Text,
+}
 
-        // return the JSON object if it's not empty
-        if !data.is_empty() {
-            return Some(Value::Object(data));
-        }
+/// An extension point for customizations without a dedicated `Config` option, see `InjectionOptions::interceptor`.
+/// Both methods are called after the crate's own processing, so they see the final values and can
+/// keep, rewrite or drop them.
+pub trait ValueInterceptor: Send + Sync {
+    /// Returns the value to use for an attribute or a text. `raw` is the trimmed text from the document
+    /// and `parsed` is the result of the type inference and the overrides.
+    fn on_value(&self, path: &str, kind: NodeKind, raw: &str, parsed: Value) -> Value;
 
-        // empty objects are treated according to config rules set by the caller
-        match config.empty_element_handling {
-            NullValue::Null => Some(Value::Null),
-            NullValue::EmptyObject => Some(Value::Object(data)),
-            NullValue::Ignore => None,
-        }
+    /// Called with every JSON object created from an element once all its properties were added.
+    #[allow(unused_variables)]
+    fn on_object(&self, path: &str, obj: &mut Map<String, Value>) {}
+}
+
+impl fmt::Debug for dyn ValueInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ValueInterceptor")
     }
 }
 
-fn xml_to_map(e: &Element, config: &Config) -> Value {
-    let mut data = Map::new();
-    data.insert(
-        e.name().to_string(),
-        convert_node(&e, &config, &String::new()).unwrap_or(Value::Null),
-    );
-    Value::Object(data)
+/// Defines how the text nodes of an element are joined, e.g. the text split by a comment in `<p>foo<!--x-->bar</p>`
+/// or by a CDATA section. The joined text is trimmed either way.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TextJoin {
+    /// Concatenate the text nodes as they are, e.g. `foobar`. This is the default.
+    Concat,
+    /// Trim the text nodes and join the non-empty ones with a space, e.g. `foo bar`.
+    Space,
+    /// Trim the text nodes and join the non-empty ones with the separator.
+    Separator(String),
+    /// Trim the text nodes and convert the non-empty ones into an array under `Config::xml_text_node_prop_name`,
+    /// e.g. `{"p":{"#text":["foo","bar"]}}`. Elements with a single text node are not affected.
+    KeepSegments,
 }
 
-/// Converts the given XML string into `serde::Value` using settings from `Config` struct.
-pub fn xml_str_to_json(xml: &str, config: &Config) -> Result<Value, Error> {
-    let root = Element::from_str(xml)?;
-    Ok(xml_to_map(&root, config))
+/// Defines what happens to the tail text of child elements, e.g. `after` in `<p>before<b>x</b>after</p>`.
+/// The tail text is a separate text node of the parent element.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TailTextHandling {
+    /// Join the tail text with the text of the parent, e.g. `{"p":"beforeafter"}`. This is the default and
+    /// how it was handled prior to this option.
+    AppendToParent,
+    /// Drop the tail text, keeping only the text before the first child element, e.g. `{"p":"before"}`.
+    Ignore,
+    /// Drop the tail text from the text of the parent and add it to the child under the given key as a string,
+    /// e.g. `{"p":{"#text":"before","b":{"#text":"x","tail":"after"}}}` with `tail`. Child elements converted
+    /// into scalar values are turned into objects with the value under `Config::xml_text_node_prop_name`.
+    /// Older profiles drop the child elements of elements with text, and their tail text with them.
+    AsField(String),
 }
 
-/// Converts the given XML string into `serde::Value` using settings from `Config` struct.
-pub fn xml_string_to_json(xml: String, config: &Config) -> Result<Value, Error> {
-    xml_str_to_json(xml.as_str(), config)
+/// Defines how the default namespace, e.g. `xmlns="http://www.w3.org/1999/xhtml"`, appears in the output.
+/// It applies to the elements without a prefix inside the declaration. Elements with a prefix and attributes
+/// are never in the default namespace.
+#[derive(Debug, PartialEq, Clone)]
+pub enum NamespaceDefaultHandling {
+    /// Leave the namespace out of the output. This is the default.
+    Ignore,
+    /// Add the namespace URI under the given key to the objects of the elements in the namespace,
+    /// e.g. `{"html":{"_ns":"http://www.w3.org/1999/xhtml","body":{...}}}` with `_ns`.
+    /// Elements converted into scalar values, e.g. `<title>x</title>`, are left as-is.
+    AddToElement(String),
+    /// Prepend the given prefix to the property names of the elements in the namespace,
+    /// e.g. `{"h:html":{"h:body":{...}}}` with `h:`. The XML paths of the elements are not affected.
+    PrefixKeys(String),
 }
 
-/// Returns a tuple for Array and Value enforcements for the current node or
-/// `(false, JsonArray::Infer(JsonType::Infer)` if the current path is not found
-/// in the list of paths with custom config.
-#[cfg(feature = "json_types")]
-#[inline]
-fn get_json_type_with_absolute_path<'conf>(config: &'conf Config, path: &String) -> (bool, &'conf JsonType) {
-    match config
-    .json_type_overrides
-    .get(path)
-    .unwrap_or(&JsonArray::Infer(JsonType::Infer))
-    {
-        JsonArray::Infer(v) => (false, v),
-        JsonArray::Always(v) => (true, v),
-    }
+/// Defines what happens to an attribute repeated on the same element, e.g. `<a href="1" href="2">`.
+/// Such documents are not well-formed, so only `QuickXmlParser` applies the policies other than `Error`,
+/// while the default parser always rejects them.
+#[derive(Debug, PartialEq, Clone)]
+pub enum DupAttr {
+    /// Keep the first value, e.g. `{"a":{"@href":1}}`.
+    First,
+    /// Keep the last value, e.g. `{"a":{"@href":2}}`.
+    Last,
+    /// Keep all values in document order in an array, e.g. `{"a":{"@href":[1,2]}}`.
+    /// The attributes without repeats remain single values.
+    MergeArray,
+    /// Reject the document with an XML error. This is the default.
+    Error,
 }
 
-/// Simply returns `get_json_type_with_absolute_path` if `regex_path` feature is disabled.
-#[cfg(feature = "json_types")]
-#[cfg(not(feature = "regex_path"))]
-#[inline]
-fn get_json_type<'conf>(config: &'conf Config, path: &String) -> (bool, &'conf JsonType) {
-    get_json_type_with_absolute_path(config, path)
+/// Where the properties passed to `xml_string_to_json_with_context` are added.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ContextPlacement {
+    /// Add them to the object of the root element, e.g. `{"orders":{"trace_id":"x","order":[...]}}`.
+    /// This is the default.
+    Root,
+    /// Add them to the objects of the child elements of the root element, e.g. to every record of a batch:
+    /// `{"orders":{"order":[{"trace_id":"x",...},{"trace_id":"x",...}]}}`.
+    Children,
 }
 
-/// Returns a tuple for Array and Value enforcements for the current node. Searches both absolute paths
-/// and regex paths, giving precedence to regex paths. Returns `(false, JsonArray::Infer(JsonType::Infer)`
-/// if the current path is not found in the list of paths with custom config.
-#[cfg(feature = "json_types")]
-#[cfg(feature = "regex_path")]
-#[inline]
-fn get_json_type<'conf>(config: &'conf Config, path: &String) -> (bool, &'conf JsonType) {
-    for (regex, json_array) in &config.json_regex_type_overrides {
-        if regex.is_match(path) {
-            return match json_array {
-                JsonArray::Infer(v) => (false, v),
-                JsonArray::Always(v) => (true, v),
-            };
-        }
-    }
+/// Defines what happens to a field missing from the object of its parent element, see
+/// `InjectionOptions::absent_field_policy`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AbsentPolicy {
+    /// Leave the field out. This is the default for all paths.
+    Omit,
+    /// Add the field with `null`.
+    Null,
+}
 
-    get_json_type_with_absolute_path(config, path)
+/// Defines what happens to XML comments inside the elements, e.g. `<a><!-- note --><b>1</b></a>`.
+/// Comments are trimmed and stored next to the attributes of the element they belong to, turning
+/// elements with text into objects with `Config::xml_text_node_prop_name` property. The comments before
+/// the root element are stored the same way at the top level of the output, next to the root element.
+#[derive(Debug, PartialEq, Clone)]
+pub enum CommentHandling {
+    /// Drop the comments. This is the default.
+    Ignore,
+    /// Store the last comment of the element as a string property with the given name,
+    /// e.g. `{"a":{"#comment":"note","b":1}}`.
+    PreserveAsField(String),
+    /// Store all comments of the element as an array of strings in the property with the given name,
+    /// e.g. `{"a":{"#comments":["note"],"b":1}}`.
+    PreserveAsArray(String),
 }
 
-/// Always returns `(false, JsonArray::Infer(JsonType::Infer)` if `json_types` feature is not enabled.
-#[cfg(not(feature = "json_types"))]
-#[inline]
-fn get_json_type<'conf>(_config: &'conf Config, _path: &String) -> (bool, &'conf JsonType) {
-    (false, &JsonType::Infer)
+/// A non-fatal issue found during the conversion. See `xml_str_to_json_with_warnings`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Warning {
+    /// String values with control characters were found and handled according to `ValueOptions::json_string_policy`.
+    /// `count` is the number of affected values and `paths` lists the XML path of each of them.
+    IllegalJsonCharacters { count: usize, paths: Vec<String> },
+    /// Elements matching `Config::tuple_rules` had attributes not listed in the rule, text or child elements,
+    /// and were converted into objects instead. `paths` lists the XML path of each of them.
+    TupleRuleMismatch { count: usize, paths: Vec<String> },
+    /// The type inference decision for a value at one of `DiagnosticOptions::trace_paths`, reported for every value.
+    TypeDecision { path: String, decision: Decision },
+    /// The objects of an array of repeated elements have different properties, found with
+    /// `DiagnosticOptions::array_homogeneity_check`. `differing_keys` lists the properties missing from some of the
+    /// objects in alphabetical order. Reported once for every such array.
+    HeterogeneousArray { path: String, differing_keys: Vec<String> },
+    /// The element at `path` was turned into an array of its `item` children by
+    /// `ArrayOptions::auto_flatten_plural_wrappers`. Reported for every flattened element.
+    PluralWrapperFlattened { path: String, item: String },
+}
+
+/// Describes how the type of a value was decided, see `DiagnosticOptions::trace_paths`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Decision {
+    /// The trimmed text of the value
+    pub input_text: String,
+    pub branch: Branch,
+    /// The JSON type of the result: `number`, `string`, `bool` or `null`
+    pub output_type: &'static str,
+}
+
+/// The rule of the type inference which decided the type of a value.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Branch {
+    /// An integer within the range of `u64`
+    U64,
+    /// A negative integer within the range of `i64`
+    I64,
+    /// An integer beyond the range of `u64`, with `NumberParser::I128U128`
+    U128,
+    /// A negative integer beyond the range of `i64`, with `NumberParser::I128U128`
+    I128,
+    /// A float, or a negative integer with `ConversionProfile::V0_6`
+    F64,
+    /// A number parsed by `NumberParser::ArbitraryPrecision`
+    ArbitraryPrecision,
+    /// A number parsed by `NumberParser::Custom`
+    CustomNumber,
+    /// `true` or `false`, or `1` or `0` with `ValueOptions::boolean_from_numeric`
+    Bool,
+    /// A number kept as a string because of its leading zero
+    LeadingZeroString,
+    /// `null` with `InferenceFlags::nulls`
+    SentinelNull,
+    /// A value at a path with `JsonType::AlwaysString` override
+    OverrideString,
+    /// A value at a path with `JsonType::Bool` override
+    OverrideBool,
+    /// A decimal number at a path with `JsonType::DecimalString { normalize: true }` override
+    NormalizedDecimal,
+    /// A number with a unit at a path with `JsonType::Measurement` override
+    Measurement,
+    /// A known value at a path with `Config::enum_mappings`
+    EnumMapping,
+    /// A value returned by `ValueOptions::text_node_mapper`
+    TextNodeMapper,
+    /// An attribute value listed in `ValueOptions::bool_truthy` or `ValueOptions::bool_falsy` with
+    /// `ValueOptions::coerce_bool_attrs`
+    CoercedBool,
+    /// Text with a CDATA section with `TextOptions::cdata_as_string`
+    Cdata,
+    /// Nothing else matched and the value remains a string
+    FallbackString,
+}
+
+/// Errors returned by the conversion functions. New variants may be added with new options.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConversionError {
+    /// The input is not a well-formed XML document.
+    Xml(XmlError),
+    /// The input could not be parsed by an `XmlParser` implemented outside of this crate, which has no
+    /// `XmlError` to return. The built-in parsers return `Xml`.
+    ParseError(String),
+    /// The elements are nested deeper than `InputOptions::max_xml_depth`.
+    DepthLimitExceeded,
+    /// The value at this path is not a number and `ValueOptions::strict_json_types` is set.
+    TypeConversionFailed { path: String, value: String },
+    /// A value at this XML path contains a control character and `ValueOptions::json_string_policy`
+    /// is set to `JsonStringPolicy::Error`.
+    IllegalCharacter { path: String },
+    /// The entity resolver returned nothing for an entity referenced in the document.
+    UnknownEntity { name: String },
+    /// The input could not be read.
+    Io(std::io::Error),
+    /// The input is not valid UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// The conversion was canceled by the caller, see `xml_string_to_json_cancelable`.
+    Canceled,
+    /// The element at this path already has a property with the name from `InjectionOptions::node_id`
+    /// and the clash policy is `NodeIdClash::Error`.
+    NodeIdClash { path: String },
+    /// The jq filter is invalid or failed, see `xml_string_to_json_transformed`.
+    TransformError(String),
+    /// The document starts with whitespace before the XML declaration and `InputOptions::strict_prolog` is set.
+    MisplacedXmlDeclaration,
+    /// The document is not encoded as UTF-8, e.g. it starts with a UTF-16 byte order mark, or
+    /// `OutputOptions::output_encoding` can't be written by `xml_string_to_json_encoded`, e.g. UTF-16.
+    UnsupportedEncoding { encoding: String },
+    /// The converted value could not be serialized, e.g. by `xml_string_to_cbor`, `xml_string_to_msgpack`
+    /// or `xml_string_to_json_bytes`.
+    SerializationError(String),
+    /// The element at this path already has a property with the name of an injected field
+    /// and `InjectionOptions::injected_fields_on_clash` is `NodeIdClash::Error`.
+    InjectedFieldClash { path: String, key: String },
+    /// The JSON value at this path has no XML form, see `json_to_xml`.
+    UnsupportedJson { path: String, reason: String },
+    /// A name is longer than `KeyOptions::max_name_length` and `KeyOptions::long_name_policy` is
+    /// `LongNamePolicy::Error`. `start` holds its first 64 characters and `length` its length in bytes.
+    NameTooLong { start: String, length: usize },
+    /// The element at this path has the name of the text property and `TextOptions::text_prop_collision`
+    /// is `TextPropCollision::Error`.
+    TextPropCollision { path: String },
+    /// The template passed to `xml_string_to_json_template` is not valid JSON or has an unterminated placeholder.
+    InvalidTemplate(String),
+    /// The XML path of a template placeholder was not found in the document, see `xml_string_to_json_template`.
+    MissingTemplatePath { path: String },
+    /// The XML version of the document is not listed in `InputOptions::allowed_versions`.
+    UnsupportedXmlVersion { version: String },
+    /// A key of `KeyOptions::dual_emit` clashes with a property of the element at this path.
+    DualKeyClash { path: String, key: String },
+    /// The property name of the node at this path has non-ASCII characters and `KeyOptions::key_charset`
+    /// is `KeyCharset::ErrorOnNonAscii`.
+    NonAsciiKey { path: String, key: String },
+    /// This option has non-ASCII characters, which `KeyOptions::key_charset` doesn't allow in property names.
+    NonAsciiKeyOption { option: String },
+    /// The record passed to `Config::from_embedded_config` has an option with a value of the wrong type.
+    InvalidEmbeddedConfig(String),
+    /// This option of the config needs a feature of the crate which is not enabled, see `Config::validate`.
+    MissingFeature { option: String, feature: String },
+    /// The array of the elements at this path has more members than `SpillConfig::threshold_members`,
+    /// which only `xml_to_json_writer` can convert.
+    SpillRequired { path: String },
+    /// This option works on the entire output and can't be combined with `OutputOptions::spill`.
+    SpillUnsupported { option: String },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::Xml(e) => write!(f, "malformed XML: {}", e),
+            ConversionError::ParseError(e) => write!(f, "malformed XML: {}", e),
+            ConversionError::DepthLimitExceeded => write!(f, "the elements are nested too deep"),
+            ConversionError::TypeConversionFailed { path, value } => {
+                write!(f, "the value {} at {} is not a number", value, path)
+            }
+            ConversionError::IllegalCharacter { path } => {
+                write!(f, "control character in the value at {}", path)
+            }
+            ConversionError::UnknownEntity { name } => write!(f, "unknown entity &{};", name),
+            ConversionError::Io(e) => write!(f, "failed to read XML: {}", e),
+            ConversionError::Utf8(e) => write!(f, "XML is not valid UTF-8: {}", e),
+            ConversionError::Canceled => write!(f, "the conversion was canceled"),
+            ConversionError::NodeIdClash { path } => {
+                write!(f, "the node identifier clashes with a property at {}", path)
+            }
+            ConversionError::TransformError(e) => write!(f, "jq filter failed: {}", e),
+            ConversionError::MisplacedXmlDeclaration => {
+                write!(f, "the XML declaration must be at the start of the document")
+            }
+            ConversionError::UnsupportedEncoding { encoding } => {
+                write!(f, "unsupported encoding {}", encoding)
+            }
+            ConversionError::SerializationError(e) => write!(f, "serialization failed: {}", e),
+            ConversionError::InjectedFieldClash { path, key } => {
+                write!(f, "the injected field {} clashes with a property at {}", key, path)
+            }
+            ConversionError::UnsupportedJson { path, reason } => {
+                write!(f, "unsupported JSON at {}: {}", path, reason)
+            }
+            ConversionError::NameTooLong { start, length } => {
+                write!(f, "the name starting with {} is too long ({} bytes)", start, length)
+            }
+            ConversionError::TextPropCollision { path } => {
+                write!(f, "the element at {} has the name of the text property", path)
+            }
+            ConversionError::InvalidTemplate(e) => write!(f, "invalid template: {}", e),
+            ConversionError::MissingTemplatePath { path } => {
+                write!(f, "the template path {} is not in the document", path)
+            }
+            ConversionError::UnsupportedXmlVersion { version } => {
+                write!(f, "XML version {} is not allowed", version)
+            }
+            ConversionError::DualKeyClash { path, key } => {
+                write!(f, "the dual key {} clashes with a property at {}", key, path)
+            }
+            ConversionError::InvalidEmbeddedConfig(e) => write!(f, "invalid embedded config: {}", e),
+            ConversionError::NonAsciiKey { path, key } => {
+                write!(f, "the property name {} of {} has non-ASCII characters", key, path)
+            }
+            ConversionError::NonAsciiKeyOption { option } => {
+                write!(f, "Config::{} has non-ASCII characters", option)
+            }
+            ConversionError::MissingFeature { option, feature } => {
+                write!(f, "Config::{} requires `{}` feature", option, feature)
+            }
+            ConversionError::SpillRequired { path } => {
+                write!(f, "the array at {} is too large, use xml_to_json_writer to spill it", path)
+            }
+            ConversionError::SpillUnsupported { option } => {
+                write!(f, "Config::{} can't be combined with OutputOptions::spill", option)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// An error reported by the XML parser. Use `Display` to get the error message
+/// or enable `minidom_interop` feature to access the parser error.
+#[derive(Debug)]
+pub struct XmlError(Error);
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+#[cfg(feature = "minidom_interop")]
+impl XmlError {
+    /// Returns the underlying `minidom` error.
+    pub fn as_minidom(&self) -> &minidom::Error {
+        &self.0
+    }
+}
+
+impl From<minidom::Error> for ConversionError {
+    fn from(e: minidom::Error) -> Self {
+        ConversionError::Xml(XmlError(e))
+    }
+}
+
+/// The pseudo-attributes of the XML declaration, e.g. `<?xml version="1.0" standalone="yes"?>`.
+/// The fields are `None` if the document has no declaration or the declaration doesn't have them.
+/// See `xml_str_to_json_with_prolog`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Prolog {
+    pub version: Option<String>,
+    pub encoding: Option<String>,
+    /// `true` for `standalone="yes"`, `false` for `standalone="no"`
+    pub standalone: Option<bool>,
+}
+
+impl Prolog {
+    /// Reads the XML declaration at the start of the document, if any. The parser drops it,
+    /// so it is scanned separately. A byte order mark and leading whitespace are skipped.
+    pub fn from_xml(xml: &str) -> Self {
+        let mut prolog = Prolog::default();
+        let xml = xml.strip_prefix('\u{feff}').unwrap_or(xml).trim_start();
+        let declaration = match xml.strip_prefix("<?xml") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => match rest.find("?>") {
+                Some(end) => &rest[..end],
+                None => return prolog,
+            },
+            _ => return prolog,
+        };
+
+        // pseudo-attributes look like attributes: `name = "value"` or `name='value'`
+        let mut rest = declaration;
+        while let Some(eq) = rest.find('=') {
+            let name = rest[..eq].trim();
+            let value = rest[eq + 1..].trim_start();
+            let quote = match value.chars().next() {
+                Some(q) if q == '"' || q == '\'' => q,
+                _ => break,
+            };
+            let value = &value[1..];
+            let end = match value.find(quote) {
+                Some(end) => end,
+                None => break,
+            };
+            let text = value[..end].to_owned();
+            match name {
+                "version" => prolog.version = Some(text),
+                "encoding" => prolog.encoding = Some(text),
+                "standalone" => prolog.standalone = Some(text == "yes"),
+                _ => (),
+            }
+            rest = &value[end + 1..];
+        }
+        prolog
+    }
+}
+
+/// Parses the XML document into a tree of `minidom` elements.
+fn parse_xml(xml: &str, config: &Config) -> Result<ParsedDocument, ConversionError> {
+    parse_xml_with::<MinidomParser>(xml, config)
+}
+
+/// Applies the options of the config working on the raw text and parses the document with the parser.
+fn parse_xml_with<P: XmlParser>(xml: &str, config: &Config) -> Result<ParsedDocument, ConversionError> {
+    let xml = skip_leading_padding(xml, config)?;
+    if let Some(allowed_versions) = &config.input.allowed_versions {
+        // documents without a declaration are XML 1.0
+        let version = Prolog::from_xml(xml).version.unwrap_or_else(|| "1.0".to_owned());
+        if !allowed_versions.contains(&version) {
+            return Err(ConversionError::UnsupportedXmlVersion { version });
+        }
+    }
+    let xml = if config.profile.normalizes_whitespace() {
+        normalize_xml(xml)
+    } else if config.input.normalize_input {
+        normalize_line_endings(Cow::Borrowed(xml))
+    } else {
+        Cow::Borrowed(xml)
+    };
+    let mut doc = P::parse_with_config(&xml, config)?;
+    let cdata = config.text.cdata_as_string && xml.contains("<![CDATA[");
+    let instructions = config.text.processing_instructions_key.is_some() && xml.contains("<?");
+    if cdata || instructions {
+        scan_nodes(&xml, cdata, instructions, &mut doc.side_table);
+    }
+    Ok(doc)
+}
+
+/// Adds the CDATA sections of the document to the side table with `cdata`, see `TextOptions::cdata_as_string`,
+/// and the processing instructions inside of the root element with `instructions`,
+/// see `TextOptions::processing_instructions_key`. The parsers keep the non-empty sections as text nodes of their own
+/// and drop the processing instructions, so the nodes are found by counting the elements and the text nodes
+/// in document order.
+fn scan_nodes(xml: &str, cdata: bool, instructions: bool, side_table: &mut parser::SideTable) {
+    // the numbers of the open elements
+    let mut open = Vec::new();
+    let mut elements = 0;
+    let mut texts = 0;
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        let (before, tail) = rest.split_at(start);
+        // the parsers keep the non-empty text inside of the root element
+        if !open.is_empty() && !before.is_empty() {
+            texts += 1;
+        }
+        let end = if tail.starts_with("<!--") {
+            tail.find("-->").map(|end| end + 3)
+        } else if let Some(content) = tail.strip_prefix("<![CDATA[") {
+            content.find("]]>").map(|end| {
+                if let Some(&element) = open.last() {
+                    if cdata {
+                        side_table.cdata_elements.push(element);
+                    }
+                    if end > 0 {
+                        if cdata {
+                            side_table.cdata_texts.push(texts);
+                        }
+                        texts += 1;
+                    }
+                }
+                end + 12
+            })
+        } else if let Some(instruction) = tail.strip_prefix("<?") {
+            instruction.find("?>").map(|end| {
+                // the instructions before the root element are found by `prolog_nodes`
+                if let Some(&element) = open.last() {
+                    if instructions && !is_xml_declaration(&instruction[..end]) {
+                        side_table.instructions.push((element, instruction[..end].to_owned()));
+                    }
+                }
+                end + 4
+            })
+        } else if tail.starts_with("<!") {
+            // the declarations before the root element
+            tail.find('>').map(|end| end + 1)
+        } else if tail.starts_with("</") {
+            open.pop();
+            tail.find('>').map(|end| end + 1)
+        } else {
+            let end = tag_end(tail);
+            if end.is_some_and(|end| !tail[..end].ends_with("/>")) {
+                open.push(elements);
+            }
+            elements += 1;
+            end
+        };
+        match end {
+            Some(end) => rest = &tail[end..],
+            // the parser reports the unterminated markup
+            None => break,
+        }
+    }
+}
+
+/// Returns the length of the tag at the start of the text. Attribute values may contain `>`.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Skips a UTF-8 byte order mark and whitespace before the first markup, see `InputOptions::strict_prolog`.
+fn skip_leading_padding<'a>(xml: &'a str, config: &Config) -> Result<&'a str, ConversionError> {
+    let without_bom = xml.strip_prefix('\u{feff}').unwrap_or(xml);
+    let trimmed = without_bom.trim_start();
+
+    // the XML declaration must be the very first thing after the byte order mark
+    let declaration = trimmed
+        .strip_prefix("<?xml")
+        .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+    if config.input.strict_prolog && declaration && trimmed.len() != without_bom.len() {
+        return Err(ConversionError::MisplacedXmlDeclaration);
+    }
+
+    Ok(trimmed)
+}
+
+/// Applies the normalization rules of the XML spec the parser doesn't apply on its own:
+/// * end-of-line handling (2.11): literal `\r\n` and `\r` become `\n`
+/// * attribute-value normalization (3.3.3): literal tabs and line feeds in attribute values become spaces
+///
+/// Character references like `&#13;` or `&#10;` are not affected and keep their characters.
+fn normalize_xml<'a>(xml: &'a str) -> Cow<'a, str> {
+    let xml = normalize_line_endings(Cow::Borrowed(xml));
+
+    let positions = attribute_whitespace(&xml);
+    if positions.is_empty() {
+        return xml;
+    }
+
+    // only ASCII bytes are replaced, so the string remains valid UTF-8
+    let mut bytes = xml.into_owned().into_bytes();
+    for pos in positions {
+        bytes[pos] = b' ';
+    }
+    Cow::Owned(String::from_utf8(bytes).expect("only ASCII bytes were replaced"))
+}
+
+/// Replaces literal `\r\n` and `\r` with `\n`.
+fn normalize_line_endings(xml: Cow<str>) -> Cow<str> {
+    if xml.contains('\r') {
+        Cow::Owned(xml.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        xml
+    }
+}
+
+/// Returns byte positions of literal tabs and line feeds inside attribute values.
+fn attribute_whitespace(xml: &str) -> Vec<usize> {
+    let bytes = xml.as_bytes();
+    let mut positions = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'<' {
+            i += 1;
+            continue;
+        }
+
+        // comments, CDATA and processing instructions may contain quotes and `>`
+        let rest = &xml[i..];
+        let skip_to = if rest.starts_with("<!--") {
+            Some(rest.find("-->").map_or(rest.len(), |end| end + 3))
+        } else if rest.starts_with("<![CDATA[") {
+            Some(rest.find("]]>").map_or(rest.len(), |end| end + 3))
+        } else if rest.starts_with("<?") {
+            Some(rest.find("?>").map_or(rest.len(), |end| end + 2))
+        } else if rest.starts_with("<!") || rest.starts_with("</") {
+            Some(1)
+        } else {
+            None
+        };
+        if let Some(skip) = skip_to {
+            i += skip;
+            continue;
+        }
+
+        // a start tag, scan up to its closing `>` outside of the attribute values
+        let mut quote = None;
+        i += 1;
+        while i < bytes.len() {
+            let b = bytes[i];
+            match quote {
+                Some(q) if b == q => quote = None,
+                Some(_) if b == b'\t' || b == b'\n' => positions.push(i),
+                Some(_) => (),
+                None if b == b'"' || b == b'\'' => quote = Some(b),
+                None if b == b'>' => break,
+                None => (),
+            }
+            i += 1;
+        }
+    }
+
+    positions
+}
+
+impl From<std::io::Error> for ConversionError {
+    fn from(e: std::io::Error) -> Self {
+        ConversionError::Io(e)
+    }
+}
+
+impl From<std::str::Utf8Error> for ConversionError {
+    fn from(e: std::str::Utf8Error) -> Self {
+        ConversionError::Utf8(e)
+    }
+}
+
+/// Where the identifier injected by `InjectionOptions::node_id` comes from.
+#[derive(Debug, PartialEq, Clone)]
+pub enum NodeIdSource {
+    /// The value of this attribute, e.g. `xml:id`. Elements without the attribute get no identifier.
+    Attr(String),
+    /// The XML path of the element with the 0-based index among siblings of the same name,
+    /// e.g. `/a/b[1]/c[0]`. The root element is `/a`. The identifiers are the same for every
+    /// conversion of the same document.
+    SyntheticPath,
+}
+
+/// Defines what happens if the converted object already has a property with the name of the identifier.
+#[derive(Debug, PartialEq, Clone)]
+pub enum NodeIdClash {
+    /// Stop the conversion with `ConversionError::NodeIdClash`. This is the default.
+    Error,
+    /// Keep the existing property and don't inject the identifier.
+    KeepExisting,
+    /// Replace the existing property with the identifier.
+    Overwrite,
+}
+
+/// Defines what happens to names longer than `KeyOptions::max_name_length`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LongNamePolicy {
+    /// Stop the conversion with `ConversionError::NameTooLong`. This is the default.
+    Error,
+    /// Truncate the name and end it with `~` and 16 hex digits of a hash of the whole name, so names sharing
+    /// a long prefix remain distinct. The hash is the same on every platform and in every version.
+    TruncateWithHash,
+}
+
+/// Defines which characters JSON property names may have, see `KeyOptions::key_charset`.
+/// Valid XML names can have characters like combining marks or bidi controls that break downstream tools.
+#[derive(Debug, PartialEq, Clone)]
+pub enum KeyCharset {
+    /// Keep the names as they are. This is the default.
+    AllowAll,
+    /// Replace every non-ASCII character with `replacement`, e.g. `_`. If another name of the document was
+    /// converted into the same property name before, `~` and a hash of the original name are appended,
+    /// e.g. `x_~1c8a4f03`, so different names are not merged into arrays.
+    AsciiOnly { replacement: String },
+    /// Stop the conversion with `ConversionError::NonAsciiKey`.
+    ErrorOnNonAscii,
+}
+
+/// Defines what happens to child elements with the name of `Config::xml_text_node_prop_name`
+/// if their parent has text, e.g. `<a>hi<text>x</text></a>` with `text` as the text property.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TextPropCollision {
+    /// Append the suffix to the property name of the element, e.g. `{"a":{"text":"hi","text_elem":"x"}}`.
+    Suffix(String),
+    /// Stop the conversion with `ConversionError::TextPropCollision`.
+    Error,
+}
+
+/// The second naming convention of `KeyOptions::dual_emit`. The attributes and the text of elements converted
+/// into objects are written under the keys of both conventions,
+/// e.g. `{"@id":1,"#text":"x","attrs":{"id":1},"value":"x"}` with `@` and `#text` as the legacy convention
+/// and `attrs`, no prefix and `value` as the new one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DualEmitConfig {
+    /// The prefix of the new attribute keys, e.g. an empty string
+    pub xml_attr_prefix: String,
+    /// The new key of the text, e.g. `value`
+    pub xml_text_node_prop_name: String,
+    /// Nest the new attribute keys in an object under this key, e.g. `attrs`. `None` keeps them next to
+    /// the legacy keys.
+    pub attrs_key: Option<String>,
+    /// Mark the keys of one of the conventions as deprecated with `deprecated_prefix`.
+    pub deprecate: Option<KeySet>,
+    /// Prepended to the deprecated keys, e.g. `deprecated_@id`. Defaults to `deprecated_`.
+    pub deprecated_prefix: String,
+}
+
+impl DualEmitConfig {
+    /// Flat new keys with the given attribute prefix and text key, and no deprecation marks.
+    pub fn new(xml_attr_prefix: &str, xml_text_node_prop_name: &str) -> Self {
+        DualEmitConfig {
+            xml_attr_prefix: xml_attr_prefix.to_owned(),
+            xml_text_node_prop_name: xml_text_node_prop_name.to_owned(),
+            attrs_key: None,
+            deprecate: None,
+            deprecated_prefix: "deprecated_".to_owned(),
+        }
+    }
+}
+
+/// The keys of one of the naming conventions of `KeyOptions::dual_emit`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KeySet {
+    /// The keys of `Config::xml_attr_prefix` and `Config::xml_text_node_prop_name`
+    Legacy,
+    /// The keys of `DualEmitConfig`
+    New,
+}
+
+/// Tells the converter to inject an identifier into every JSON object created from an XML element.
+/// Elements converted into scalar values, e.g. `<a>1</a>`, get no identifier.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NodeIdConfig {
+    /// The name of the JSON property with the identifier, e.g. `_id`
+    pub prop_name: String,
+    pub source: NodeIdSource,
+    pub on_clash: NodeIdClash,
+}
+
+impl NodeIdConfig {
+    /// Creates a config with `NodeIdClash::Error` clash policy.
+    pub fn new(prop_name: &str, source: NodeIdSource) -> Self {
+        NodeIdConfig {
+            prop_name: prop_name.to_owned(),
+            source,
+            on_clash: NodeIdClash::Error,
+        }
+    }
+}
+
+/// Tells the converter how to perform certain conversions.
+/// See docs for individual fields for more info. Besides the basic options and the JSON type overrides,
+/// the options are grouped by what they affect, e.g. `config.text.trim_text` or `config.arrays.forced_array_paths`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Numeric values starting with 0 will be treated as strings.
+    /// E.g. convert `<agent>007</agent>` into `"agent":"007"` or `"agent":7`
+    /// Defaults to `false`.
+    pub leading_zero_as_string: bool,
+    /// The conversion rules of a crate release, see `ConversionProfile`. Defaults to `ConversionProfile::Latest`.
+    pub profile: ConversionProfile,
+    /// Prefix XML attribute names with this value to distinguish them from XML elements.
+    /// E.g. set it to `@` for `<x a="Hello!" />` to become `{"x": {"@a":"Hello!"}}`
+    /// or set it to a blank string for `{"x": {"a":"Hello!"}}`
+    /// Defaults to `@`.
+    pub xml_attr_prefix: String,
+    /// A property name for XML text nodes.
+    /// E.g. set it to `text` for `<x a="Hello!">Goodbye!</x>` to become `{"x": {"@a":"Hello!", "text":"Goodbye!"}}`
+    /// XML nodes with text only and no attributes or no child elements are converted into JSON properties with the
+    /// name of the element. E.g. `<x>Goodbye!</x>` becomes `{"x":"Goodbye!"}`
+    /// Defaults to `#text`
+    pub xml_text_node_prop_name: String,
+    /// Defines how empty elements like `<x />` should be handled.
+    pub empty_element_handling: NullValue,
+    /// The type inference and the format of the values, see `ValueOptions`.
+    pub values: ValueOptions,
+    /// The conversion of text nodes, comments and processing instructions, see `TextOptions`.
+    pub text: TextOptions,
+    /// The JSON property names made of element and attribute names, see `KeyOptions`.
+    pub keys: KeyOptions,
+    /// The conversion of attributes, see `AttributeOptions`.
+    pub attributes: AttributeOptions,
+    /// When repeated and single child elements become arrays, see `ArrayOptions`.
+    pub arrays: ArrayOptions,
+    /// Properties and processing added to the converted objects, see `InjectionOptions`.
+    pub injection: InjectionOptions,
+    /// The documents accepted for the conversion, see `InputOptions`.
+    pub input: InputOptions,
+    /// The shape and the encoding of the output, see `OutputOptions`.
+    pub output: OutputOptions,
+    /// Warnings and progress reports of the conversions, see `DiagnosticOptions`.
+    pub diagnostics: DiagnosticOptions,
+    /// A map of XML paths with their JsonArray overrides. They take precedence over the document-wide `json_type`
+    /// property. The path syntax is based on xPath: literal element names and attribute names prefixed with `@`.
+    /// The path must start with a leading `/`. It is a bit of an inconvenience to remember about it, but it saves
+    /// an extra `if`-check in the code to improve the performance.
+    /// # Example
+    /// - **XML**: `<a><b c="123">007</b></a>`
+    /// - path for `c`: `/a/b/@c`
+    /// - path for `b` text node (007): `/a/b`
+    #[cfg(feature = "json_types")]
+    pub json_type_overrides: HashMap<String, JsonArray>,
+    /// Overrides with paths containing `*` steps, e.g. `/report/*/@id` for the `id` attribute of any child element
+    /// of `report`, see `add_json_type_override`. A `*` matches the name of any single element, but not
+    /// an attribute. They only apply if `json_type_overrides` has no path for the node, and the first matching one
+    /// in the order they were added is used. Unlike the paths of `json_type_overrides` they don't add empty arrays
+    /// for missing elements with `JsonArray::Always`.
+    #[cfg(feature = "json_types")]
+    pub json_type_wildcard_overrides: Vec<(String, JsonArray)>,
+    /// Overrides with paths containing `{name}` variables not known yet, e.g. `/root/{tenant}/config/@value`.
+    /// They don't apply until `expand_template_overrides` moves them into `json_type_overrides`.
+    /// See `add_json_type_override_template`.
+    #[cfg(feature = "json_types")]
+    pub json_type_override_templates: Vec<(String, JsonArray)>,
+    /// A list of pairs of regex and JsonArray overrides. They take precedence over both the document-wide `json_type`
+    /// property and the `json_type_overrides` property. The path syntax is based on xPath just like
+    /// `json_type_overrides`.
+    #[cfg(feature = "regex_path")]
+    pub json_regex_type_overrides: Vec<(Regex, JsonArray)>,
+    /// A map of XML paths with lists of known enum values. A value found in the list is converted into
+    /// its 0-based index in the list, any other value is kept as a JSON string.
+    /// E.g. `<a>ACTIVE</a>` becomes `{"a":1}` for `/a` mapped to `["PENDING", "ACTIVE", "CLOSED"]`.
+    /// Enum mappings take precedence over `json_type_overrides`.
+    #[cfg(feature = "json_types")]
+    pub enum_mappings: HashMap<String, Vec<String>>,
+    /// Where the paths of `json_type_overrides` and `enum_mappings` are anchored. Regex paths are not affected.
+    /// Defaults to `PathAnchor::Root`.
+    #[cfg(feature = "json_types")]
+    pub path_anchor: PathAnchor,
+    /// A map of XML paths with lists of attribute names. Elements at these paths with no text and
+    /// no child elements are converted into arrays of their attribute values in the listed order,
+    /// which is much more compact and faster than objects for large numbers of small elements.
+    /// E.g. `<a><point x="1" y="2"/></a>` becomes `{"a":{"point":[[1,2]]}}` for `/a/point` mapped to `["x", "y"]`.
+    /// The elements are always collected into an array, even if there is only one. Missing attributes become `null`.
+    /// Elements with other attributes, text or child elements are converted into objects as usual and reported with
+    /// `Warning::TupleRuleMismatch`.
+    #[cfg(feature = "json_types")]
+    pub tuple_rules: HashMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Numbers with leading zero will be treated as numbers.
+    /// Prefix XML Attribute names with `@`
+    /// Name XML text nodes `#text` for XML Elements with other children
+    pub fn new_with_defaults() -> Self {
+        Config {
+            leading_zero_as_string: false,
+            profile: ConversionProfile::Latest,
+            xml_attr_prefix: "@".to_owned(),
+            xml_text_node_prop_name: "#text".to_owned(),
+            empty_element_handling: NullValue::EmptyObject,
+            values: ValueOptions::default(),
+            text: TextOptions::default(),
+            keys: KeyOptions::default(),
+            attributes: AttributeOptions::default(),
+            arrays: ArrayOptions::default(),
+            injection: InjectionOptions::default(),
+            input: InputOptions::default(),
+            output: OutputOptions::default(),
+            diagnostics: DiagnosticOptions::default(),
+            #[cfg(feature = "json_types")]
+            json_type_overrides: HashMap::new(),
+            #[cfg(feature = "json_types")]
+            json_type_wildcard_overrides: Vec::new(),
+            #[cfg(feature = "json_types")]
+            json_type_override_templates: Vec::new(),
+            #[cfg(feature = "regex_path")]
+            json_regex_type_overrides: Vec::new(),
+            #[cfg(feature = "json_types")]
+            enum_mappings: HashMap::new(),
+            #[cfg(feature = "json_types")]
+            path_anchor: PathAnchor::Root,
+            #[cfg(feature = "json_types")]
+            tuple_rules: HashMap::new(),
+        }
+    }
+
+    /// Create a Config object with non-default values. See the `Config` struct docs for more info.
+    pub fn new_with_custom_values(
+        leading_zero_as_string: bool,
+        xml_attr_prefix: &str,
+        xml_text_node_prop_name: &str,
+        empty_element_handling: NullValue,
+    ) -> Self {
+        Config {
+            leading_zero_as_string,
+            xml_attr_prefix: xml_attr_prefix.to_owned(),
+            xml_text_node_prop_name: xml_text_node_prop_name.to_owned(),
+            empty_element_handling,
+            ..Config::new_with_defaults()
+        }
+    }
+
+    /// Adds a single JSON Type override rule to the current config.
+    /// # Example
+    /// - **XML**: `<a><b c="123">007</b></a>`
+    /// - path for `c`: `/a/b/@c`
+    /// - path for `b` text node (007): `/a/b`
+    /// - path for `c` of any child element of `a`: `/a/*/@c`, see `json_type_wildcard_overrides`
+    /// - regex path for any `element` node: `(\w/)*element$` [requires `regex_path` feature]
+    #[cfg(feature = "json_types")]
+    pub fn add_json_type_override<P>(self, path: P, json_type: JsonArray) -> Self
+    where
+        P: Into<PathMatcher>
+    {
+        let mut conf = self;
+
+        match path.into() {
+            PathMatcher::Absolute(path) => conf.insert_json_type_override(path, json_type),
+            #[cfg(feature = "regex_path")]
+            PathMatcher::Regex(regex) => {
+                conf.json_regex_type_overrides.push((
+                    regex,
+                    json_type
+                ));
+            }
+        }
+
+        conf
+    }
+
+    /// Adds an absolute XML path of elements which are always converted into arrays, see `forced_array_paths`.
+    /// # Example
+    /// - **XML**: `<order><item>1</item></order>`
+    /// - `add_forced_array_path("/order/item")`
+    /// - **JSON**: `{"order":{"item":[1]}}`
+    pub fn add_forced_array_path(self, path: &str) -> Self {
+        let mut conf = self;
+        conf.arrays.forced_array_paths.insert(with_leading_slash(path));
+        conf
+    }
+
+    /// Adds the local name of elements which are always converted into arrays, see `always_array_elements`.
+    /// # Example
+    /// - **XML**: `<feed><item>1</item><group><item>2</item></group></feed>`
+    /// - `add_always_array_element("item")`
+    /// - **JSON**: `{"feed":{"item":[1],"group":{"item":[2]}}}`
+    pub fn add_always_array_element(self, name: &str) -> Self {
+        let mut conf = self;
+        conf.arrays.always_array_elements.insert(name.to_owned());
+        conf
+    }
+
+    /// Adds a JSON type override for a path with `{name}` variables replaced with the values of `vars`,
+    /// e.g. `/root/{tenant}/config/@value` becomes `/root/acme/config/@value` for `tenant` set to `acme`.
+    /// Paths with variables missing from `vars` are kept in `json_type_override_templates` until
+    /// `expand_template_overrides` provides them.
+    #[cfg(feature = "json_types")]
+    pub fn add_json_type_override_template(
+        self,
+        template: &str,
+        vars: HashMap<String, String>,
+        json_type: JsonType,
+    ) -> Self {
+        let mut conf = self;
+        let (path, resolved) = expand_path_template(template, &vars);
+        if resolved {
+            conf.insert_json_type_override(with_leading_slash(&path), JsonArray::Infer(json_type));
+        } else {
+            conf.json_type_override_templates
+                .push((path, JsonArray::Infer(json_type)));
+        }
+        conf
+    }
+
+    /// Returns a copy of the config with the variables of `json_type_override_templates` replaced with
+    /// the values of `vars`, e.g. once per tenant. The resolved paths are moved into `json_type_overrides`
+    /// and the paths with variables still missing are kept as templates.
+    #[cfg(feature = "json_types")]
+    pub fn expand_template_overrides(&self, vars: &HashMap<String, String>) -> Config {
+        let mut conf = self.clone();
+        let templates = std::mem::take(&mut conf.json_type_override_templates);
+        for (template, json_type) in templates {
+            let (path, resolved) = expand_path_template(&template, vars);
+            if resolved {
+                conf.insert_json_type_override(with_leading_slash(&path), json_type);
+            } else {
+                conf.json_type_override_templates.push((path, json_type));
+            }
+        }
+        conf
+    }
+
+    /// Adds the override to `json_type_wildcard_overrides` if the path has a `*` step,
+    /// or to `json_type_overrides` otherwise.
+    #[cfg(feature = "json_types")]
+    fn insert_json_type_override(&mut self, path: String, json_type: JsonArray) {
+        if path.split('/').any(|step| step == "*") {
+            self.json_type_wildcard_overrides.retain(|(existing, _)| *existing != path);
+            self.json_type_wildcard_overrides.push((path, json_type));
+        } else {
+            self.json_type_overrides.insert(path, json_type);
+        }
+    }
+
+    /// Maps the values of the node at this absolute path to integer codes, see `Config::enum_mappings`.
+    /// # Example
+    /// - **XML**: `<order status="ACTIVE" />`
+    /// - `add_enum_mapping("/order/@status", vec!["PENDING".to_owned(), "ACTIVE".to_owned()])`
+    /// - **JSON**: `{"order":{"@status":1}}`
+    #[cfg(feature = "json_types")]
+    pub fn add_enum_mapping(self, path: &str, values: Vec<String>) -> Self {
+        let mut conf = self;
+        conf.enum_mappings.insert(with_leading_slash(path), values);
+        conf
+    }
+
+    /// Applies the function to the raw values of all attributes with this name before the type inference.
+    /// E.g. `.add_attr_value_transform("color", |v| v.trim_start_matches('#').to_lowercase())`
+    /// turns `<a color="#FF0000"/>` into `{"a":{"@color":"ff0000"}}`.
+    pub fn add_attr_value_transform<F>(self, attr_name: &str, f: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        let mut conf = self;
+        conf.attributes.attr_value_transforms
+            .insert(attr_name.to_owned(), AttrValueTransform(Arc::new(f)));
+        conf
+    }
+
+    /// Adds a property computed from the attributes of the elements at this absolute path, see
+    /// `AttributeOptions::virtual_attributes`. The function gets the converted attributes with their prefixed names and
+    /// the property is added next to them unless the function returns `None` or an attribute has the same name.
+    pub fn add_virtual_attribute<F>(self, path: &str, key: &str, f: F) -> Self
+    where
+        F: Fn(&Map<String, Value>) -> Option<Value> + Send + Sync + 'static,
+    {
+        let mut conf = self;
+        conf.attributes.virtual_attributes
+            .push((with_leading_slash(path), key.to_owned(), VirtualAttribute(Arc::new(f))));
+        conf
+    }
+
+    /// Adds the property to the objects of the elements at this absolute path, see `InjectionOptions::injected_fields`.
+    /// E.g. `.add_injected_field("/feed", "feed_id", InjectedValue::Constant(json!(42)))`.
+    pub fn add_injected_field(self, path: &str, key: &str, value: InjectedValue) -> Self {
+        let mut conf = self;
+        conf.injection.injected_fields
+            .push((with_leading_slash(path), key.to_owned(), value));
+        conf
+    }
+
+    /// Sets what happens if the element or attribute at this absolute path is missing from the object
+    /// of its parent, see `InjectionOptions::absent_field_policy`.
+    /// E.g. `.add_absent_field_policy("/order/note", AbsentPolicy::Null)` turns `<order><id>1</id></order>`
+    /// into `{"order":{"id":1,"note":null}}`.
+    pub fn add_absent_field_policy(self, path: &str, policy: AbsentPolicy) -> Self {
+        let mut conf = self;
+        conf.injection.absent_field_policy.insert(with_leading_slash(path), policy);
+        conf
+    }
+
+    /// Converts the elements at this absolute path into arrays of the attribute values, see `Config::tuple_rules`.
+    #[cfg(feature = "json_types")]
+    pub fn add_tuple_rule(self, path: &str, attrs: &[&str]) -> Self {
+        let mut conf = self;
+        let attrs = attrs.iter().map(|attr| attr.to_string()).collect();
+        conf.tuple_rules.insert(with_leading_slash(path), attrs);
+        conf
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new_with_defaults()
+    }
+}
+
+/// The type inference and the format of the values, see `Config::values`.
+#[derive(Debug, Clone)]
+pub struct ValueOptions {
+    /// Infer the texts `1` and `0` as JSON bool `true` and `false` instead of numbers, e.g. convert
+    /// `<active>1</active>` into `"active":true`. Takes priority over number parsing, but only the exact texts
+    /// are affected, so `01` or `1.0` still follow `Config::leading_zero_as_string` and the number rules.
+    /// Overrides like `JsonType::AlwaysString` take priority over it. Defaults to `false`.
+    pub boolean_from_numeric: bool,
+    /// Convert attribute values listed in `ValueOptions::bool_truthy` and `ValueOptions::bool_falsy` into JSON bool
+    /// ignoring the case, e.g. `<x enabled="True" visible="NO"/>` into `{"x":{"@enabled":true,"@visible":false}}`.
+    /// Only applies to attributes without JSON type overrides, and takes priority over the rest of the type
+    /// inference. Text nodes are not affected. Defaults to `false`.
+    pub coerce_bool_attrs: bool,
+    /// The attribute values converted into `true` by `ValueOptions::coerce_bool_attrs`. Defaults to `true` and `yes`.
+    pub bool_truthy: Vec<String>,
+    /// The attribute values converted into `false` by `ValueOptions::coerce_bool_attrs`. Defaults to `false` and `no`.
+    pub bool_falsy: Vec<String>,
+    /// Emit inferred numbers as objects with the number and the original text, e.g. convert
+    /// `<price>42.50</price>` into `"price":{"$num":42.5,"$str":"42.50"}`, for consumers that compute with
+    /// the number but display the text. Other values, including enum codes, are not wrapped. Defaults to `false`.
+    pub emit_number_as_hybrid: bool,
+    /// Stop with `ConversionError::TypeConversionFailed` if a value at a path with `JsonType::AlwaysInt` or
+    /// `JsonType::AlwaysFloat` override is not a number, instead of keeping it as a string. Defaults to `false`.
+    pub strict_json_types: bool,
+    /// The JSON types inferred for values with `JsonType::Infer`, which is the default for all values.
+    /// Overrides with other JSON types still force them for their paths.
+    /// Defaults to numbers and booleans.
+    pub inference: InferenceFlags,
+    /// The parser for numbers inferred from the values. Defaults to `NumberParser::Standard`.
+    pub number_parser: NumberParser,
+    /// Keep the values parsed as floats in float notation, e.g. for consumers telling float fields apart by the `.0`.
+    /// `serde_json` already writes whole floats like `42.0` with the `.0`, but switches to the exponent notation
+    /// from `1e16` on, e.g. `<a>2e16</a>` becomes `{"a":2e+16}`. With this option it becomes
+    /// `{"a":20000000000000000.0}`. Only numbers parsed by `NumberParser::Standard` or `NumberParser::I128U128`
+    /// are affected and integers like `<a>42</a>` remain integers. Requires `arbitrary_precision` feature, which
+    /// lets the numbers keep their digits, and has no effect without it, see `Config::validate`. Defaults to `false`.
+    pub force_float_format: bool,
+    /// Replaces the type inference of all text nodes and attribute values, e.g. to parse dates.
+    /// JSON type overrides and enum mappings of the path take precedence over it. Defaults to `None`.
+    pub text_node_mapper: Option<TextNodeMapper>,
+    /// Defines what happens to string values with control characters, e.g. a vertical tab.
+    /// Defaults to `JsonStringPolicy::Keep`.
+    pub json_string_policy: JsonStringPolicy,
+    /// Apply `json_string_policy` to JSON property names created from element and attribute names as well.
+    /// Defaults to `false`.
+    pub json_string_policy_keys: bool,
+}
+
+impl Default for ValueOptions {
+    fn default() -> Self {
+        ValueOptions {
+            boolean_from_numeric: false,
+            coerce_bool_attrs: false,
+            bool_truthy: vec!["true".to_owned(), "yes".to_owned()],
+            bool_falsy: vec!["false".to_owned(), "no".to_owned()],
+            emit_number_as_hybrid: false,
+            strict_json_types: false,
+            inference: InferenceFlags::default(),
+            number_parser: NumberParser::Standard,
+            force_float_format: false,
+            text_node_mapper: None,
+            json_string_policy: JsonStringPolicy::Keep,
+            json_string_policy_keys: false,
+        }
+    }
+}
+
+/// The conversion of text nodes, comments and processing instructions, see `Config::text`.
+#[derive(Debug, Clone)]
+pub struct TextOptions {
+    /// Trim the whitespace around the text of elements without child elements and attribute values.
+    /// Turn it off to keep significant whitespace, e.g. of fixed-width codes: `<code>  42  </code>` becomes
+    /// `"code":"  42  "` and `<br>\n</br>` becomes `"br":"\n"` instead of an empty element. The types are
+    /// still inferred, so only values without whitespace around them become numbers or booleans.
+    /// The text of elements with child elements is always trimmed. Defaults to `true`.
+    pub trim_text: bool,
+    /// Convert the text of CDATA sections into strings as it is, without trimming it or inferring its type,
+    /// e.g. `<v><![CDATA[00123]]></v>` into `{"v":"00123"}`. The text around the section is trimmed as usual and
+    /// becomes a part of the string. An empty section becomes an empty string. Defaults to `false`.
+    pub cdata_as_string: bool,
+    /// Defines what happens to child elements named like `Config::xml_text_node_prop_name`, e.g. `<text>` with
+    /// `text` as the text property. They are never merged with the text of their parent. Only the elements
+    /// whose parent has text, and so the text property, are disambiguated. The names are compared after
+    /// the renames of the config. The default `#text` is not a valid element name.
+    /// Defaults to `TextPropCollision::Suffix("_elem")`.
+    pub text_prop_collision: TextPropCollision,
+    /// Defines how the text nodes of an element are joined. Defaults to `TextJoin::Concat`.
+    pub text_join: TextJoin,
+    /// Defines what happens to the text following a child element. Defaults to `TailTextHandling::AppendToParent`.
+    pub tail_text_handling: TailTextHandling,
+    /// Drop XML comments or keep them in the JSON output. Defaults to `CommentHandling::Ignore`.
+    pub comment_handling: CommentHandling,
+    /// Keep the processing instructions under this key of the element they are in, as objects with `target`
+    /// and `data` properties, or an array of them if there are several, e.g. `<a><?page break?></a>` becomes
+    /// `{"a":{"?pi":{"target":"page","data":"break"}}}` with `?pi`. The ones before the root element,
+    /// e.g. `<?xml-stylesheet href="x.xsl"?>`, are kept at the top level of the output, next to the root element.
+    /// The XML declaration is not a processing instruction. Defaults to `None`.
+    pub processing_instructions_key: Option<String>,
+    /// Convert elements with child elements into their attributes and an array of the text runs and child
+    /// elements in document order under `TextOptions::content_key`, e.g. for markup like
+    /// `<p>one <em>two</em> three</p>` which becomes `{"p":{"#content":["one ",{"em":"two"}," three"]}}`.
+    /// Every child element is a single-key object, so repeated elements are not collected into arrays.
+    /// Text runs are kept as-is, except for whitespace-only runs, and are not inferred. Elements without child
+    /// elements are converted as usual, and `TextOptions::tail_text_handling` doesn't apply. Defaults to `false`.
+    pub ordered_content: bool,
+    /// The property name of the array of `TextOptions::ordered_content`. Defaults to `#content`.
+    pub content_key: String,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            trim_text: true,
+            cdata_as_string: false,
+            text_prop_collision: TextPropCollision::Suffix("_elem".to_owned()),
+            text_join: TextJoin::Concat,
+            tail_text_handling: TailTextHandling::AppendToParent,
+            comment_handling: CommentHandling::Ignore,
+            processing_instructions_key: None,
+            ordered_content: false,
+            content_key: "#content".to_owned(),
+        }
+    }
+}
+
+/// The JSON property names made of element and attribute names, see `Config::keys`.
+#[derive(Debug, Clone)]
+pub struct KeyOptions {
+    /// Truncate JSON property names longer than this number of characters, e.g. for Elasticsearch
+    /// which caps the length of field names. The limit applies to the entire name including the attribute prefix.
+    /// Names that become identical after truncation are treated as repeated elements and collected into arrays.
+    /// Defaults to `None`.
+    pub key_length_limit: Option<usize>,
+    /// Ends truncated property names with this suffix, e.g. `...`. The suffix counts towards `key_length_limit`.
+    /// Defaults to an empty string.
+    pub key_truncation_suffix: String,
+    /// The maximum length of element and attribute names in bytes, and of the property names made of them,
+    /// e.g. against fuzzed documents with megabyte-long names. Checked before any other processing of the names,
+    /// see `long_name_policy`. Unlike `key_length_limit` it guards against broken input and isn't meant
+    /// for shortening the output. Defaults to 4 KB.
+    pub max_name_length: Option<usize>,
+    /// What happens to names longer than `max_name_length`. Defaults to `LongNamePolicy::Error`.
+    pub long_name_policy: LongNamePolicy,
+    /// Restricts the characters of the property names, including the attribute prefix and the text property.
+    /// Unless all characters are allowed, `Config::xml_attr_prefix` and `Config::xml_text_node_prop_name`
+    /// must be ASCII or the conversion fails with `ConversionError::NonAsciiKeyOption`.
+    /// Defaults to `KeyCharset::AllowAll`.
+    pub key_charset: KeyCharset,
+    /// Separates the name of the ancestor element from the name of the property merged into the records
+    /// by `xml_to_ndjson`, e.g. `export.@date`. Defaults to `.`.
+    pub ancestor_key_separator: String,
+    /// Write the attributes and the text of elements converted into objects under the keys of a second naming
+    /// convention as well, e.g. while migrating consumers. New keys clashing with other properties stop
+    /// the conversion with `ConversionError::DualKeyClash`. Defaults to `None`.
+    pub dual_emit: Option<DualEmitConfig>,
+}
+
+impl Default for KeyOptions {
+    fn default() -> Self {
+        KeyOptions {
+            key_length_limit: None,
+            key_truncation_suffix: String::new(),
+            max_name_length: Some(4096),
+            long_name_policy: LongNamePolicy::Error,
+            key_charset: KeyCharset::AllowAll,
+            ancestor_key_separator: ".".to_owned(),
+            dual_emit: None,
+        }
+    }
+}
+
+/// The conversion of attributes, see `Config::attributes`.
+#[derive(Debug, Clone)]
+pub struct AttributeOptions {
+    /// Attributes converted into properties without `Config::xml_attr_prefix`. Defaults to `PromoteAttrs::None`.
+    pub promote_attributes: PromoteAttrs,
+    /// Appended to the names of promoted attributes if the element has a child element with the same name,
+    /// e.g. `<user id="7"><id>x</id></user>` becomes `{"user":{"id":"x","id_attr":7}}`. Defaults to `_attr`.
+    pub promoted_attr_collision_suffix: String,
+    /// A map of attribute names with functions normalizing their raw values before the type inference,
+    /// e.g. to turn `#FF0000` into `16711680`. Applies to the attributes with these names anywhere in the document.
+    /// See `Config::add_attr_value_transform`.
+    pub attr_value_transforms: HashMap<String, AttrValueTransform>,
+    /// Properties computed from the attributes of the elements at the XML paths, e.g. `full_name` from
+    /// the `first_name` and `last_name` attributes. The tuples are the path of the element, the property name
+    /// and the function. See `Config::add_virtual_attribute`. Defaults to an empty list.
+    pub virtual_attributes: Vec<(String, String, VirtualAttribute)>,
+    /// Move the attributes of the root element to the top level of the JSON output next to the root element,
+    /// e.g. `<root lang="en"><a>1</a></root>` becomes `{"@lang":"en","root":{"a":1}}`.
+    /// An attribute with the same name as the root element is replaced by the root element.
+    /// Doesn't apply to `xml_string_to_json_resume` and `xml_to_ndjson` which don't output the root element.
+    /// Defaults to `false`.
+    pub promote_root_attrs: bool,
+    /// Joins arrays found under attribute properties by `json_to_xml`, e.g. `{"a":{"@tags":["x","y"]}}`
+    /// becomes `<a tags="x y"/>` with ` `. Such arrays are errors if not set. Defaults to `None`.
+    pub xml_attr_array_separator: Option<String>,
+    /// Defines what happens to attributes repeated on the same element, see `DupAttr`.
+    /// Only applies to `QuickXmlParser`, e.g. with `xml_string_to_json_with_parser`. Defaults to `DupAttr::Error`.
+    pub duplicate_source_attributes: DupAttr,
+}
+
+impl Default for AttributeOptions {
+    fn default() -> Self {
+        AttributeOptions {
+            promote_attributes: PromoteAttrs::None,
+            promoted_attr_collision_suffix: "_attr".to_owned(),
+            attr_value_transforms: HashMap::new(),
+            virtual_attributes: Vec::new(),
+            promote_root_attrs: false,
+            xml_attr_array_separator: None,
+            duplicate_source_attributes: DupAttr::Error,
+        }
+    }
+}
+
+/// When repeated and single child elements become arrays, see `Config::arrays`.
+#[derive(Debug, Clone, Default)]
+pub struct ArrayOptions {
+    /// Absolute XML paths of elements which are always converted into arrays, even if there is only one of them,
+    /// e.g. `/order/item` for `{"order":{"item":[{..}]}}`, so the consumers always get the same shape.
+    /// The paths use the syntax of `Config::json_type_overrides` and must start with a leading `/`,
+    /// see `Config::add_forced_array_path`. Unlike `JsonArray::Always` it doesn't change the type inference of the
+    /// values.
+    pub forced_array_paths: HashSet<String>,
+    /// Convert the child elements with the same name into an array if the element has more than this number
+    /// of them, counting the ones left out of the output, e.g. by `NullValue::Ignore`. So with `Some(1)` and
+    /// `<a><b/><b>1</b></a>` the remaining `b` is still `{"a":{"b":[1]}}`, and repeated objects are never merged
+    /// by `merge_same_name_objects`. It's a hint based on the element at hand, other elements at the same path
+    /// with fewer children stay scalar. `Some(0)` converts every child element into an array.
+    /// Defaults to `None`.
+    pub force_array_above_child_count: Option<usize>,
+    /// Local names of elements which are always converted into arrays, even if there is only one of them,
+    /// at any depth, e.g. `item` for feeds with items at many levels, see `Config::add_always_array_element`.
+    /// An override of the path in `Config::json_type_overrides` or `Config::json_regex_type_overrides` takes
+    /// precedence, so `JsonArray::Infer` at a path keeps a single element there scalar.
+    pub always_array_elements: HashSet<String>,
+    /// Merge the objects of repeated elements into one object instead of collecting them into an array,
+    /// e.g. `<a><b><x>1</x></b><b><y>2</y></b></a>` becomes `{"a":{"b":{"x":1,"y":2}}}`.
+    /// Nested objects are merged recursively and other properties of the later elements replace the earlier ones.
+    /// Repeated elements converted into scalar values and paths with `JsonArray::Always` still become arrays.
+    /// Defaults to `false`.
+    pub merge_same_name_objects: bool,
+    /// Convert wrapper elements whose children all have the singular form of their name into arrays
+    /// of the converted children, e.g. `<items><item>1</item><item>2</item></items>` into `"items":[1,2]`.
+    /// The singular is the name without a trailing `s` or `es`, or the name from `plural_pairs`. Wrappers
+    /// with attributes or text are not flattened. Each flattening is reported with
+    /// `Warning::PluralWrapperFlattened`, since the heuristic may misfire. Defaults to `false`.
+    pub auto_flatten_plural_wrappers: bool,
+    /// Irregular plurals for `auto_flatten_plural_wrappers` with the singular for each plural,
+    /// e.g. `people` -> `person`. Empty by default.
+    pub plural_pairs: HashMap<String, String>,
+    /// XML paths of the elements never flattened by `auto_flatten_plural_wrappers`, e.g. `/doc/news`.
+    /// Empty by default.
+    pub plural_wrapper_exclusions: Vec<String>,
+}
+
+/// Properties and processing added to the converted objects, see `Config::injection`.
+#[derive(Debug, Clone)]
+pub struct InjectionOptions {
+    /// Inject a stable identifier into every JSON object created from an XML element, e.g. for building
+    /// cross-references after the conversion. Defaults to `None`.
+    pub node_id: Option<NodeIdConfig>,
+    /// Add the 0-based position of the element among all its sibling elements to every JSON object
+    /// created from a child element, so the original order can be restored after array promotion.
+    /// E.g. `<a><b x="1"/><c y="2"/><b x="3"/></a>` becomes
+    /// `{"a":{"b":[{"@x":1,"_order":0},{"@x":3,"_order":2}],"c":{"@y":2,"_order":1}}}`.
+    /// Elements converted into scalar values have no place for it and are left as-is.
+    /// Defaults to `false`.
+    pub preserve_child_order: bool,
+    /// The name of the property added by `preserve_child_order`. Defaults to `_order`.
+    pub child_order_key: String,
+    /// Properties added to the JSON objects of the elements at the XML paths, e.g. to stamp every document
+    /// with its feed id and the time of the conversion. The tuples are the path of the element, the property name
+    /// and the value. Repeated elements get the property in every object. Elements converted into scalar values,
+    /// e.g. `<a>1</a>`, have no object and are left as-is. See `Config::add_injected_field`. Defaults to an empty list.
+    pub injected_fields: Vec<(String, String, InjectedValue)>,
+    /// Defines what happens if the object already has a property with the name of an injected field.
+    /// Defaults to `NodeIdClash::Error`, which stops the conversion with `ConversionError::InjectedFieldClash`.
+    pub injected_fields_on_clash: NodeIdClash,
+    /// A map of XML paths of child elements and attributes with what happens if they produce no value in the
+    /// object of their parent element, e.g. because they are missing from the document or are empty elements
+    /// with `NullValue::Ignore`. With `AbsentPolicy::Null` the object gets the field with `null`, e.g. for schemas
+    /// that require the field. Parents converted into scalar values, e.g. `<b>1</b>`, have no object and are
+    /// left as-is. See `Config::add_absent_field_policy`. Defaults to an empty map, which omits all missing fields.
+    pub absent_field_policy: HashMap<String, AbsentPolicy>,
+    /// Where `xml_string_to_json_with_context` adds the context properties. Defaults to `ContextPlacement::Root`.
+    pub context_placement: ContextPlacement,
+    /// Custom processing of every value and object of the output, see `ValueInterceptor`.
+    /// Defaults to `None`.
+    pub interceptor: Option<Arc<dyn ValueInterceptor>>,
+    /// Add the settings of the conversion to the output under this root key, e.g. to tell later how an archived
+    /// document was converted. The record has the crate version, the enabled features and the options, and
+    /// `Config::from_embedded_config` restores the config from it. Functions, e.g. `InjectionOptions::interceptor`,
+    /// are only listed by name. A property of the document with the same name is kept. Defaults to `None`.
+    pub embed_config: Option<String>,
+}
+
+impl Default for InjectionOptions {
+    fn default() -> Self {
+        InjectionOptions {
+            node_id: None,
+            preserve_child_order: false,
+            child_order_key: "_order".to_owned(),
+            injected_fields: Vec::new(),
+            injected_fields_on_clash: NodeIdClash::Error,
+            absent_field_policy: HashMap::new(),
+            context_placement: ContextPlacement::Root,
+            interceptor: None,
+            embed_config: None,
+        }
+    }
+}
+
+/// The documents accepted for the conversion, see `Config::input`.
+#[derive(Debug, Clone)]
+pub struct InputOptions {
+    /// Stop with `ConversionError::DepthLimitExceeded` if elements are nested deeper than this number of levels,
+    /// e.g. to reject hostile documents before they exhaust the stack. The root element is at level 1.
+    /// Defaults to `None`.
+    pub max_xml_depth: Option<usize>,
+    /// Reject documents with whitespace before the XML declaration, which the XML spec doesn't allow,
+    /// e.g. for validating the documents. A UTF-8 byte order mark and whitespace before the first markup are
+    /// skipped either way. Defaults to `false`.
+    pub strict_prolog: bool,
+    /// Normalize the documents the way XML canonicalization does before the conversion, so equivalent documents
+    /// from different sources give the same output. Line endings in texts and attribute values become `\n`.
+    /// The other differences C14N removes never reach the output: attributes are always ordered by name and
+    /// namespace declarations, including redundant ones, are always dropped. The crate doesn't expose the raw XML
+    /// or hashes of it, so the option only changes the output of `ConversionProfile::V0_6`,
+    /// because `Latest` already normalizes line endings. Defaults to `false`.
+    pub normalize_input: bool,
+    /// Defines how the default namespace of the elements appears in the output.
+    /// Defaults to `NamespaceDefaultHandling::Ignore`.
+    pub namespace_default_handling: NamespaceDefaultHandling,
+    /// Reject documents with an XML version not in the list with `ConversionError::UnsupportedXmlVersion`,
+    /// e.g. `vec!["1.0".to_owned()]` to refuse XML 1.1. Documents without an XML declaration are version 1.0.
+    /// Defaults to `None`, which accepts all versions.
+    pub allowed_versions: Option<Vec<String>>,
+}
+
+impl Default for InputOptions {
+    fn default() -> Self {
+        InputOptions {
+            max_xml_depth: None,
+            strict_prolog: false,
+            normalize_input: false,
+            namespace_default_handling: NamespaceDefaultHandling::Ignore,
+            allowed_versions: None,
+        }
+    }
+}
+
+/// The shape and the encoding of the output, see `Config::output`.
+#[derive(Debug, Clone)]
+pub struct OutputOptions {
+    /// Replace JSON values nested deeper than this number of levels with `json_depth_sentinel`,
+    /// e.g. to keep the output within the limits of a document store. The top-level object is at level 1,
+    /// every object property and array element adds a level. The JSON depth may differ from the XML depth,
+    /// e.g. repeated elements add an array level. Defaults to `None`.
+    pub max_json_depth: Option<usize>,
+    /// The value replacing everything below `max_json_depth`. Defaults to `"…"`, use `Value::Null` to drop the values.
+    pub json_depth_sentinel: Value,
+    /// Wrap every value into an object with its JSON type for storage systems without a schema,
+    /// e.g. `<a><b>42</b></a>` becomes `{"a":{"$val":{"b":{"$val":42,"$t":"u64"}},"$t":"object"}}`.
+    /// The types are `u64`, `i64`, `f64`, `bool`, `string`, `null`, `object` and `array`.
+    /// Every value grows by 17 bytes plus the length of the type name, which often doubles the size of the output.
+    /// Defaults to `false`.
+    pub emit_type_annotations: bool,
+    /// Skip the rest of the document once all priority paths of `xml_string_to_json_focused` were found.
+    /// The elements on the priority paths are always converted completely, but the other elements are only
+    /// in the output if they were converted before the last priority path was found. `extract_values` stops
+    /// reading once every path without wildcards has a value. Defaults to `false`.
+    pub stop_after_priority_paths: bool,
+    /// The byte encoding of the JSON text written by `xml_string_to_json_encoded`, e.g.
+    /// `OutputEncoding::for_label("shift_jis")` for any encoding of `encoding_rs` writing ASCII as single bytes.
+    /// The other functions ignore it. Defaults to `OutputEncoding::Utf8`. Requires `encoding` feature.
+    #[cfg(feature = "encoding")]
+    pub output_encoding: OutputEncoding,
+    /// Move the arrays of repeated child elements with many members to temporary files while converting,
+    /// see `xml_to_json_writer`. The other functions return `ConversionError::SpillRequired` for documents
+    /// with such arrays. Defaults to `None`. Requires `spill` feature.
+    #[cfg(feature = "spill")]
+    pub spill: Option<SpillConfig>,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            max_json_depth: None,
+            json_depth_sentinel: Value::String("…".to_owned()),
+            emit_type_annotations: false,
+            stop_after_priority_paths: false,
+            #[cfg(feature = "encoding")]
+            output_encoding: OutputEncoding::Utf8,
+            #[cfg(feature = "spill")]
+            spill: None,
+        }
+    }
+}
+
+/// Warnings and progress reports of the conversions, see `Config::diagnostics`.
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticOptions {
+    /// XML paths of the values whose type inference decisions are reported as `Warning::TypeDecision`
+    /// by `xml_str_to_json_with_warnings`, e.g. to find out why a value is a string in some documents
+    /// and a number in the others. Defaults to an empty list.
+    pub trace_paths: Vec<String>,
+    /// Report arrays of repeated elements converted into objects with different properties as
+    /// `Warning::HeterogeneousArray` by `xml_str_to_json_with_warnings`, e.g. `<a><b x="1"/><b y="2"/></a>`.
+    /// Consumers often expect all items of an array to have the same shape. Values converted into scalars
+    /// count as having no properties. Defaults to `false`.
+    pub array_homogeneity_check: bool,
+    /// Report the progress of conversions reading from readers, e.g. for a progress indicator.
+    /// Defaults to `None`.
+    pub progress: Option<ProgressConfig>,
+}
+
+/// Returns the trimmed text as one of `serde::Value` types: int, float, bool or string.
+#[cfg(test)]
+fn parse_text(text: &str, leading_zero_as_string: bool, json_type: &JsonType) -> Value {
+    let mut config = Config::new_with_defaults();
+    config.leading_zero_as_string = leading_zero_as_string;
+    parse_value(text.trim(), &config, json_type).0
+}
+
+/// Returns the text as one of `serde::Value` types: int, float, bool or string, and the rule that decided it.
+/// The text is expected to be trimmed by the caller.
+fn parse_value(text: &str, config: &Config, json_type: &JsonType) -> (Value, Branch) {
+    match json_type {
+        JsonType::AlwaysInt => return parse_int(text).unwrap_or((Value::String(text.into()), Branch::FallbackString)),
+        JsonType::AlwaysFloat => {
+            return parse_float(text).unwrap_or((Value::String(text.into()), Branch::FallbackString))
+        }
+        JsonType::Measurement { units, emit } => {
+            return parse_measurement(text, config, units, emit)
+                .unwrap_or((Value::String(text.into()), Branch::FallbackString))
+        }
+        JsonType::Null(sentinels) if sentinels.contains(&text) => return (Value::Null, Branch::SentinelNull),
+        JsonType::Null(_) => return (Value::String(text.into()), Branch::FallbackString),
+        JsonType::Chain(links) => {
+            return parse_chain(text, config, links, 0)
+                .unwrap_or((Value::String(text.into()), Branch::FallbackString))
+        }
+        _ => (),
+    }
+
+    // enforce JSON String data type regardless of the underlying type
+    if json_type == &JsonType::AlwaysString {
+        return (Value::String(text.into()), Branch::OverrideString);
+    }
+
+    if let JsonType::DecimalString { normalize } = json_type {
+        return match normalize_decimal(text).filter(|_| *normalize) {
+            Some(decimal) => (Value::String(decimal), Branch::NormalizedDecimal),
+            None => (Value::String(text.into()), Branch::OverrideString),
+        };
+    }
+
+    // enforce JSON Bool data type
+    #[cfg(feature = "json_types")]
+    if let JsonType::Bool(true_values) = json_type {
+        if true_values.contains(&text) {
+            // any values matching the `true` list are bool/true
+            return (Value::Bool(true), Branch::OverrideBool);
+        } else {
+            // anything else is false
+            return (Value::Bool(false), Branch::OverrideBool);
+        }
+    }
+
+    // integer-encoded booleans
+    if config.values.boolean_from_numeric {
+        match text {
+            "1" => return (Value::Bool(true), Branch::Bool),
+            "0" => return (Value::Bool(false), Branch::Bool),
+            _ => (),
+        }
+    }
+
+    if config.values.inference.numbers {
+        if let Some(v) = parse_number(text, config) {
+            return v;
+        }
+    }
+
+    // booleans
+    if config.values.inference.booleans {
+        if let Ok(v) = text.parse::<bool>() {
+            return (Value::Bool(v), Branch::Bool);
+        }
+    }
+
+    // nulls
+    if config.values.inference.nulls && text == "null" {
+        return (Value::Null, Branch::SentinelNull);
+    }
+
+    (Value::String(text.into()), Branch::FallbackString)
+}
+
+/// Returns the text as a JSON integer, or `None` if it is not an integer.
+fn parse_int(text: &str) -> Option<(Value, Branch)> {
+    if let Ok(v) = text.parse::<u64>() {
+        return Some((Value::Number(v.into()), Branch::U64));
+    }
+    text.parse::<i64>().ok().map(|v| (Value::Number(v.into()), Branch::I64))
+}
+
+/// Returns the text as a JSON float, or `None` if it is not a number.
+fn parse_float(text: &str) -> Option<(Value, Branch)> {
+    let v = text.parse::<f64>().ok()?;
+    Number::from_f64(v).map(|v| (Value::Number(v), Branch::F64))
+}
+
+/// Returns the number of a `JsonType::Measurement` in the form of `emit`, or `None` if the text doesn't end
+/// with one of the units or the rest of it is not a number.
+fn parse_measurement(text: &str, config: &Config, units: &[String], emit: &MeasurementEmit) -> Option<(Value, Branch)> {
+    let unit = units
+        .iter()
+        .filter(|unit| !unit.is_empty() && text.ends_with(unit.as_str()))
+        .max_by_key(|unit| unit.len())?;
+    let number = text[..text.len() - unit.len()].trim_end();
+    let value = parse_number(number, config).map(|(value, _)| value).filter(Value::is_number)?;
+
+    let value = match emit {
+        MeasurementEmit::NumberOnly => value,
+        MeasurementEmit::Object { value_key, unit_key } => {
+            let mut map = Map::new();
+            map.insert(value_key.clone(), value);
+            map.insert(unit_key.clone(), Value::String(unit.clone()));
+            Value::Object(map)
+        }
+    };
+    Some((value, Branch::Measurement))
+}
+
+/// Returns the value of the first link of a `JsonType::Chain` that succeeds for the text,
+/// or `None` if none of them does. `depth` is the number of chains the links are nested in.
+fn parse_chain(text: &str, config: &Config, links: &[JsonType], depth: usize) -> Option<(Value, Branch)> {
+    let matches = |values: &[&str]| values.contains(&text);
+    links.iter().find_map(|link| match link {
+        JsonType::AlwaysString | JsonType::DecimalString { .. } => Some(parse_value(text, config, link)),
+        JsonType::Bool(true_values) if matches(true_values) => Some((Value::Bool(true), Branch::OverrideBool)),
+        JsonType::Null(sentinels) if matches(sentinels) => Some((Value::Null, Branch::SentinelNull)),
+        JsonType::Bool(_) | JsonType::Null(_) => None,
+        JsonType::AlwaysInt => parse_int(text),
+        JsonType::AlwaysFloat => parse_float(text),
+        JsonType::Measurement { units, emit } => parse_measurement(text, config, units, emit),
+        JsonType::Chain(links) if depth == 0 => parse_chain(text, config, links, depth + 1),
+        JsonType::Chain(_) => None,
+        JsonType::Infer => {
+            let (value, branch) = parse_value(text, config, link);
+            Some((value, branch)).filter(|_| branch != Branch::FallbackString)
+        }
+    })
+}
+
+/// Returns the canonical form of a decimal number like `-12.50`, i.e. without the trailing zeros
+/// of the fraction, a trailing dot or the sign of zero. Returns `None` if the text is not a decimal number.
+fn normalize_decimal(text: &str) -> Option<String> {
+    let (sign, digits) = match text.as_bytes().first() {
+        Some(b'-') | Some(b'+') => text.split_at(1),
+        _ => ("", text),
+    };
+    let (integer, fraction) = match digits.find('.') {
+        Some(dot) => (&digits[..dot], &digits[dot + 1..]),
+        None => (digits, ""),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if integer.len() + fraction.len() == 0 || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    let integer = if integer.is_empty() { "0" } else { integer };
+    let is_zero = integer.bytes().all(|b| b == b'0') && fraction.is_empty();
+    let sign = if is_zero { "" } else { sign };
+
+    let mut decimal = [sign, integer].concat();
+    if !fraction.is_empty() {
+        decimal.push('.');
+        decimal.push_str(fraction);
+    }
+    Some(decimal)
+}
+
+/// Returns the text as a JSON number using the configured parser, or as a string if the number
+/// has a leading zero that must be kept. Returns `None` if the text is not a number.
+fn parse_number(text: &str, config: &Config) -> Option<(Value, Branch)> {
+    let leading_zero_as_string = config.leading_zero_as_string;
+    match config.values.number_parser {
+        NumberParser::Standard => (),
+        #[cfg(feature = "arbitrary_precision")]
+        NumberParser::ArbitraryPrecision => {
+            // JSON syntax doesn't allow leading zeros, so they are left to the standard rules below
+            if let Ok(v) = Number::from_str(text) {
+                return Some((Value::Number(v), Branch::ArbitraryPrecision));
+            }
+        }
+        NumberParser::I128U128 => {
+            if let Ok(v) = text.parse::<u128>() {
+                if leading_zero_as_string && text.starts_with("0") && (v != 0 || text.len() > 1) {
+                    return Some((Value::String(text.into()), Branch::LeadingZeroString));
+                }
+                // out of range values are kept as strings unless serde_json has `arbitrary_precision`
+                let value = Number::from_u128(v).map_or_else(|| Value::String(text.into()), Value::Number);
+                let branch = if v <= u64::MAX as u128 { Branch::U64 } else { Branch::U128 };
+                return Some((value, branch));
+            }
+            if let Ok(v) = text.parse::<i128>() {
+                let value = Number::from_i128(v).map_or_else(|| Value::String(text.into()), Value::Number);
+                let branch = if v >= i64::MIN as i128 { Branch::I64 } else { Branch::I128 };
+                return Some((value, branch));
+            }
+        }
+        NumberParser::Custom(ref parser) => return parser(text).map(|v| (v, Branch::CustomNumber)),
+    }
+
+    // ints
+    if let Ok(v) = text.parse::<u64>() {
+        // don't parse octal numbers and those with leading 0
+        // `text` value "0" will always be converted into number 0, "0000" may be converted
+        // into 0 or "0000" depending on `leading_zero_as_string`
+        if leading_zero_as_string && text.starts_with("0") && (v != 0 || text.len() > 1) {
+            return Some((Value::String(text.into()), Branch::LeadingZeroString));
+        }
+        return Some((Value::Number(Number::from(v)), Branch::U64));
+    }
+    if text.starts_with('-') && !config.profile.negative_int_as_float() {
+        if let Ok(v) = text.parse::<i64>() {
+            return Some((Value::Number(Number::from(v)), Branch::I64));
+        }
+    }
+
+    // floats
+    if let Ok(v) = text.parse::<f64>() {
+        if text.starts_with("0") && !text.starts_with("0.") {
+            return Some((Value::String(text.into()), Branch::LeadingZeroString));
+        }
+        if let Some(val) = Number::from_f64(v) {
+            let val = if config.values.force_float_format { float_format(val, v) } else { val };
+            return Some((Value::Number(val), Branch::F64));
+        }
+    }
+
+    None
+}
+
+/// A float written without the exponent notation and with the `.0` of whole values, see
+/// `ValueOptions::force_float_format`.
+#[cfg(feature = "arbitrary_precision")]
+struct PlainFloat(f64);
+
+#[cfg(feature = "arbitrary_precision")]
+impl fmt::Display for PlainFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // `Display` of `f64` never uses the exponent notation, but drops the `.0`
+        if self.0.fract() == 0.0 {
+            write!(f, "{:.1}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+/// Replaces the number with the digits of the float in plain notation if `serde_json` would write it with
+/// an exponent. Without `arbitrary_precision` feature the number can't keep the digits, so it's returned as is.
+#[cfg(feature = "arbitrary_precision")]
+fn float_format(number: Number, v: f64) -> Number {
+    let text = number.to_string();
+    if !text.contains(['e', 'E']) {
+        return number;
+    }
+    Number::from_str(&PlainFloat(v).to_string()).unwrap_or(number)
+}
+
+#[cfg(not(feature = "arbitrary_precision"))]
+fn float_format(number: Number, _v: f64) -> Number {
+    number
+}
+
+/// Mutable state shared by all nodes of a single conversion. It is owned by `Converter`
+/// and reused between conversions to avoid allocating new buffers.
+#[derive(Default)]
+struct Context {
+    /// XML path of the node being converted, e.g. `/a/b/@c`
+    path: String,
+    /// XML paths of the values affected by `ValueOptions::json_string_policy`
+    illegal_char_paths: Vec<String>,
+    /// XML paths of the elements which didn't fit their `Config::tuple_rules`
+    tuple_mismatch_paths: Vec<String>,
+    /// XML paths and type inference decisions of the values at `DiagnosticOptions::trace_paths`
+    decisions: Vec<(String, Decision)>,
+    /// XML paths and differing keys of the arrays found by `DiagnosticOptions::array_homogeneity_check`
+    heterogeneous_arrays: Vec<(String, Vec<String>)>,
+    /// XML paths and child names of the elements flattened by `ArrayOptions::auto_flatten_plural_wrappers`
+    flattened_wrappers: Vec<(String, String)>,
+    /// XML path of the element being converted with sibling indices, e.g. `/a/b[1]`.
+    /// Only maintained for `NodeIdSource::SyntheticPath`.
+    id_path: String,
+    /// The attributes of the root element moved to the top level by `AttributeOptions::promote_root_attrs`.
+    /// Only set while converting a whole document with the option on.
+    root_attrs: Option<Map<String, Value>>,
+    /// The time of the conversion for `InjectedValue::TimestampRfc3339`, taken once it is needed
+    timestamp: Option<String>,
+    /// The names of the children with `JsonArray::Always` overrides by the XML path of their parent,
+    /// collected once they are needed, see `apply_empty_arrays`
+    #[cfg(feature = "json_types")]
+    empty_arrays: Option<HashMap<String, Vec<String>>>,
+    /// Which of the priority paths of `xml_string_to_json_focused` were found so far
+    priority_found: Vec<bool>,
+    /// The number of elements converted so far, for `DiagnosticOptions::progress`
+    elements: u64,
+    /// The property names produced by `KeyCharset::AsciiOnly` with the names they were converted from
+    ascii_keys: HashMap<String, String>,
+    /// The temporary files of the arrays moved out by `OutputOptions::spill`.
+    /// Only set while converting a document with `xml_to_json_writer`.
+    #[cfg(feature = "spill")]
+    spill: Option<spill::SpillFiles>,
+    /// The side table of the parser for the nodes of the document being converted
+    marks: parser::NodeMarks,
+}
+
+impl Context {
+    /// Prepares the buffers for the next conversion.
+    fn reset(&mut self) {
+        self.path.clear();
+        self.id_path.clear();
+        self.illegal_char_paths.clear();
+        self.tuple_mismatch_paths.clear();
+        self.decisions.clear();
+        self.heterogeneous_arrays.clear();
+        self.flattened_wrappers.clear();
+        self.root_attrs = None;
+        self.timestamp = None;
+        #[cfg(feature = "json_types")]
+        {
+            self.empty_arrays = None;
+        }
+        self.priority_found.clear();
+        self.elements = 0;
+        self.ascii_keys.clear();
+        self.marks = parser::NodeMarks::default();
+    }
+
+    /// Collects the non-fatal issues found during the conversion.
+    fn take_warnings(&mut self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if !self.illegal_char_paths.is_empty() {
+            warnings.push(Warning::IllegalJsonCharacters {
+                count: self.illegal_char_paths.len(),
+                paths: self.illegal_char_paths.drain(..).collect(),
+            });
+        }
+        if !self.tuple_mismatch_paths.is_empty() {
+            warnings.push(Warning::TupleRuleMismatch {
+                count: self.tuple_mismatch_paths.len(),
+                paths: self.tuple_mismatch_paths.drain(..).collect(),
+            });
+        }
+        for (path, decision) in self.decisions.drain(..) {
+            warnings.push(Warning::TypeDecision { path, decision });
+        }
+        for (path, differing_keys) in self.heterogeneous_arrays.drain(..) {
+            warnings.push(Warning::HeterogeneousArray { path, differing_keys });
+        }
+        for (path, item) in self.flattened_wrappers.drain(..) {
+            warnings.push(Warning::PluralWrapperFlattened { path, item });
+        }
+        warnings
+    }
+}
+
+/// Per-call settings of a single conversion that can't be kept in the reusable `Context`.
+#[derive(Default, Clone, Copy)]
+struct Hooks<'a> {
+    /// The conversion stops with `ConversionError::Canceled` once this flag is set
+    cancel: Option<&'a AtomicBool>,
+    /// JSON types taking precedence over all overrides from the config, see `xml_string_to_json_with_overrides`
+    #[cfg(feature = "json_types")]
+    overrides: &'a [(String, JsonType)],
+    /// The name of the converted document for `InjectedValue::SourceName`
+    source_name: Option<&'a str>,
+    /// Properties added to the output, see `xml_string_to_json_with_context`
+    context: Option<&'a HashMap<String, Value>>,
+    /// XML paths converted before the rest of the document, see `xml_string_to_json_focused`
+    priority_paths: &'a [&'a str],
+    /// Reports the number of converted elements, see `xml_string_to_json_with_progress`
+    progress: Option<ElementProgress<'a>>,
+    /// The comments before the root element with `TextOptions::comment_handling`
+    prolog_comments: &'a [&'a str],
+    /// The processing instructions before the root element with `TextOptions::processing_instructions_key`
+    prolog_instructions: &'a [&'a str],
+    /// The last steps of the override paths, see `CompiledConfig`
+    #[cfg(feature = "json_types")]
+    override_names: Option<&'a HashSet<String>>,
+    /// What the parser found besides the tree of elements
+    side_table: Option<&'a parser::SideTable>,
+}
+
+impl<'a> Hooks<'a> {
+    /// Returns an error if the caller asked to cancel the conversion.
+    fn check_canceled(&self) -> Result<(), ConversionError> {
+        match self.cancel {
+            Some(cancel) if cancel.load(Ordering::Relaxed) => Err(ConversionError::Canceled),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The progress function of `xml_string_to_json_with_progress` with the estimated number of elements.
+#[derive(Clone, Copy)]
+struct ElementProgress<'a> {
+    callback: &'a dyn Fn(usize, usize),
+    total: usize,
+    /// The number of elements between the calls
+    every: usize,
+}
+
+impl<'a> ElementProgress<'a> {
+    /// Reports the progress of roughly every hundredth of the document.
+    fn new(callback: &'a dyn Fn(usize, usize), xml: &str) -> Self {
+        let total = estimate_elements(xml);
+        ElementProgress {
+            callback,
+            total,
+            every: std::cmp::max(1, total / 100),
+        }
+    }
+
+    fn report(&self, processed: usize) {
+        if processed.is_multiple_of(self.every) {
+            // the estimate may be too low, e.g. for documents with entities
+            (self.callback)(processed, std::cmp::max(processed, self.total));
+        }
+    }
+}
+
+/// Counts the start tags of the document without parsing it. Tags inside of comments and CDATA sections
+/// are counted as well, so it's only an estimate.
+fn estimate_elements(xml: &str) -> usize {
+    xml.as_bytes()
+        .windows(2)
+        .filter(|pair| pair[0] == b'<' && !matches!(pair[1], b'/' | b'!' | b'?'))
+        .count()
+}
+
+/// Control characters other than tab, LF and CR are troublesome for JSON consumers.
+fn is_illegal_json_char(c: char) -> bool {
+    c.is_control() && c != '\t' && c != '\n' && c != '\r'
+}
+
+/// Applies `ValueOptions::json_string_policy` to a single string and records the current path if it was affected.
+fn apply_string_policy(
+    text: String,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<String, ConversionError> {
+    if !text.chars().any(is_illegal_json_char) {
+        return Ok(text);
+    }
+
+    ctx.illegal_char_paths.push(ctx.path.clone());
+
+    match config.values.json_string_policy {
+        JsonStringPolicy::Keep => Ok(text),
+        JsonStringPolicy::StripControls => {
+            Ok(text.chars().filter(|c| !is_illegal_json_char(*c)).collect())
+        }
+        JsonStringPolicy::ReplaceWith(r) => Ok(text
+            .chars()
+            .map(|c| if is_illegal_json_char(c) { r } else { c })
+            .collect()),
+        JsonStringPolicy::Error => Err(ConversionError::IllegalCharacter {
+            path: ctx.path.clone(),
+        }),
+    }
+}
+
+/// Applies `ValueOptions::json_string_policy` to a JSON property name, if enabled, and `KeyOptions::key_charset`,
+/// and truncates it to `KeyOptions::key_length_limit`.
+fn convert_key(
+    key: String,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<String, ConversionError> {
+    let key = match config.keys.max_name_length {
+        Some(max) if key.len() > max => limit_name_length(key, max, config)?,
+        _ => key,
+    };
+    let key = if config.values.json_string_policy_keys {
+        apply_string_policy(key, config, ctx)?
+    } else {
+        key
+    };
+    let key = match config.keys.key_charset {
+        KeyCharset::AllowAll => key,
+        ref key_charset => apply_key_charset(key, key_charset, config, ctx)?,
+    };
+
+    match config.keys.key_length_limit {
+        Some(limit) => Ok(truncate_key(key, limit, &config.keys.key_truncation_suffix)),
+        None => Ok(key),
+    }
+}
+
+/// Applies `KeyOptions::long_name_policy` to a name longer than `max` bytes. The truncated name keeps the hash suffix
+/// even if `max` is shorter than it.
+fn limit_name_length(key: String, max: usize, config: &Config) -> Result<String, ConversionError> {
+    if config.keys.long_name_policy == LongNamePolicy::Error {
+        return Err(ConversionError::NameTooLong {
+            start: key.chars().take(64).collect(),
+            length: key.len(),
+        });
+    }
+
+    let hash = format!("~{:016x}", fnv1a(key.as_bytes()));
+    let mut end = max.saturating_sub(hash.len());
+    while !key.is_char_boundary(end) {
+        end -= 1;
+    }
+    Ok([&key[..end], hash.as_str()].concat())
+}
+
+/// The 64-bit FNV-1a hash, which unlike `DefaultHasher` doesn't change between Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Replaces or rejects the non-ASCII characters of the key, see `KeyCharset`.
+fn apply_key_charset(
+    key: String,
+    key_charset: &KeyCharset,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<String, ConversionError> {
+    let options = [
+        ("xml_attr_prefix", config.xml_attr_prefix.as_str()),
+        ("xml_text_node_prop_name", config.xml_text_node_prop_name.as_str()),
+    ];
+    let replacement = match key_charset {
+        KeyCharset::AsciiOnly { replacement } => Some(("key_charset", replacement.as_str())),
+        _ => None,
+    };
+    if let Some((option, _)) = options.iter().chain(replacement.iter()).find(|(_, value)| !value.is_ascii()) {
+        return Err(ConversionError::NonAsciiKeyOption {
+            option: (*option).to_owned(),
+        });
+    }
+
+    let replacement = match key_charset {
+        KeyCharset::AsciiOnly { replacement } => replacement,
+        _ if key.is_ascii() => return Ok(key),
+        _ => {
+            return Err(ConversionError::NonAsciiKey {
+                path: ctx.path.clone(),
+                key,
+            })
+        }
+    };
+
+    let ascii: String = if key.is_ascii() {
+        key.clone()
+    } else {
+        key.chars().fold(String::with_capacity(key.len()), |mut ascii, c| {
+            if c.is_ascii() {
+                ascii.push(c);
+            } else {
+                ascii.push_str(replacement);
+            }
+            ascii
+        })
+    };
+    match ctx.ascii_keys.get(&ascii) {
+        Some(original) if *original != key => Ok(format!("{}~{:08x}", ascii, fnv1a(key.as_bytes()) as u32)),
+        Some(_) => Ok(ascii),
+        None => {
+            ctx.ascii_keys.insert(ascii.clone(), key);
+            Ok(ascii)
+        }
+    }
+}
+
+/// Truncates the key to `limit` characters including the suffix.
+fn truncate_key(key: String, limit: usize, suffix: &str) -> String {
+    if key.chars().count() <= limit {
+        return key;
+    }
+
+    let suffix_len = suffix.chars().count();
+    if suffix_len >= limit {
+        return key.chars().take(limit).collect();
+    }
+
+    key.chars()
+        .take(limit - suffix_len)
+        .chain(suffix.chars())
+        .collect()
+}
+
+/// Converts the trimmed text of an attribute or a text node with `parse_value` and applies
+/// `ValueOptions::json_string_policy` to string results. Text with CDATA sections is a string with
+/// `TextOptions::cdata_as_string`, which the caller tells with `cdata`.
+fn convert_text(
+    text: &str,
+    cdata: bool,
+    json_type: &JsonType,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Value, ConversionError> {
+    let (mut value, branch) = if cdata && config.text.cdata_as_string {
+        (Value::String(text.to_owned()), Branch::Cdata)
+    } else {
+        parse_text_or_enum(text, json_type, config, ctx)
+    };
+
+    if config.values.strict_json_types && branch == Branch::FallbackString {
+        if let JsonType::AlwaysInt | JsonType::AlwaysFloat = json_type {
+            return Err(ConversionError::TypeConversionFailed { path: ctx.path.clone(), value: text.to_owned() });
+        }
+    }
+
+    // `trace_paths` is empty unless debugging
+    if !config.diagnostics.trace_paths.is_empty() && is_traced(config, &ctx.path) {
+        ctx.decisions.push((
+            ctx.path.clone(),
+            Decision {
+                input_text: text.to_owned(),
+                branch,
+                output_type: json_type_name(&value),
+            },
+        ));
+    }
+
+    if let Value::String(s) = value {
+        value = Value::String(apply_string_policy(s, config, ctx)?);
+    } else if config.values.emit_number_as_hybrid
+        && value.is_number()
+        && branch != Branch::EnumMapping
+        && branch != Branch::TextNodeMapper
+    {
+        let mut hybrid = Map::new();
+        hybrid.insert("$num".to_owned(), value);
+        hybrid.insert("$str".to_owned(), Value::String(text.to_owned()));
+        value = Value::Object(hybrid);
+    }
+
+    if let Some(ref interceptor) = config.injection.interceptor {
+        // element names can't start with `@`, so the last step tells attributes apart
+        let kind = match ctx.path.rsplit('/').next() {
+            Some(name) if name.starts_with('@') => NodeKind::Attribute,
+            _ => NodeKind::Text,
+        };
+        value = interceptor.on_value(&ctx.path, kind, text, value);
+    }
+
+    Ok(value)
+}
+
+/// Returns the text nodes of the element. The tail text of the child elements is only included
+/// with `TailTextHandling::AppendToParent`.
+fn element_texts<'a>(el: &'a Element, config: &Config) -> impl Iterator<Item = &'a str> + 'a {
+    let own_text_only = config.text.tail_text_handling != TailTextHandling::AppendToParent;
+    el.nodes()
+        .take_while(move |node| !own_text_only || node.as_element().is_none())
+        .filter_map(Node::as_text)
+}
+
+/// Returns the tail text following each child element, see `TailTextHandling::AsField`.
+fn tail_texts(el: &Element) -> Vec<String> {
+    let mut tails = Vec::new();
+    for node in el.nodes() {
+        match node {
+            Node::Element(_) => tails.push(String::new()),
+            Node::Text(text) => {
+                if let Some(tail) = tails.last_mut() {
+                    tail.push_str(text);
+                }
+            }
+            _ => (),
+        }
+    }
+    tails
+}
+
+/// Returns `true` if the element has CDATA sections with `TextOptions::cdata_as_string`.
+fn has_cdata(el: &Element, config: &Config, ctx: &Context) -> bool {
+    config.text.cdata_as_string && ctx.marks.has_cdata(el)
+}
+
+/// Returns the trimmed non-empty text nodes of the element with `true` for the CDATA sections,
+/// which are not trimmed with `TextOptions::cdata_as_string`.
+fn text_segments<'a>(el: &'a Element, config: &Config, ctx: &Context) -> Vec<(&'a str, bool)> {
+    let cdata = has_cdata(el, config, ctx);
+    element_texts(el, config)
+        .map(|text| {
+            if cdata && ctx.marks.is_cdata(text) {
+                (text, true)
+            } else {
+                (text.trim(), false)
+            }
+        })
+        .filter(|(text, _)| !text.is_empty())
+        .collect()
+}
+
+/// Joins the text nodes of the element according to `TextOptions::text_join`. With `TextOptions::cdata_as_string`
+/// it also returns the range from the start of the first to the end of the last CDATA section in the text.
+fn join_text<'a>(el: &'a Element, config: &Config, ctx: &Context) -> (Cow<'a, str>, Option<(usize, usize)>) {
+    let separator = match config.text.text_join {
+        TextJoin::Concat | TextJoin::KeepSegments if !has_cdata(el, config, ctx) => {
+            return (Cow::Owned(element_texts(el, config).collect()), None)
+        }
+        TextJoin::Concat | TextJoin::KeepSegments => "",
+        TextJoin::Space => " ",
+        TextJoin::Separator(ref separator) => separator,
+    };
+    let segments = match config.text.text_join {
+        TextJoin::Concat | TextJoin::KeepSegments => {
+            element_texts(el, config).map(|text| (text, ctx.marks.is_cdata(text))).collect()
+        }
+        _ => text_segments(el, config, ctx),
+    };
+
+    match segments.as_slice() {
+        [] => return (Cow::Borrowed(""), None),
+        [(text, false)] => return (Cow::Borrowed(*text), None),
+        _ => (),
+    }
+    let mut joined = String::new();
+    let mut cdata = None;
+    for (index, (text, is_cdata)) in segments.into_iter().enumerate() {
+        if index > 0 {
+            joined.push_str(separator);
+        }
+        if is_cdata {
+            let start = cdata.map_or(joined.len(), |(start, _)| start);
+            cdata = Some((start, joined.len() + text.len()));
+        }
+        joined.push_str(text);
+    }
+    (Cow::Owned(joined), cdata)
+}
+
+/// Trims the whitespace around the text, but not the range of CDATA sections returned by `join_text`.
+fn trim_around_cdata(text: &str, cdata: Option<(usize, usize)>) -> &str {
+    match cdata {
+        Some((start, end)) => {
+            let start = start - text[..start].trim_start().len();
+            let end = end + text[end..].trim_end().len();
+            &text[start..end]
+        }
+        None => text.trim(),
+    }
+}
+
+/// Converts the joined text of the element, or every text node with `TextJoin::KeepSegments`.
+/// Returns `true` with the value if it is an array of several segments.
+fn convert_element_text(
+    el: &Element,
+    text: &str,
+    json_type: &JsonType,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(Value, bool), ConversionError> {
+    if config.text.text_join == TextJoin::KeepSegments {
+        let segments = text_segments(el, config, ctx);
+        if segments.len() > 1 {
+            let mut values = Vec::with_capacity(segments.len());
+            for (segment, cdata) in segments {
+                values.push(convert_text(segment, cdata, json_type, config, ctx)?);
+            }
+            return Ok((Value::Array(values), true));
+        }
+    }
+
+    let cdata = has_cdata(el, config, ctx);
+    Ok((convert_text(text, cdata, json_type, config, ctx)?, false))
+}
+
+/// Returns the value of a known enum member or the parsed text.
+#[cfg_attr(not(feature = "json_types"), allow(unused_variables))]
+fn parse_text_or_enum(text: &str, json_type: &JsonType, config: &Config, ctx: &Context) -> (Value, Branch) {
+    // known enum values are replaced with their codes, unknown values remain strings
+    #[cfg(feature = "json_types")]
+    if let Some(values) = find_by_path(&config.enum_mappings, &ctx.path, &config.path_anchor) {
+        return match values.iter().position(|v| v == text) {
+            Some(index) => (Value::Number(Number::from(index)), Branch::EnumMapping),
+            None => (Value::String(text.to_owned()), Branch::FallbackString),
+        };
+    }
+
+    if let (Some(mapper), JsonType::Infer) = (config.values.text_node_mapper.as_ref(), json_type) {
+        return ((mapper.0)(&ctx.path, text), Branch::TextNodeMapper);
+    }
+
+    if config.values.coerce_bool_attrs && json_type == &JsonType::Infer && is_attr_path(&ctx.path) {
+        let listed = |values: &[String]| values.iter().any(|v| v.eq_ignore_ascii_case(text));
+        if listed(&config.values.bool_truthy) {
+            return (Value::Bool(true), Branch::CoercedBool);
+        } else if listed(&config.values.bool_falsy) {
+            return (Value::Bool(false), Branch::CoercedBool);
+        }
+    }
+
+    parse_value(text, config, json_type)
+}
+
+/// Returns `true` if the last step of the path is an attribute. Element names can't start with `@`.
+fn is_attr_path(path: &str) -> bool {
+    path.rsplit('/').next().is_some_and(|name| name.starts_with('@'))
+}
+
+/// Returns `true` if the path is listed in `DiagnosticOptions::trace_paths`.
+fn is_traced(config: &Config, path: &str) -> bool {
+    let path = path.trim_start_matches('/');
+    config.diagnostics.trace_paths.iter().any(|p| p.trim_start_matches('/') == path)
+}
+
+/// Returns the name of the JSON type of the value, e.g. `number`.
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Copies the attributes and the text of the element to the keys of `KeyOptions::dual_emit`.
+fn apply_dual_emit(
+    el: &Element,
+    data: &mut Map<String, Value>,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    let dual = match config.keys.dual_emit {
+        Some(ref dual) => dual,
+        None => return Ok(()),
+    };
+
+    // the legacy and the new key of each value, and whether the new key is nested
+    let mut keys = Vec::new();
+    for (k, _) in el.attrs() {
+        let legacy = convert_key([config.xml_attr_prefix.as_str(), k].concat(), config, ctx)?;
+        if data.contains_key(&legacy) {
+            let new = convert_key([dual.xml_attr_prefix.as_str(), k].concat(), config, ctx)?;
+            keys.push((legacy, new, dual.attrs_key.is_some()));
+        }
+    }
+    let legacy = convert_key(config.xml_text_node_prop_name.clone(), config, ctx)?;
+    if data.contains_key(&legacy) {
+        let new = convert_key(dual.xml_text_node_prop_name.clone(), config, ctx)?;
+        keys.push((legacy, new, false));
+    }
+    // values under the same key in both conventions are not duplicated
+    keys.retain(|(legacy, new, nested)| *nested || legacy != new);
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let mark = |key: String, set: KeySet| match dual.deprecate {
+        Some(deprecated) if deprecated == set => [dual.deprecated_prefix.as_str(), &key].concat(),
+        _ => key,
+    };
+    let clash = |key: &str| ConversionError::DualKeyClash {
+        path: ctx.path.clone(),
+        key: key.to_owned(),
+    };
+
+    let mut nested = Map::new();
+    let mut flat = Vec::new();
+    for (legacy, new, is_nested) in keys {
+        let value = match dual.deprecate {
+            Some(KeySet::Legacy) => {
+                let value = data.remove(&legacy).unwrap_or(Value::Null);
+                data.insert(mark(legacy, KeySet::Legacy), value.clone());
+                value
+            }
+            _ => data[&legacy].clone(),
+        };
+        if is_nested {
+            nested.insert(new, value);
+        } else {
+            flat.push((mark(new, KeySet::New), value));
+        }
+    }
+    if let Some(ref attrs_key) = dual.attrs_key {
+        if !nested.is_empty() {
+            flat.push((mark(attrs_key.clone(), KeySet::New), Value::Object(nested)));
+        }
+    }
+
+    for (key, value) in flat {
+        if data.contains_key(&key) {
+            return Err(clash(&key));
+        }
+        data.insert(key, value);
+    }
+    Ok(())
+}
+
+/// Adds the tail text of a child element to its value, see `TailTextHandling::AsField`.
+fn add_tail_text(
+    value: Value,
+    tail: &str,
+    field: &str,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Value, ConversionError> {
+    let mut data = match value {
+        Value::Object(data) => data,
+        value => {
+            let mut data = Map::new();
+            data.insert(convert_key(config.xml_text_node_prop_name.clone(), config, ctx)?, value);
+            data
+        }
+    };
+    let tail = apply_string_policy(tail.to_owned(), config, ctx)?;
+    data.insert(convert_key(field.to_owned(), config, ctx)?, Value::String(tail));
+    Ok(Value::Object(data))
+}
+
+/// Converts the children of a wrapper element into an array, see `ArrayOptions::auto_flatten_plural_wrappers`.
+/// Returns `None` if the element is not a wrapper.
+fn flatten_plural_wrapper(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Option<Value>, ConversionError> {
+    let item = match el.children().next() {
+        Some(child) => child.name(),
+        None => return Ok(None),
+    };
+    let path = ctx.path.trim_start_matches('/');
+    let wrapper = el.attrs().next().is_none()
+        && el.text().trim().is_empty()
+        && el.children().all(|child| child.name() == item)
+        && is_plural_of(el.name(), item, config)
+        && !config.arrays.plural_wrapper_exclusions.iter().any(|p| p.trim_start_matches('/') == path);
+    if !wrapper {
+        return Ok(None);
+    }
+
+    let synthetic_ids = synthetic_ids(config);
+    let id_path_len = ctx.id_path.len();
+    let mut items = Value::Array(Vec::new());
+    for (count, child) in el.children().enumerate() {
+        if synthetic_ids {
+            ctx.id_path.truncate(id_path_len);
+            ctx.id_path.push_str(&format!("/{}[{}]", item, count));
+        }
+        if let Some(value) = convert_node(child, config, ctx, hooks)? {
+            push_member(&mut items, value, item, config, ctx)?;
+        }
+    }
+    ctx.id_path.truncate(id_path_len);
+
+    ctx.flattened_wrappers.push((ctx.path.clone(), item.to_owned()));
+    Ok(Some(items))
+}
+
+/// Returns `true` if `singular` looks like the singular form of `plural`.
+fn is_plural_of(plural: &str, singular: &str, config: &Config) -> bool {
+    if let Some(pair) = config.arrays.plural_pairs.get(plural) {
+        return pair == singular;
+    }
+    [singular, "s"].concat() == plural || [singular, "es"].concat() == plural
+}
+
+/// Converts the attributes of the element into JSON properties.
+fn convert_attributes(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Map<String, Value>, ConversionError> {
+    let mut data = Map::new();
+    let path_len = ctx.path.len();
+
+    // promoted attributes must not clash with the child elements
+    let child_names: HashSet<&str> = match config.attributes.promote_attributes {
+        PromoteAttrs::None => HashSet::new(),
+        _ => el.children().map(|child| child.name()).collect(),
+    };
+
+    for (k, v) in el.attrs() {
+        // add the current node to the path
+        ctx.path.push_str("/@");
+        ctx.path.push_str(k);
+        // get the json_type for this node
+        let (_, json_type_value) = get_json_type(config, hooks, &ctx.path);
+        let key = if !config.attributes.promote_attributes.promotes(k, &ctx.path) {
+            [config.xml_attr_prefix.as_str(), k].concat()
+        } else if child_names.contains(k) {
+            [k, config.attributes.promoted_attr_collision_suffix.as_str()].concat()
+        } else {
+            k.to_owned()
+        };
+        let key = convert_key(key, config, ctx)?;
+        // `QuickXmlParser` passes all values of repeated attributes in its side table
+        let repeated = match config.attributes.duplicate_source_attributes {
+            DupAttr::MergeArray => ctx.marks.repeated_attr(el, k).map(<[String]>::to_vec),
+            _ => None,
+        };
+        let value = match repeated {
+            Some(values) => {
+                let values = values
+                    .iter()
+                    .map(|v| convert_attr_value(k, v, json_type_value, config, ctx))
+                    .collect::<Result<_, _>>()?;
+                Value::Array(values)
+            }
+            None => convert_attr_value(k, v, json_type_value, config, ctx)?,
+        };
+        data.insert(key, value);
+        ctx.path.truncate(path_len);
+    }
+
+    // computed properties only see the real attributes
+    let mut computed = Vec::new();
+    for (path, key, f) in &config.attributes.virtual_attributes {
+        if path.trim_start_matches('/') != ctx.path.trim_start_matches('/') || data.contains_key(key) {
+            continue;
+        }
+        if let Some(value) = (f.0)(&data) {
+            computed.push((key.clone(), value));
+        }
+    }
+    data.extend(computed);
+
+    // the path of the root element is the only one without other slashes
+    if let Some(ref mut root_attrs) = ctx.root_attrs {
+        if ctx.path.rfind('/') == Some(0) {
+            *root_attrs = data;
+            return Ok(Map::new());
+        }
+    }
+
+    Ok(data)
+}
+
+/// Applies `AttributeOptions::attr_value_transforms` to the raw attribute value and converts the result with
+/// `convert_text`.
+fn convert_attr_value(
+    name: &str,
+    value: &str,
+    json_type: &JsonType,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Value, ConversionError> {
+    match config.attributes.attr_value_transforms.get(name) {
+        Some(transform) => {
+            let value = (transform.0)(value);
+            convert_text(trim_attr(&value, config), false, json_type, config, ctx)
+        }
+        None => convert_text(trim_attr(value, config), false, json_type, config, ctx),
+    }
+}
+
+/// Trims the attribute value according to `Config::profile`, unless `TextOptions::trim_text` is off.
+fn trim_attr<'a>(value: &'a str, config: &Config) -> &'a str {
+    if config.text.trim_text {
+        config.profile.trim_attr(value)
+    } else {
+        value
+    }
+}
+
+/// Converts the element into an array of the listed attribute values, see `Config::tuple_rules`.
+/// Returns `None` if the element has any other attributes, text or child elements.
+#[cfg(feature = "json_types")]
+fn convert_tuple(
+    el: &Element,
+    attrs: &[String],
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Option<Value>, ConversionError> {
+    if el.children().next().is_some()
+        || el.texts().any(|text| !text.trim().is_empty())
+        || el.attrs().any(|(k, _)| !attrs.iter().any(|attr| attr == k))
+    {
+        return Ok(None);
+    }
+
+    let path_len = ctx.path.len();
+    let mut values = Vec::with_capacity(attrs.len());
+
+    for attr in attrs {
+        let value = match el.attr(attr) {
+            Some(v) => {
+                ctx.path.push_str("/@");
+                ctx.path.push_str(attr);
+                let (_, json_type_value) = get_json_type(config, hooks, &ctx.path);
+                let value = convert_attr_value(attr, v, json_type_value, config, ctx)?;
+                ctx.path.truncate(path_len);
+                value
+            }
+            None => Value::Null,
+        };
+        values.push(value);
+    }
+
+    Ok(Some(Value::Array(values)))
+}
+
+/// Returns the properties with the comments of the element according to `TextOptions::comment_handling`
+/// and with its processing instructions, see `TextOptions::processing_instructions_key`.
+fn convert_comments(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Vec<(String, Value)>, ConversionError> {
+    let mut properties = Vec::new();
+    if let Some(ref key) = config.text.processing_instructions_key {
+        let instructions = ctx.marks.instructions(el).to_vec();
+        let instructions = instructions.iter().map(String::as_str);
+        if let Some(value) = processing_instructions_value(instructions, config, ctx)? {
+            properties.push((convert_key(key.clone(), config, ctx)?, value));
+        }
+    }
+    if let Some(comments) = convert_comment_nodes(el, config, ctx)? {
+        properties.push(comments);
+    }
+    Ok(properties)
+}
+
+/// Returns the property with the comments of the element according to `TextOptions::comment_handling`
+/// or `None` if there are no comments to keep.
+fn convert_comment_nodes(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Option<(String, Value)>, ConversionError> {
+    let comments = el.nodes().filter_map(|node| match node {
+        Node::Comment(comment) => Some(comment.trim()),
+        _ => None,
+    });
+    comments_property(comments, config, ctx)
+}
+
+/// Returns the property with the comments according to `TextOptions::comment_handling`
+/// or `None` if there are no comments to keep.
+fn comments_property<'a>(
+    mut comments: impl DoubleEndedIterator<Item = &'a str>,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Option<(String, Value)>, ConversionError> {
+    let (key, value) = match config.text.comment_handling {
+        CommentHandling::Ignore => return Ok(None),
+        CommentHandling::PreserveAsField(ref key) => match comments.next_back() {
+            Some(comment) => (key, Value::String(apply_string_policy(comment.to_owned(), config, ctx)?)),
+            None => return Ok(None),
+        },
+        CommentHandling::PreserveAsArray(ref key) => {
+            let mut values = Vec::new();
+            for comment in comments {
+                values.push(Value::String(apply_string_policy(comment.to_owned(), config, ctx)?));
+            }
+            if values.is_empty() {
+                return Ok(None);
+            }
+            (key, Value::Array(values))
+        }
+    };
+
+    Ok(Some((convert_key(key.clone(), config, ctx)?, value)))
+}
+
+/// Returns a single processing instruction as an object and several as an array, see
+/// `TextOptions::processing_instructions_key`. The instructions are passed without `<?` and `?>`.
+fn processing_instructions_value<'a>(
+    instructions: impl Iterator<Item = &'a str>,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<Option<Value>, ConversionError> {
+    let mut values = Vec::new();
+    for instruction in instructions {
+        let (target, data) = match instruction.find(char::is_whitespace) {
+            Some(end) => (&instruction[..end], instruction[end..].trim_start()),
+            None => (instruction, ""),
+        };
+        let mut value = Map::new();
+        value.insert("target".to_owned(), Value::String(target.to_owned()));
+        value.insert("data".to_owned(), Value::String(apply_string_policy(data.to_owned(), config, ctx)?));
+        values.push(Value::Object(value));
+    }
+    Ok(match values.len() {
+        0 => None,
+        1 => values.pop(),
+        _ => Some(Value::Array(values)),
+    })
+}
+
+/// Returns the comments and the processing instructions before the root element, which the parsers drop.
+/// The processing instructions are returned without `<?` and `?>`. The XML declaration and the document type
+/// declaration are skipped.
+fn prolog_nodes(xml: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut comments = Vec::new();
+    let mut instructions = Vec::new();
+    let mut rest = xml.strip_prefix('\u{feff}').unwrap_or(xml).trim_start();
+
+    loop {
+        let end = if let Some(comment) = rest.strip_prefix("<!--") {
+            match comment.find("-->") {
+                Some(end) => {
+                    comments.push(comment[..end].trim());
+                    end + 7
+                }
+                None => break,
+            }
+        } else if let Some(instruction) = rest.strip_prefix("<?") {
+            match instruction.find("?>") {
+                Some(end) => {
+                    if !is_xml_declaration(&instruction[..end]) {
+                        instructions.push(&instruction[..end]);
+                    }
+                    end + 4
+                }
+                None => break,
+            }
+        } else if rest.starts_with("<!DOCTYPE") {
+            // the internal subset may contain `>`
+            let subset_end = match (rest.find('['), rest.find('>')) {
+                (Some(open), Some(close)) if open < close => rest[open..]
+                    .find(']')
+                    .and_then(|end| rest[open + end..].find('>').map(|close| open + end + close + 1)),
+                (_, close) => close.map(|end| end + 1),
+            };
+            match subset_end {
+                Some(end) => end,
+                None => break,
+            }
+        } else {
+            break;
+        };
+        rest = rest[end..].trim_start();
+    }
+
+    (comments, instructions)
+}
+
+/// Returns `true` if the content of the processing instruction is the XML declaration, e.g. `xml version="1.0"`.
+fn is_xml_declaration(instruction: &str) -> bool {
+    instruction.split(char::is_whitespace).next() == Some("xml")
+}
+
+/// Adds `null` properties for the missing children of the element with `AbsentPolicy::Null`,
+/// see `InjectionOptions::absent_field_policy`.
+fn apply_absent_fields(
+    data: &mut Map<String, Value>,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    if config.injection.absent_field_policy.is_empty() {
+        return Ok(());
+    }
+
+    let parent = ctx.path.trim_start_matches('/');
+    let mut missing = Vec::new();
+    for (path, policy) in &config.injection.absent_field_policy {
+        if *policy != AbsentPolicy::Null {
+            continue;
+        }
+        let (path_parent, name) = match path.trim_start_matches('/').rsplit_once('/') {
+            Some(split) => split,
+            None => continue,
+        };
+        if path_parent != parent {
+            continue;
+        }
+        let key = match name.strip_prefix('@') {
+            Some(attr) => [config.xml_attr_prefix.as_str(), attr].concat(),
+            None => name.to_owned(),
+        };
+        missing.push(key);
+    }
+
+    for key in missing {
+        let key = convert_key(key, config, ctx)?;
+        data.entry(key).or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+/// Adds an empty array for every child element with a `JsonArray::Always` override the element doesn't have,
+/// so an empty container has the same shape as one with items.
+#[cfg(feature = "json_types")]
+fn apply_empty_arrays(
+    data: &mut Map<String, Value>,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    let empty_arrays = ctx.empty_arrays.get_or_insert_with(|| empty_array_parents(config));
+    if empty_arrays.is_empty() {
+        return Ok(());
+    }
+
+    let parent = ctx.path.as_str();
+    let missing: Vec<String> = match config.path_anchor {
+        PathAnchor::Root => empty_arrays.get(parent.trim_start_matches('/')).cloned().unwrap_or_default(),
+        // the parent paths are suffixes starting at a step, e.g. `/b/c` of `/a/b/c`
+        PathAnchor::Anywhere => parent
+            .match_indices('/')
+            .filter_map(|(start, _)| empty_arrays.get(&parent[start..]))
+            .flatten()
+            .cloned()
+            .collect(),
+    };
+
+    for name in missing {
+        let key = convert_key(name, config, ctx)?;
+        data.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+    }
+    Ok(())
+}
+
+/// Groups the names of the children with `JsonArray::Always` overrides by the path of their parent for
+/// `apply_empty_arrays`. The parent paths are anchored like `Config::path_anchor`: without the leading `/`
+/// for `PathAnchor::Root`, and with it for `PathAnchor::Anywhere`.
+#[cfg(feature = "json_types")]
+fn empty_array_parents(config: &Config) -> HashMap<String, Vec<String>> {
+    let mut parents: HashMap<String, Vec<String>> = HashMap::new();
+    for (path, json_array) in &config.json_type_overrides {
+        if let JsonArray::Infer(_) = json_array {
+            continue;
+        }
+        let (path_parent, name) = match path.rsplit_once('/') {
+            Some(split) => split,
+            None => continue,
+        };
+        // attributes are never repeated
+        if name.starts_with('@') {
+            continue;
+        }
+        let path_parent = match config.path_anchor {
+            PathAnchor::Root => path_parent.trim_start_matches('/'),
+            // suffixes without a parent like `/b` would add the array to every object
+            PathAnchor::Anywhere if path_parent.starts_with('/') => path_parent,
+            PathAnchor::Anywhere => continue,
+        };
+        parents.entry(path_parent.to_owned()).or_default().push(name.to_owned());
+    }
+    parents
+}
+
+/// Returns the default namespace of the element, or `None` if it has a prefix or there is no default namespace.
+fn default_namespace(el: &Element) -> Option<String> {
+    match el.prefix() {
+        Some(_) => None,
+        None => el.ns(),
+    }
+}
+
+/// Returns the property name of the element with the prefix of `NamespaceDefaultHandling::PrefixKeys`, if any.
+fn element_key(el: &Element, config: &Config) -> String {
+    match config.input.namespace_default_handling {
+        NamespaceDefaultHandling::PrefixKeys(ref prefix) if default_namespace(el).is_some() => {
+            [prefix.as_str(), el.name()].concat()
+        }
+        _ => el.name().to_owned(),
+    }
+}
+
+/// Adds the default namespace of the element to its object, see `NamespaceDefaultHandling::AddToElement`.
+fn inject_default_namespace(
+    el: &Element,
+    data: &mut Map<String, Value>,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    let key = match config.input.namespace_default_handling {
+        NamespaceDefaultHandling::AddToElement(ref key) => key,
+        _ => return Ok(()),
+    };
+    if let Some(ns) = default_namespace(el) {
+        let key = convert_key(key.clone(), config, ctx)?;
+        data.entry(key).or_insert(Value::String(ns));
+    }
+    Ok(())
+}
+
+/// Adds the identifier configured in `InjectionOptions::node_id` to the object created from the element.
+fn inject_node_id(
+    el: &Element,
+    data: &mut Map<String, Value>,
+    config: &Config,
+    ctx: &Context,
+) -> Result<(), ConversionError> {
+    let node_id = match config.injection.node_id {
+        Some(ref node_id) => node_id,
+        None => return Ok(()),
+    };
+
+    let id = match node_id.source {
+        NodeIdSource::Attr(ref attr) => match el.attr(attr) {
+            Some(id) => id.to_owned(),
+            None => return Ok(()),
+        },
+        NodeIdSource::SyntheticPath => ctx.id_path.clone(),
+    };
+
+    if data.contains_key(&node_id.prop_name) {
+        match node_id.on_clash {
+            NodeIdClash::Error => {
+                return Err(ConversionError::NodeIdClash {
+                    path: ctx.path.clone(),
+                })
+            }
+            NodeIdClash::KeepExisting => return Ok(()),
+            NodeIdClash::Overwrite => (),
+        }
+    }
+
+    data.insert(node_id.prop_name.clone(), Value::String(id));
+    Ok(())
+}
+
+/// Adds the properties of `InjectionOptions::injected_fields` matching the path of the element to its object.
+fn inject_fields(
+    data: &mut Map<String, Value>,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<(), ConversionError> {
+    if config.injection.injected_fields.is_empty() {
+        return Ok(());
+    }
+
+    for (path, key, value) in &config.injection.injected_fields {
+        if path.trim_start_matches('/') != ctx.path.trim_start_matches('/') {
+            continue;
+        }
+        if data.contains_key(key) {
+            match config.injection.injected_fields_on_clash {
+                NodeIdClash::Error => {
+                    return Err(ConversionError::InjectedFieldClash {
+                        path: ctx.path.clone(),
+                        key: key.clone(),
+                    })
+                }
+                NodeIdClash::KeepExisting => continue,
+                NodeIdClash::Overwrite => (),
+            }
+        }
+
+        let value = match value {
+            InjectedValue::Constant(v) => v.clone(),
+            InjectedValue::SourceName => hooks
+                .source_name
+                .map_or(Value::Null, |name| Value::String(name.to_owned())),
+            InjectedValue::TimestampRfc3339 => {
+                let timestamp = ctx.timestamp.get_or_insert_with(|| {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+                    rfc3339(now.map_or(0, |d| d.as_secs()))
+                });
+                Value::String(timestamp.clone())
+            }
+        };
+        data.insert(key.clone(), value);
+    }
+
+    Ok(())
+}
+
+/// Passes the object created from the element to `ValueInterceptor::on_object`.
+fn intercept_object(data: &mut Map<String, Value>, config: &Config, ctx: &Context) {
+    if let Some(ref interceptor) = config.injection.interceptor {
+        interceptor.on_object(&ctx.path, data);
+    }
+}
+
+/// Formats the number of seconds since the Unix epoch as RFC 3339 UTC time, e.g. `2024-05-01T12:30:00Z`.
+fn rfc3339(secs: u64) -> String {
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // the civil date from the number of days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Returns true if `Context::id_path` has to be maintained.
+fn synthetic_ids(config: &Config) -> bool {
+    match config.injection.node_id {
+        Some(ref node_id) => node_id.source == NodeIdSource::SyntheticPath,
+        None => false,
+    }
+}
+
+/// Converts an XML Element into a JSON property
+fn convert_node(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Option<Value>, ConversionError> {
+    hooks.check_canceled()?;
+    ctx.elements += 1;
+    if let Some(progress) = hooks.progress {
+        progress.report(ctx.elements as usize);
+    }
+
+    // add the current node to the path
+    let path_len = ctx.path.len();
+    ctx.path.push('/');
+    ctx.path.push_str(el.name());
+
+    // every element adds a step to the path, including the ancestors of the records of `xml_to_ndjson`
+    if let Some(max_depth) = config.input.max_xml_depth {
+        if ctx.path.matches('/').count() > max_depth {
+            return Err(ConversionError::DepthLimitExceeded);
+        }
+    }
+
+    let value = convert_element(el, config, ctx, hooks)?;
+    if !hooks.priority_paths.is_empty() {
+        mark_priority_found(el, ctx, hooks);
+    }
+
+    ctx.path.truncate(path_len);
+    Ok(value)
+}
+
+/// Records which of the priority paths of `xml_string_to_json_focused` the converted element provides.
+fn mark_priority_found(el: &Element, ctx: &mut Context, hooks: &Hooks) {
+    let path = ctx.path.trim_start_matches('/');
+    for (found, priority_path) in ctx.priority_found.iter_mut().zip(hooks.priority_paths) {
+        let priority_path = priority_path.trim_start_matches('/');
+        if priority_path == path {
+            *found = true;
+        } else if let Some(attr) = priority_path.strip_prefix(path).and_then(|rest| rest.strip_prefix("/@")) {
+            *found |= el.attr(attr).is_some();
+        }
+    }
+}
+
+/// Returns `true` if the path leads to one of the priority paths or is inside of one of them.
+fn on_priority_path(path: &str, priority_paths: &[&str]) -> bool {
+    let path = path.trim_start_matches('/');
+    priority_paths.iter().any(|priority_path| {
+        let priority_path = priority_path.trim_start_matches('/');
+        let is_prefix = |prefix: &str, path: &str| {
+            path.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+        is_prefix(path, priority_path) || is_prefix(priority_path, path)
+    })
+}
+
+/// Returns the child elements on the priority paths first, keeping the order of the siblings with the same name.
+fn focus_order<'a>(el: &'a Element, ctx: &Context, hooks: &Hooks) -> Vec<(usize, &'a Element)> {
+    let mut children: Vec<(bool, usize, &Element)> = el
+        .children()
+        .enumerate()
+        .map(|(position, child)| {
+            let path = [ctx.path.as_str(), "/", child.name()].concat();
+            (!on_priority_path(&path, hooks.priority_paths), position, child)
+        })
+        .collect();
+    children.sort_by_key(|(unfocused, _, _)| *unfocused);
+    children.into_iter().map(|(_, position, child)| (position, child)).collect()
+}
+
+/// Does the actual conversion for `convert_node` once the element was added to the path.
+fn convert_element(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Option<Value>, ConversionError> {
+    // attribute-only elements with a tuple rule don't need a map
+    #[cfg(feature = "json_types")]
+    if let Some(attrs) = find_by_path(&config.tuple_rules, &ctx.path, &config.path_anchor) {
+        if let Some(tuple) = convert_tuple(el, attrs, config, ctx, hooks)? {
+            return Ok(Some(tuple));
+        }
+        ctx.tuple_mismatch_paths.push(ctx.path.clone());
+    }
+
+    if config.arrays.auto_flatten_plural_wrappers {
+        if let Some(items) = flatten_plural_wrapper(el, config, ctx, hooks)? {
+            return Ok(Some(items));
+        }
+    }
+
+    if config.text.ordered_content && el.children().next().is_some() {
+        return convert_ordered_content(el, config, ctx, hooks).map(Some);
+    }
+
+    // get the json_type for this node
+    let (_, json_type_value) = get_json_type(config, hooks, &ctx.path);
+
+    let (text, cdata) = join_text(el, config, ctx);
+    // the whitespace around the text of elements with child elements is usually indentation
+    let text = if config.text.trim_text || el.children().next().is_some() {
+        trim_around_cdata(&text, cdata)
+    } else {
+        &*text
+    };
+    // an empty CDATA section is an empty string
+    let has_text = !text.is_empty() || has_cdata(el, config, ctx);
+    let mixed_content = config.profile.keeps_mixed_content() && el.children().next().is_some();
+
+    // is it an element with text?
+    if has_text && !mixed_content {
+        let (text, segmented) = convert_element_text(el, text, json_type_value, config, ctx)?;
+
+        let comments = convert_comments(el, config, ctx)?;
+
+        // process node's attributes and comments, if present
+        let mut data = convert_attributes(el, config, ctx, hooks)?;
+        if !data.is_empty() || !comments.is_empty() || segmented {
+            data.extend(comments);
+            let key = convert_key(config.xml_text_node_prop_name.clone(), config, ctx)?;
+            data.insert(key, text);
+            apply_dual_emit(el, &mut data, config, ctx)?;
+            #[cfg(feature = "json_types")]
+            apply_empty_arrays(&mut data, config, ctx)?;
+            apply_absent_fields(&mut data, config, ctx)?;
+            inject_node_id(el, &mut data, config, ctx)?;
+            inject_default_namespace(el, &mut data, config, ctx)?;
+            inject_fields(&mut data, config, ctx, hooks)?;
+            intercept_object(&mut data, config, ctx);
+            Ok(Some(Value::Object(data)))
+        } else {
+            Ok(Some(text))
+        }
+    } else {
+        // this element has no text or has both text and child elements, but may have other child nodes
+        let mut data = convert_attributes(el, config, ctx, hooks)?;
+        data.extend(convert_comments(el, config, ctx)?);
+        // the property with the text, which child elements must not be merged with
+        let mut text_key = None;
+        if has_text {
+            let (value, _) = convert_element_text(el, text, json_type_value, config, ctx)?;
+            let key = convert_key(config.xml_text_node_prop_name.clone(), config, ctx)?;
+            data.insert(key.clone(), value);
+            text_key = Some(key);
+        }
+        let path_len = ctx.path.len();
+
+        // sibling counters for synthetic node ids
+        let synthetic_ids = synthetic_ids(config);
+        let mut sibling_counts: HashMap<&str, usize> = HashMap::new();
+        let id_path_len = ctx.id_path.len();
+
+        // properties turned into arrays of child elements with the element names
+        let mut arrays: Vec<(String, &str)> = Vec::new();
+
+        // the number of child elements with the same name for `ArrayOptions::force_array_above_child_count`
+        let mut child_counts: HashMap<&str, usize> = HashMap::new();
+        if config.arrays.force_array_above_child_count.is_some() {
+            for child in el.children() {
+                *child_counts.entry(child.name()).or_insert(0) += 1;
+            }
+        }
+
+        let tails = match config.text.tail_text_handling {
+            TailTextHandling::AsField(_) => tail_texts(el),
+            _ => Vec::new(),
+        };
+
+        // the children on the priority paths of `xml_string_to_json_focused` go first
+        let mut all_children = el.children().enumerate();
+        let mut focused_children;
+        let children: &mut dyn Iterator<Item = (usize, &Element)> = if hooks.priority_paths.is_empty() {
+            &mut all_children
+        } else {
+            focused_children = focus_order(el, ctx, hooks).into_iter();
+            &mut focused_children
+        };
+
+        // process child element recursively
+        for (position, child) in children {
+            // the rest of the document is skipped once all priority paths were found
+            if config.output.stop_after_priority_paths
+                && !hooks.priority_paths.is_empty()
+                && ctx.priority_found.iter().all(|found| *found)
+                && !on_priority_path(&[ctx.path.as_str(), "/", child.name()].concat(), hooks.priority_paths)
+            {
+                continue;
+            }
+
+            if synthetic_ids {
+                let count = sibling_counts.entry(child.name()).or_insert(0);
+                ctx.id_path.truncate(id_path_len);
+                ctx.id_path.push_str(&format!("/{}[{}]", child.name(), count));
+                *count += 1;
+            }
+
+            match convert_node(child, config, ctx, hooks)? {
+                Some(mut val) => {
+                    if let TailTextHandling::AsField(ref field) = config.text.tail_text_handling {
+                        let tail = tails[position].trim();
+                        if !tail.is_empty() {
+                            val = add_tail_text(val, tail, field, config, ctx)?;
+                        }
+                    }
+
+                    if config.injection.preserve_child_order {
+                        if let Value::Object(ref mut obj) = val {
+                            obj.insert(config.injection.child_order_key.clone(), Value::Number(position.into()));
+                        }
+                    }
+
+                    ctx.path.push('/');
+                    ctx.path.push_str(child.name());
+                    let mut name = convert_key(element_key(child, config), config, ctx)?;
+                    if text_key.as_ref() == Some(&name) {
+                        match config.text.text_prop_collision {
+                            TextPropCollision::Suffix(ref suffix) => name.push_str(suffix),
+                            TextPropCollision::Error => {
+                                return Err(ConversionError::TextPropCollision {
+                                    path: ctx.path.clone(),
+                                })
+                            }
+                        }
+                    }
+                    let (json_type_array, _) = get_json_type(config, hooks, &ctx.path);
+                    let json_type_array = json_type_array
+                        || config.arrays.forced_array_paths.contains(&ctx.path)
+                        || (config.arrays.always_array_elements.contains(child.name())
+                            && !has_path_override(config, &ctx.path))
+                        || config
+                            .arrays
+                            .force_array_above_child_count
+                            .is_some_and(|limit| child_counts.get(child.name()).is_some_and(|n| *n > limit));
+                    // tuples are arrays themselves and can't be told apart from a list of values
+                    #[cfg(feature = "json_types")]
+                    let json_type_array = json_type_array
+                        || find_by_path(&config.tuple_rules, &ctx.path, &config.path_anchor).is_some();
+                    ctx.path.truncate(path_len);
+
+                    // objects of repeated elements may be merged instead of becoming an array
+                    let merge = config.arrays.merge_same_name_objects
+                        && !json_type_array
+                        && val.is_object()
+                        && data.get(&name).is_some_and(Value::is_object);
+
+                    if merge {
+                        if let (Some(Value::Object(existing)), Value::Object(new)) = (data.get_mut(&name), val) {
+                            merge_objects(existing, new);
+                        }
+                    } else if json_type_array || data.contains_key(&name) {
+                        if config.diagnostics.array_homogeneity_check && !arrays.iter().any(|(n, _)| *n == name) {
+                            arrays.push((name.clone(), child.name()));
+                        }
+                        // arrays moved to temporary files by `OutputOptions::spill` are continued there
+                        #[cfg(feature = "spill")]
+                        if let Some(spilled) = data.get(&name) {
+                            if spill::append_spilled(spilled, &val, ctx)? {
+                                continue;
+                            }
+                        }
+                        // was this property converted to an array earlier?
+                        if data.get(&name).unwrap_or(&Value::Null).is_array() {
+                            // add the new value to an existing array
+                            let items = data.get_mut(&name).unwrap();
+                            items.as_array_mut().unwrap().push(val);
+                            #[cfg(feature = "spill")]
+                            spill::spill_array(items, child.name(), config, ctx)?;
+                        } else {
+                            // convert the property to an array with the existing and the new values
+                            let new_val = match data.remove(&name) {
+                                None => vec![val],
+                                Some(temp) => vec![temp, val],
+                            };
+                            #[allow(unused_mut)]
+                            let mut new_val = Value::Array(new_val);
+                            #[cfg(feature = "spill")]
+                            spill::spill_array(&mut new_val, child.name(), config, ctx)?;
+                            data.insert(name, new_val);
+                        }
+                    } else {
+                        // this is the first time this property is encountered and it doesn't
+                        // have to be an array, so add it as-is
+                        data.insert(name, val);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        ctx.id_path.truncate(id_path_len);
+
+        for (name, child_name) in arrays {
+            if let Some(Value::Array(items)) = data.get(&name) {
+                let differing_keys = differing_keys(items);
+                if !differing_keys.is_empty() {
+                    let path = format!("{}/{}", ctx.path, child_name);
+                    ctx.heterogeneous_arrays.push((path, differing_keys));
+                }
+            }
+        }
+
+        apply_dual_emit(el, &mut data, config, ctx)?;
+        #[cfg(feature = "json_types")]
+        apply_empty_arrays(&mut data, config, ctx)?;
+        apply_absent_fields(&mut data, config, ctx)?;
+
+        // return the JSON object if it's not empty
+        if !data.is_empty() {
+            inject_node_id(el, &mut data, config, ctx)?;
+            inject_default_namespace(el, &mut data, config, ctx)?;
+            inject_fields(&mut data, config, ctx, hooks)?;
+            intercept_object(&mut data, config, ctx);
+            return Ok(Some(Value::Object(data)));
+        }
+
+        // empty objects are treated according to config rules set by the caller
+        match config.empty_element_handling {
+            NullValue::Null => Ok(Some(Value::Null)),
+            NullValue::EmptyString => Ok(Some(Value::String(String::new()))),
+            NullValue::EmptyObject => {
+                inject_node_id(el, &mut data, config, ctx)?;
+                inject_default_namespace(el, &mut data, config, ctx)?;
+                inject_fields(&mut data, config, ctx, hooks)?;
+                intercept_object(&mut data, config, ctx);
+                Ok(Some(Value::Object(data)))
+            }
+            NullValue::Ignore => Ok(None),
+        }
+    }
+}
+
+/// Converts the element into its attributes and the array of its text runs and child elements,
+/// see `TextOptions::ordered_content`.
+fn convert_ordered_content(
+    el: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Value, ConversionError> {
+    let mut data = convert_attributes(el, config, ctx, hooks)?;
+    data.extend(convert_comments(el, config, ctx)?);
+
+    let synthetic_ids = synthetic_ids(config);
+    let mut sibling_counts: HashMap<&str, usize> = HashMap::new();
+    let id_path_len = ctx.id_path.len();
+    let path_len = ctx.path.len();
+
+    let mut content = Value::Array(Vec::new());
+    for node in el.nodes() {
+        match node {
+            Node::Element(child) => {
+                if synthetic_ids {
+                    let count = sibling_counts.entry(child.name()).or_insert(0);
+                    ctx.id_path.truncate(id_path_len);
+                    ctx.id_path.push_str(&format!("/{}[{}]", child.name(), count));
+                    *count += 1;
+                }
+                if let Some(value) = convert_node(child, config, ctx, hooks)? {
+                    ctx.path.push('/');
+                    ctx.path.push_str(child.name());
+                    let name = convert_key(element_key(child, config), config, ctx)?;
+                    ctx.path.truncate(path_len);
+                    let mut item = Map::new();
+                    item.insert(name, value);
+                    push_member(&mut content, Value::Object(item), &config.text.content_key, config, ctx)?;
+                }
+            }
+            Node::Text(text) if !text.trim().is_empty() => {
+                let text = Value::String(apply_string_policy(text.clone(), config, ctx)?);
+                push_member(&mut content, text, &config.text.content_key, config, ctx)?;
+            }
+            _ => (),
+        }
+    }
+    ctx.id_path.truncate(id_path_len);
+
+    let key = convert_key(config.text.content_key.clone(), config, ctx)?;
+    data.insert(key, content);
+    apply_dual_emit(el, &mut data, config, ctx)?;
+    apply_absent_fields(&mut data, config, ctx)?;
+    inject_node_id(el, &mut data, config, ctx)?;
+    inject_default_namespace(el, &mut data, config, ctx)?;
+    inject_fields(&mut data, config, ctx, hooks)?;
+    intercept_object(&mut data, config, ctx);
+    Ok(Value::Object(data))
+}
+
+/// Adds the member to an array built by the converter, which is moved to a temporary file once it gets too large,
+/// see `OutputOptions::spill`. `name` is the name of the members in the XML path of `ConversionError::SpillRequired`.
+#[cfg_attr(not(feature = "spill"), allow(unused_variables))]
+fn push_member(
+    items: &mut Value,
+    member: Value,
+    name: &str,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    #[cfg(feature = "spill")]
+    {
+        if spill::append_spilled(items, &member, ctx)? {
+            return Ok(());
+        }
+    }
+    if let Value::Array(ref mut members) = *items {
+        members.push(member);
+    }
+    #[cfg(feature = "spill")]
+    spill::spill_array(items, name, config, ctx)?;
+    Ok(())
+}
+
+/// Adds the properties of `new` to `existing`, see `ArrayOptions::merge_same_name_objects`.
+/// Properties holding objects in both are merged recursively, other values of `new` replace the existing ones.
+fn merge_objects(existing: &mut Map<String, Value>, new: Map<String, Value>) {
+    for (key, value) in new {
+        match (existing.get_mut(&key), value) {
+            (Some(Value::Object(existing)), Value::Object(new)) => merge_objects(existing, new),
+            (_, value) => {
+                existing.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns the sorted property names missing from some of the objects in the array, see
+/// `DiagnosticOptions::array_homogeneity_check`. Other values have no properties.
+fn differing_keys(items: &[Value]) -> Vec<String> {
+    let keys = |item: &Value| -> BTreeSet<String> {
+        match item {
+            Value::Object(map) => map.keys().cloned().collect(),
+            _ => BTreeSet::new(),
+        }
+    };
+
+    let mut union = BTreeSet::new();
+    let mut intersection: Option<BTreeSet<String>> = None;
+    for item in items {
+        let item_keys = keys(item);
+        union.extend(item_keys.iter().cloned());
+        intersection = Some(match intersection {
+            None => item_keys,
+            Some(common) => common.intersection(&item_keys).cloned().collect(),
+        });
+    }
+    let common = intersection.unwrap_or_default();
+    union.into_iter().filter(|key| !common.contains(key)).collect()
+}
+
+fn xml_to_map(
+    e: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+) -> Result<Value, ConversionError> {
+    let mut data = Map::new();
+    xml_into_map(e, config, ctx, hooks, &mut data)?;
+    Ok(Value::Object(data))
+}
+
+/// Same as `xml_to_map`, but adds the output to the given map.
+fn xml_into_map(
+    e: &Element,
+    config: &Config,
+    ctx: &mut Context,
+    hooks: &Hooks,
+    data: &mut Map<String, Value>,
+) -> Result<(), ConversionError> {
+    if let Some(side_table) = hooks.side_table {
+        ctx.marks = side_table.resolve(e);
+    }
+    ctx.path.push('/');
+    ctx.path.push_str(e.name());
+    let name = convert_key(element_key(e, config), config, ctx)?;
+    ctx.path.clear();
+    if synthetic_ids(config) {
+        ctx.id_path.push('/');
+        ctx.id_path.push_str(e.name());
+    }
+    if config.attributes.promote_root_attrs {
+        ctx.root_attrs = Some(Map::new());
+    }
+    ctx.priority_found.resize(hooks.priority_paths.len(), false);
+    // the root element can't be left out, so an empty one with `NullValue::Ignore` becomes `null`
+    let mut value = match convert_node(&e, &config, ctx, hooks)? {
+        Some(value) => value,
+        None => Value::Null,
+    };
+    if let Some(context) = hooks.context {
+        merge_context(&mut value, context, config);
+    }
+    if let Some(root_attrs) = ctx.root_attrs.take() {
+        data.extend(root_attrs);
+    }
+    data.insert(name, value);
+    if let Some((key, comments)) = comments_property(hooks.prolog_comments.iter().copied(), config, ctx)? {
+        data.insert(key, comments);
+    }
+    if let Some(ref key) = config.text.processing_instructions_key {
+        let instructions = hooks.prolog_instructions.iter().copied();
+        if let Some(instructions) = processing_instructions_value(instructions, config, ctx)? {
+            data.insert(convert_key(key.clone(), config, ctx)?, instructions);
+        }
+    }
+
+    // the options working on the entire output take a `Value`, the map is moved back afterwards
+    let mut value = Value::Object(std::mem::take(data));
+    finish_output(&mut value, config);
+    if let Value::Object(map) = value {
+        *data = map;
+    }
+    if let Some(key) = &config.injection.embed_config {
+        data.entry(key.clone()).or_insert_with(|| provenance::embedded_config(config));
+    }
+    Ok(())
+}
+
+/// Adds the context properties to the objects selected by `InjectionOptions::context_placement`.
+/// Properties converted from the document are never replaced, and scalar values are left as-is.
+fn merge_context(root: &mut Value, context: &HashMap<String, Value>, config: &Config) {
+    let merge = |value: &mut Value| {
+        if let Value::Object(map) = value {
+            for (key, context_value) in context {
+                map.entry(key.clone()).or_insert_with(|| context_value.clone());
+            }
+        }
+    };
+
+    match config.injection.context_placement {
+        ContextPlacement::Root => merge(root),
+        ContextPlacement::Children => {
+            if let Value::Object(map) = root {
+                for child in map.values_mut() {
+                    match child {
+                        Value::Array(items) => items.iter_mut().for_each(merge),
+                        _ => merge(child),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Applies the options that work on the entire output of a conversion.
+fn finish_output(value: &mut Value, config: &Config) {
+    limit_json_depth(value, config);
+    if config.output.emit_type_annotations {
+        // the top-level object holds the name of the root element, not a value
+        if let Value::Object(map) = value {
+            map.values_mut().for_each(annotate_type);
+        }
+    }
+}
+
+/// Replaces the value and everything nested in it with `{"$val":value,"$t":"type"}`,
+/// see `OutputOptions::emit_type_annotations`.
+fn annotate_type(value: &mut Value) {
+    let json_type = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_u64() => "u64",
+        Value::Number(n) if n.is_i64() => "i64",
+        Value::Number(_) => "f64",
+        Value::String(_) => "string",
+        Value::Object(map) => {
+            map.values_mut().for_each(annotate_type);
+            "object"
+        }
+        Value::Array(items) => {
+            items.iter_mut().for_each(annotate_type);
+            "array"
+        }
+    };
+
+    let mut annotated = Map::new();
+    annotated.insert("$val".to_owned(), value.take());
+    annotated.insert("$t".to_owned(), Value::String(json_type.to_owned()));
+    *value = Value::Object(annotated);
+}
+
+/// Replaces the values nested deeper than `OutputOptions::max_json_depth` with `OutputOptions::json_depth_sentinel`.
+fn limit_json_depth(value: &mut Value, config: &Config) {
+    /// Replaces the children of the value if there are no levels left for them.
+    fn truncate(value: &mut Value, levels_left: usize, config: &Config) {
+        let replace = |child: &mut Value| {
+            if levels_left == 0 {
+                *child = config.output.json_depth_sentinel.clone();
+            } else {
+                truncate(child, levels_left - 1, config);
+            }
+        };
+        match value {
+            Value::Object(map) => map.values_mut().for_each(replace),
+            Value::Array(items) => items.iter_mut().for_each(replace),
+            _ => (),
+        }
+    }
+
+    // the top-level value is at level 1, so it's never replaced
+    if let Some(max_depth) = config.output.max_json_depth {
+        truncate(value, max_depth.saturating_sub(1), config);
+    }
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct.
+pub fn xml_str_to_json(xml: &str, config: &Config) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str(xml)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct.
+/// Accepts any string type, e.g. `&str`, `String`, `Arc<str>` or `Cow<str>`, so callers don't have to
+/// allocate a `String` for it.
+pub fn xml_string_to_json<S: AsRef<str>>(xml: S, config: &Config) -> Result<Value, ConversionError> {
+    xml_str_to_json(xml.as_ref(), config)
+}
+
+/// Converts the given XML string into the JSON text wrapped in a `RawValue`, e.g. for embedding the converted
+/// document into another serialized struct. The text is the same as `serde_json::to_string` of
+/// `xml_string_to_json` gives and is written as is when the struct is serialized. Requires `raw_value` feature.
+#[cfg(feature = "raw_value")]
+pub fn xml_string_to_raw_json(
+    xml: String,
+    config: &Config,
+) -> Result<Box<serde_json::value::RawValue>, ConversionError> {
+    let value = xml_str_to_json(&xml, config)?;
+    serde_json::value::to_raw_value(&value).map_err(|e| ConversionError::SerializationError(e.to_string()))
+}
+
+/// Converts the given XML string into the bytes of the JSON text using settings from `Config` struct,
+/// e.g. for a response body. The output is the same as `serde_json::to_vec` of `xml_string_to_json`.
+/// The value is still built first, because many options work on the entire output, e.g.
+/// `OutputOptions::max_json_depth`, but it's written into a buffer sized for the document at once and dropped right
+/// away.
+pub fn xml_string_to_json_bytes(xml: String, config: &Config) -> Result<Vec<u8>, ConversionError> {
+    let value = xml_str_to_json(&xml, config)?;
+    let mut bytes = Vec::with_capacity(xml.len());
+    drop(xml);
+    serde_json::to_writer(&mut bytes, &value).map_err(|e| ConversionError::SerializationError(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Reads the XML document from the reader, e.g. a file, a socket or a decompressor, and converts it
+/// into `serde::Value` using settings from `Config` struct. The input must be UTF-8.
+/// Use `Converter::convert_reader` to reuse the buffers for many documents.
+pub fn xml_reader_to_json<R: std::io::Read>(reader: R, config: &Config) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_reader(reader)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and
+/// a list of XML paths with their JSON types for this call only, e.g. built dynamically for every document.
+/// They take precedence over all overrides from the config, including regex paths, and must match the path
+/// exactly, with the leading `/` being optional. The config is not cloned or modified.
+/// Requires `json_types` feature.
+#[cfg(feature = "json_types")]
+pub fn xml_string_to_json_with_overrides(
+    xml: String,
+    base_config: &Config,
+    overrides: &[(String, JsonType)],
+) -> Result<Value, ConversionError> {
+    Converter::new(base_config).convert_str_with_overrides(&xml, overrides)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and the parser,
+/// e.g. `xml_string_to_json_with_parser::<QuickXmlParser>(xml, &config)`. All parsers produce the same output
+/// for well-formed documents, but may report different errors for malformed ones.
+pub fn xml_string_to_json_with_parser<P: XmlParser>(
+    xml: String,
+    config: &Config,
+) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str_with_parser::<P>(&xml)
+}
+
+/// Converts an already parsed `minidom` element into `serde::Value` using settings from `Config` struct.
+/// Use `quickxml_to_serde::minidom` re-export to make sure the versions of `minidom` match.
+/// Requires `minidom_interop` feature.
+#[cfg(feature = "minidom_interop")]
+pub fn element_to_json(el: &minidom::Element, config: &Config) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_element(el)
+}
+
+/// Same as `xml_str_to_json`, but the name of the source, e.g. the file name, is used for the
+/// `InjectedValue::SourceName` fields of `InjectionOptions::injected_fields`.
+pub fn xml_str_to_json_with_source(
+    xml: &str,
+    config: &Config,
+    source_name: &str,
+) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str_with_source(xml, source_name)
+}
+
+/// Same as `xml_str_to_json`, but also returns the list of non-fatal issues found during the conversion,
+/// e.g. values affected by `ValueOptions::json_string_policy`.
+pub fn xml_str_to_json_with_warnings(
+    xml: &str,
+    config: &Config,
+) -> Result<(Value, Vec<Warning>), ConversionError> {
+    Converter::new(config).convert_str_with_warnings(xml)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and calls `progress_fn`
+/// with the number of converted elements and the estimated number of elements in the document,
+/// about a hundred times for large documents. The estimate comes from counting the start tags before
+/// the conversion. The last call is made after the conversion with both numbers being the number of
+/// converted elements, e.g. `(1200, 1200)`.
+pub fn xml_string_to_json_with_progress<F: Fn(usize, usize)>(
+    xml: String,
+    config: &Config,
+    progress_fn: F,
+) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str_with_progress(&xml, &progress_fn)
+}
+
+/// Converts the given XML string using settings from `Config` struct and adds the output to the given map
+/// instead of returning a new `serde::Value`, e.g. to reuse the allocation of the map for many documents.
+/// The properties already in the map are kept unless the output replaces them, so the caller is responsible
+/// for clearing the map before reusing it. The map is left unchanged if the conversion fails.
+pub fn xml_string_into_map(xml: String, config: &Config, map: &mut Map<String, Value>) -> Result<(), ConversionError> {
+    Converter::new(config).convert_str_into_map(&xml, map)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct, converting the elements
+/// on the priority paths, e.g. `/order/@id` or `/order/total`, before their siblings. The output is the same as
+/// the output of `xml_string_to_json`, unless `OutputOptions::stop_after_priority_paths` is set to skip the rest of
+/// the document once all priority paths were found. Paths not in the document never stop the conversion.
+pub fn xml_string_to_json_focused(
+    xml: String,
+    config: &Config,
+    priority_paths: &[&str],
+) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str_focused(&xml, priority_paths)
+}
+
+/// Same as `xml_str_to_json`, but also returns the version, encoding and standalone declaration
+/// of the document, e.g. to refuse `standalone="no"` documents which rely on an external DTD.
+pub fn xml_str_to_json_with_prolog(
+    xml: &str,
+    config: &Config,
+) -> Result<(Value, Prolog), ConversionError> {
+    Converter::new(config).convert_str_with_prolog(xml)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and adds
+/// the `context` properties, e.g. request-scoped trace or user ids, to the object of the root element
+/// or to the objects of its children, see `InjectionOptions::context_placement`. Properties converted from
+/// the document take precedence over the context properties with the same name.
+pub fn xml_string_to_json_with_context(
+    xml: String,
+    config: &Config,
+    context: HashMap<String, Value>,
+) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str_with_context(&xml, &context)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct.
+/// The `cancel` flag is checked before converting each element. Setting it from another thread
+/// stops the conversion with `ConversionError::Canceled`.
+pub fn xml_string_to_json_cancelable(
+    xml: String,
+    config: &Config,
+    cancel: &AtomicBool,
+) -> Result<Value, ConversionError> {
+    Converter::new(config).convert_str_cancelable(&xml, cancel)
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct.
+/// References to entities other than the five predefined ones (`&amp;`, `&lt;`, ...) are replaced with
+/// the text returned by `resolver` before the document is parsed. This allows converting documents that
+/// rely on entities from an external DTD. E.g. `<a>&company;</a>` becomes `{"a":"ACME"}` if the resolver
+/// returns `Some("ACME".to_owned())` for `company`. Returning `None` fails the conversion with
+/// `ConversionError::UnknownEntity`. Entity references inside CDATA sections, comments and the DOCTYPE
+/// declaration are left untouched.
+pub fn xml_string_to_json_with_entity_resolver<F>(
+    xml: String,
+    config: &Config,
+    resolver: F,
+) -> Result<Value, ConversionError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let xml = resolve_entities(&xml, resolver)?;
+    xml_str_to_json(&xml, config)
+}
+
+/// Entities known to any XML parser, they are left for the parser to handle.
+const PREDEFINED_ENTITIES: [&str; 5] = ["amp", "lt", "gt", "quot", "apos"];
+
+/// Substitutes entity references with the values returned by `resolver`.
+/// Character references like `&#160;` and the predefined entities are copied as-is.
+fn resolve_entities<F>(xml: &str, resolver: F) -> Result<String, ConversionError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut resolved = String::with_capacity(xml.len());
+    let mut rest = xml;
+
+    while let Some(pos) = rest.find(['&', '<']) {
+        resolved.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        // sections where entity references are not expanded are copied verbatim
+        let verbatim_end = if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|end| end + 3)
+        } else if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if rest.starts_with("<!DOCTYPE") {
+            // the internal subset ends with `]>`, otherwise the declaration ends with the first `>`
+            match (rest.find('['), rest.find('>')) {
+                (Some(open), Some(close)) if open < close => rest.find("]>").map(|end| end + 2),
+                (_, close) => close.map(|end| end + 1),
+            }
+        } else if rest.starts_with('<') {
+            Some(1)
+        } else {
+            None
+        };
+
+        if let Some(end) = verbatim_end {
+            resolved.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        // it's an `&` - check if it starts a named entity reference
+        let name = rest[1..].find(';').map(|end| &rest[1..end + 1]).filter(|name| {
+            !name.is_empty()
+                && !PREDEFINED_ENTITIES.contains(name)
+                && !name.starts_with('#')
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':')
+        });
+
+        match name {
+            Some(name) => {
+                match resolver(name) {
+                    Some(text) => resolved.push_str(&text),
+                    None => {
+                        return Err(ConversionError::UnknownEntity {
+                            name: name.to_owned(),
+                        })
+                    }
+                }
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                resolved.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/// Looks up the value for the node path in a map of paths. With `PathAnchor::Anywhere` the trailing
+/// segments of the path are tried from the longest to the shortest if there is no exact match.
+#[cfg(feature = "json_types")]
+#[inline]
+fn find_by_path<'conf, T>(map: &'conf HashMap<String, T>, path: &str, anchor: &PathAnchor) -> Option<&'conf T> {
+    if map.is_empty() {
+        return None;
+    }
+    if let Some(v) = map.get(path) {
+        return Some(v);
+    }
+    if anchor == &PathAnchor::Root {
+        return None;
+    }
+
+    path.char_indices()
+        .skip(1)
+        .filter(|(_, c)| *c == '/')
+        .find_map(|(i, _)| map.get(&path[i..]))
+}
+
+/// Returns the override of the first wildcard path matching the node path, see
+/// `Config::json_type_wildcard_overrides`. With `PathAnchor::Anywhere` the path may also match
+/// the trailing steps of the node path.
+#[cfg(feature = "json_types")]
+#[inline]
+fn find_by_wildcard<'conf>(
+    overrides: &'conf [(String, JsonArray)],
+    path: &str,
+    anchor: &PathAnchor,
+) -> Option<&'conf JsonArray> {
+    let matches = |pattern: &str| {
+        let mut steps = path.trim_start_matches('/').rsplit('/');
+        for pattern_step in pattern.trim_start_matches('/').rsplit('/') {
+            match steps.next() {
+                Some(step) if step == pattern_step || (pattern_step == "*" && !step.starts_with('@')) => (),
+                _ => return false,
+            }
+        }
+        anchor == &PathAnchor::Anywhere || steps.next().is_none()
+    };
+    overrides
+        .iter()
+        .find(|(pattern, _)| matches(pattern))
+        .map(|(_, json_array)| json_array)
+}
+
+/// Returns `true` if the path has an override in `json_type_overrides` or `json_regex_type_overrides`.
+#[cfg(feature = "json_types")]
+fn has_path_override(config: &Config, path: &str) -> bool {
+    #[cfg(feature = "regex_path")]
+    if config.json_regex_type_overrides.iter().any(|(regex, _)| regex.is_match(path)) {
+        return true;
+    }
+    find_by_path(&config.json_type_overrides, path, &config.path_anchor).is_some()
+        || find_by_wildcard(&config.json_type_wildcard_overrides, path, &config.path_anchor).is_some()
+}
+
+/// Always returns `false` if `json_types` feature is not enabled.
+#[cfg(not(feature = "json_types"))]
+fn has_path_override(_config: &Config, _path: &str) -> bool {
+    false
+}
+
+/// Returns a tuple for Array and Value enforcements for the current node or
+/// `(false, JsonArray::Infer(JsonType::Infer)` if the current path is not found
+/// in the list of paths with custom config.
+#[cfg(feature = "json_types")]
+#[inline]
+fn get_json_type_with_absolute_path<'conf>(config: &'conf Config, path: &String) -> (bool, &'conf JsonType) {
+    match find_by_path(&config.json_type_overrides, path, &config.path_anchor)
+    .or_else(|| find_by_wildcard(&config.json_type_wildcard_overrides, path, &config.path_anchor))
+    .unwrap_or(&JsonArray::Infer(JsonType::Infer))
+    {
+        JsonArray::Infer(v) => (false, v),
+        JsonArray::Always(v) => (true, v),
+    }
+}
+
+/// Simply returns `get_json_type_with_absolute_path` if `regex_path` feature is disabled.
+#[cfg(feature = "json_types")]
+#[cfg(not(feature = "regex_path"))]
+#[inline]
+fn get_json_type<'conf>(config: &'conf Config, hooks: &Hooks<'conf>, path: &String) -> (bool, &'conf JsonType) {
+    if let Some(json_type) = find_hook_override(hooks, path) {
+        return (false, json_type);
+    }
+    if !may_have_override(hooks, path) {
+        return (false, &JsonType::Infer);
+    }
+
+    get_json_type_with_absolute_path(config, path)
+}
+
+/// Returns a tuple for Array and Value enforcements for the current node. Searches both absolute paths
+/// and regex paths, giving precedence to regex paths. Returns `(false, JsonArray::Infer(JsonType::Infer)`
+/// if the current path is not found in the list of paths with custom config.
+#[cfg(feature = "json_types")]
+#[cfg(feature = "regex_path")]
+#[inline]
+fn get_json_type<'conf>(config: &'conf Config, hooks: &Hooks<'conf>, path: &String) -> (bool, &'conf JsonType) {
+    if let Some(json_type) = find_hook_override(hooks, path) {
+        return (false, json_type);
+    }
+
+    for (regex, json_array) in &config.json_regex_type_overrides {
+        if regex.is_match(path) {
+            return match json_array {
+                JsonArray::Infer(v) => (false, v),
+                JsonArray::Always(v) => (true, v),
+            };
+        }
+    }
+    if !may_have_override(hooks, path) {
+        return (false, &JsonType::Infer);
+    }
+
+    get_json_type_with_absolute_path(config, path)
+}
+
+/// Always returns `(false, JsonArray::Infer(JsonType::Infer)` if `json_types` feature is not enabled.
+#[cfg(not(feature = "json_types"))]
+#[inline]
+fn get_json_type<'conf>(_config: &'conf Config, _hooks: &Hooks, _path: &String) -> (bool, &'conf JsonType) {
+    (false, &JsonType::Infer)
+}
+
+/// Returns `false` if the last step of the path doesn't end any override path of the `CompiledConfig`.
+#[cfg(feature = "json_types")]
+#[inline]
+fn may_have_override(hooks: &Hooks, path: &str) -> bool {
+    match hooks.override_names {
+        Some(names) => path
+            .rsplit('/')
+            .next()
+            .is_some_and(|name| names.contains(name) || (!name.starts_with('@') && names.contains("*"))),
+        None => true,
+    }
+}
+
+/// Returns the JSON type of the per-call override for the path. The leading slash is optional.
+#[cfg(feature = "json_types")]
+#[inline]
+fn find_hook_override<'a>(hooks: &Hooks<'a>, path: &str) -> Option<&'a JsonType> {
+    let path = path.trim_start_matches('/');
+    hooks
+        .overrides
+        .iter()
+        .find(|(p, _)| p.trim_start_matches('/') == path)
+        .map(|(_, json_type)| json_type)
 }
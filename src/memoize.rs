@@ -0,0 +1,76 @@
+//! Caching of the output for documents converted many times, e.g. the same XML snippet in a pipeline.
+//! Requires `memoize` feature.
+
+use ahash::AHasher;
+use dashmap::DashMap;
+use serde_json::Value;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use super::provenance::embedded_config;
+use super::{xml_str_to_json, Config, ConversionError, InjectedValue};
+
+/// The output of `xml_string_to_json_memoized` by the hash of the document and the config.
+/// The cache can be shared between threads. Nothing is evicted, so use `clear` to limit its size.
+#[derive(Default)]
+pub struct XmlToJsonCache(DashMap<u64, Arc<Value>>);
+
+impl XmlToJsonCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        XmlToJsonCache::default()
+    }
+
+    /// Returns the number of cached documents.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if nothing was cached yet.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Removes all cached documents.
+    pub fn clear(&self) {
+        self.0.clear()
+    }
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct, or returns the value
+/// cached for the same document and config. Configs with functions, e.g. `InjectionOptions::interceptor`,
+/// or with `InjectedValue::TimestampRfc3339` fields may give a different output every time,
+/// so their documents are converted without the cache.
+///
+/// The cache key is a 64-bit hash of the document and the options written by `InjectionOptions::embed_config`.
+/// Errors are not cached.
+pub fn xml_string_to_json_memoized(
+    xml: String,
+    config: &Config,
+    cache: &XmlToJsonCache,
+) -> Result<Arc<Value>, ConversionError> {
+    let record = embedded_config(config);
+    let has_functions = record["elided"]
+        .as_array()
+        .is_some_and(|elided| elided.iter().any(|name| name != "progress"));
+    let has_timestamps = config
+        .injection
+        .injected_fields
+        .iter()
+        .any(|(_, _, value)| *value == InjectedValue::TimestampRfc3339);
+    if has_functions || has_timestamps {
+        return xml_str_to_json(&xml, config).map(Arc::new);
+    }
+
+    let mut hasher = AHasher::default();
+    xml.hash(&mut hasher);
+    record.to_string().hash(&mut hasher);
+    let key = hasher.finish();
+
+    if let Some(value) = cache.0.get(&key) {
+        return Ok(Arc::clone(value.value()));
+    }
+    let value = Arc::new(xml_str_to_json(&xml, config)?);
+    // another thread may have converted the same document meanwhile
+    Ok(Arc::clone(cache.0.entry(key).or_insert(value).value()))
+}
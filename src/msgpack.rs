@@ -0,0 +1,15 @@
+//! Conversion into [MessagePack](https://msgpack.org/), a compact binary alternative to JSON.
+//! Requires `msgpack` feature which uses [rmp-serde](https://github.com/3Hren/msgpack-rust).
+
+use super::{xml_string_to_json, Config, ConversionError};
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and
+/// serializes the result into MessagePack bytes. Decoding the bytes gives back the same JSON value.
+/// With `arbitrary_precision` feature the numbers are encoded the way `serde_json` serializes them,
+/// as maps holding the text of the number, which only `serde_json::Value` can decode.
+pub fn xml_string_to_msgpack(xml: String, config: &Config) -> Result<Vec<u8>, ConversionError> {
+    let value = xml_string_to_json(xml, config)?;
+    // `serde_json` numbers with arbitrary precision are structs, which must be written as maps to decode them
+    rmp_serde::to_vec_named(&value)
+        .map_err(|e| ConversionError::SerializationError(e.to_string()))
+}
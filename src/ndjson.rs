@@ -0,0 +1,176 @@
+//! Conversion of repeated records into [NDJSON](http://ndjson.org/), one JSON document per line.
+
+use serde_json::{Map, Value};
+use std::io::{Read, Write};
+
+use super::progress::CountingReader;
+use super::{
+    convert_key, convert_attributes, convert_node, convert_text, element_key, finish_output, get_json_type,
+    parse_xml, Config, Context, ConversionError, Hooks,
+};
+
+/// Converts every element found at `record_path` into a single line of JSON and writes it into `writer`.
+/// Returns the number of lines written. E.g. `<export><row id="1"/><row id="2"/></export>` with
+/// `/export/row` record path becomes two lines: `{"@id":1}` and `{"@id":2}`.
+///
+/// With `merge_ancestors` the attributes and text-only child elements of the ancestors of the records
+/// are copied into every line. Their names are prefixed with the name of the ancestor and
+/// `KeyOptions::ancestor_key_separator`, e.g. `<export date="2020-01-01">` adds `"export.@date":"2020-01-01"`
+/// to every `row`. The properties of the record take precedence over the ancestor properties.
+///
+/// The records are converted with the same rules as when converting the whole document, e.g. JSON type
+/// overrides for `/export/row/@id` apply. Records converted into scalar values are written as-is, or as
+/// an object with the value under `Config::xml_text_node_prop_name` if ancestor properties are merged into it.
+/// The whole document is read into memory before writing the first line. `DiagnosticOptions::progress` is reported
+/// while reading the document and once all lines were written.
+pub fn xml_to_ndjson<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    record_path: &str,
+    config: &Config,
+    merge_ancestors: bool,
+) -> Result<u64, ConversionError> {
+    let mut reader = CountingReader::new(reader, config);
+    let mut xml = String::new();
+    reader.read_to_string(&mut xml)?;
+    let doc = parse_xml(&xml, config)?;
+    let root = &doc.root;
+
+    let segments: Vec<&str> = record_path.split('/').filter(|s| !s.is_empty()).collect();
+    let mut writer = RecordWriter {
+        writer: &mut writer,
+        config,
+        merge_ancestors,
+        ctx: Context {
+            marks: doc.side_table.resolve(root),
+            ..Context::default()
+        },
+        lines: 0,
+        elements: 0,
+    };
+
+    if segments.first() == Some(&root.name()) {
+        writer.write_records(root, &segments[1..], &Map::new())?;
+    }
+
+    writer.writer.flush()?;
+    reader.tracker.finish(writer.elements, writer.lines);
+    Ok(writer.lines)
+}
+
+struct RecordWriter<'a, W: Write> {
+    writer: &'a mut W,
+    config: &'a Config,
+    merge_ancestors: bool,
+    ctx: Context,
+    lines: u64,
+    /// The number of elements converted into records, for `DiagnosticOptions::progress`
+    elements: u64,
+}
+
+impl<'a, W: Write> RecordWriter<'a, W> {
+    /// Walks down the record path. `rest` is the remaining part of the path below `el`.
+    fn write_records(
+        &mut self,
+        el: &minidom::Element,
+        rest: &[&str],
+        ancestors: &Map<String, Value>,
+    ) -> Result<(), ConversionError> {
+        let (next, rest) = match rest.split_first() {
+            Some(split) => split,
+            None => return self.write_record(el, ancestors),
+        };
+
+        // this element is an ancestor of the records
+        let path_len = self.ctx.path.len();
+        self.ctx.path.push('/');
+        self.ctx.path.push_str(el.name());
+
+        let mut merged;
+        let ancestors = if self.merge_ancestors {
+            merged = ancestors.clone();
+            self.collect_ancestor_fields(el, next, &mut merged)?;
+            &merged
+        } else {
+            ancestors
+        };
+
+        for child in el.children().filter(|c| c.name() == *next) {
+            self.write_records(child, rest, ancestors)?;
+        }
+
+        self.ctx.path.truncate(path_len);
+        Ok(())
+    }
+
+    /// Adds the attributes and text-only children of the ancestor to the merged properties.
+    fn collect_ancestor_fields(
+        &mut self,
+        el: &minidom::Element,
+        record_step: &str,
+        merged: &mut Map<String, Value>,
+    ) -> Result<(), ConversionError> {
+        let prefix = [el.name(), self.config.keys.ancestor_key_separator.as_str()].concat();
+
+        for (k, v) in convert_attributes(el, self.config, &mut self.ctx, &Hooks::default())? {
+            merged.insert([prefix.as_str(), &k].concat(), v);
+        }
+
+        let path_len = self.ctx.path.len();
+        for child in el.children() {
+            let simple = child.name() != record_step
+                && child.children().next().is_none()
+                && child.attrs().next().is_none()
+                && !child.text().trim().is_empty();
+            if !simple {
+                continue;
+            }
+
+            self.ctx.path.push('/');
+            self.ctx.path.push_str(child.name());
+            let (_, json_type) = get_json_type(self.config, &Hooks::default(), &self.ctx.path);
+            let cdata = self.config.text.cdata_as_string && self.ctx.marks.has_cdata(child);
+            let value = convert_text(child.text().trim(), cdata, json_type, self.config, &mut self.ctx)?;
+            let key = convert_key(element_key(child, self.config), self.config, &mut self.ctx)?;
+            merged.insert([prefix.as_str(), &key].concat(), value);
+            self.ctx.path.truncate(path_len);
+        }
+
+        Ok(())
+    }
+
+    /// Converts the record and writes it as a single line.
+    fn write_record(
+        &mut self,
+        el: &minidom::Element,
+        ancestors: &Map<String, Value>,
+    ) -> Result<(), ConversionError> {
+        self.ctx.elements = 0;
+        let converted = convert_node(el, self.config, &mut self.ctx, &Hooks::default())?;
+        self.elements += self.ctx.elements;
+        let value = match converted {
+            Some(value) => value,
+            None if ancestors.is_empty() => return Ok(()),
+            None => Value::Object(Map::new()),
+        };
+
+        let mut line = if ancestors.is_empty() {
+            value
+        } else {
+            let mut line = ancestors.clone();
+            match value {
+                Value::Object(data) => line.extend(data),
+                value => {
+                    line.insert(self.config.xml_text_node_prop_name.clone(), value);
+                }
+            }
+            Value::Object(line)
+        };
+        finish_output(&mut line, self.config);
+
+        serde_json::to_writer(&mut *self.writer, &line).map_err(std::io::Error::from)?;
+        self.writer.write_all(b"\n")?;
+        self.lines += 1;
+        Ok(())
+    }
+}
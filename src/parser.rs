@@ -0,0 +1,302 @@
+//! Swappable XML parsers. The conversion works on a tree of elements, which any parser implementing
+//! `XmlParser` can produce, e.g. to compare the parsers or to use one with different performance characteristics.
+
+use minidom::{Element, Node};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+#[cfg(feature = "quickxml-parser")]
+use quick_xml::events::{BytesStart, Event};
+#[cfg(feature = "quickxml-parser")]
+use quick_xml::Reader;
+
+use super::{Config, ConversionError, XmlError};
+#[cfg(feature = "quickxml-parser")]
+use super::DupAttr;
+
+/// A parsed XML document ready for the conversion. The tree is opaque, so the public API doesn't depend
+/// on the types of the parsers.
+pub struct ParsedDocument {
+    pub(crate) root: Element,
+    pub(crate) side_table: SideTable,
+}
+
+impl ParsedDocument {
+    fn new(root: Element) -> Self {
+        ParsedDocument {
+            root,
+            side_table: SideTable::default(),
+        }
+    }
+}
+
+/// What the tree of elements can't hold, with the elements and the text nodes numbered in document order.
+#[derive(Debug, Default)]
+pub(crate) struct SideTable {
+    /// The number of the element, the name and all values of the repeated attributes with `DupAttr::MergeArray`
+    pub(crate) repeated_attrs: Vec<(usize, String, Vec<String>)>,
+    /// The numbers of the elements with CDATA sections, including empty ones, see `TextOptions::cdata_as_string`
+    pub(crate) cdata_elements: Vec<usize>,
+    /// The numbers of the text nodes of the non-empty CDATA sections
+    pub(crate) cdata_texts: Vec<usize>,
+    /// The number of the element and the processing instructions inside of it without `<?` and `?>`,
+    /// see `TextOptions::processing_instructions_key`
+    pub(crate) instructions: Vec<(usize, String)>,
+}
+
+impl SideTable {
+    /// Looks up the nodes of the tree, which must be the tree parsed with this table.
+    pub(crate) fn resolve(&self, root: &Element) -> NodeMarks {
+        let mut marks = NodeMarks::default();
+        if self.repeated_attrs.is_empty() && self.cdata_elements.is_empty() && self.instructions.is_empty() {
+            return marks;
+        }
+
+        let mut elements = Vec::new();
+        let mut texts = Vec::new();
+        collect_nodes(root, &mut elements, &mut texts);
+        for (element, name, values) in &self.repeated_attrs {
+            if let Some(el) = elements.get(*element) {
+                marks.repeated_attrs.insert((element_address(el), name.clone()), values.clone());
+            }
+        }
+        marks.cdata_elements = self
+            .cdata_elements
+            .iter()
+            .filter_map(|element| elements.get(*element).map(|el| element_address(el)))
+            .collect();
+        marks.cdata_texts = self
+            .cdata_texts
+            .iter()
+            .filter_map(|text| texts.get(*text).map(|text| text_address(text)))
+            .collect();
+        for (element, instruction) in &self.instructions {
+            if let Some(el) = elements.get(*element) {
+                let instructions = marks.instructions.entry(element_address(el)).or_insert_with(Vec::new);
+                instructions.push(instruction.clone());
+            }
+        }
+        marks
+    }
+}
+
+/// The `SideTable` of the tree being converted. The nodes are identified by their address, which doesn't change
+/// while the tree is borrowed for the conversion.
+#[derive(Debug, Default)]
+pub(crate) struct NodeMarks {
+    repeated_attrs: HashMap<(usize, String), Vec<String>>,
+    cdata_elements: HashSet<usize>,
+    cdata_texts: HashSet<usize>,
+    instructions: HashMap<usize, Vec<String>>,
+}
+
+impl NodeMarks {
+    /// Returns all values of the attribute if it was repeated on the element, see `DupAttr::MergeArray`.
+    pub(crate) fn repeated_attr(&self, el: &Element, name: &str) -> Option<&[String]> {
+        if self.repeated_attrs.is_empty() {
+            return None;
+        }
+        self.repeated_attrs.get(&(element_address(el), name.to_owned())).map(Vec::as_slice)
+    }
+
+    /// Returns `true` if the element has CDATA sections.
+    pub(crate) fn has_cdata(&self, el: &Element) -> bool {
+        !self.cdata_elements.is_empty() && self.cdata_elements.contains(&element_address(el))
+    }
+
+    /// Returns `true` if the text node is a CDATA section. The text must be the whole text node.
+    pub(crate) fn is_cdata(&self, text: &str) -> bool {
+        !self.cdata_texts.is_empty() && self.cdata_texts.contains(&text_address(text))
+    }
+
+    /// Returns the processing instructions inside of the element in document order.
+    pub(crate) fn instructions(&self, el: &Element) -> &[String] {
+        match self.instructions.get(&element_address(el)) {
+            Some(instructions) => instructions,
+            None => &[],
+        }
+    }
+}
+
+fn element_address(el: &Element) -> usize {
+    el as *const Element as usize
+}
+
+/// The parsers don't keep empty text nodes, so the text of every node has an address of its own.
+fn text_address(text: &str) -> usize {
+    text.as_ptr() as usize
+}
+
+/// Collects the elements and the text nodes in document order.
+fn collect_nodes<'a>(el: &'a Element, elements: &mut Vec<&'a Element>, texts: &mut Vec<&'a str>) {
+    elements.push(el);
+    for node in el.nodes() {
+        match node {
+            Node::Element(child) => collect_nodes(child, elements, texts),
+            Node::Text(text) => texts.push(text),
+            _ => (),
+        }
+    }
+}
+
+/// Parses XML documents into `ParsedDocument`, see `xml_string_to_json_with_parser`.
+/// The input is the document after the options of `Config` that apply to the raw text, e.g. without a byte order mark.
+pub trait XmlParser {
+    fn parse(xml: &str) -> Result<ParsedDocument, ConversionError>;
+
+    /// Same as `parse` for parsers with options in `Config`, e.g. `AttributeOptions::duplicate_source_attributes`.
+    /// This is what the conversion functions call. Defaults to `parse`.
+    fn parse_with_config(xml: &str, config: &Config) -> Result<ParsedDocument, ConversionError> {
+        let _ = config;
+        Self::parse(xml)
+    }
+}
+
+/// The parser used by all other conversion functions, based on `minidom`.
+pub struct MinidomParser;
+
+impl XmlParser for MinidomParser {
+    fn parse(xml: &str) -> Result<ParsedDocument, ConversionError> {
+        Element::from_str(xml)
+            .map(ParsedDocument::new)
+            .map_err(|e| ConversionError::Xml(XmlError(e)))
+    }
+}
+
+/// An experimental parser reading the events of `quick-xml` directly.
+/// Namespace declarations are dropped, which only affects `InputOptions::namespace_default_handling`.
+/// Unlike the default parser, it can read documents with repeated attributes,
+/// see `AttributeOptions::duplicate_source_attributes`.
+/// Requires `quickxml-parser` feature.
+#[cfg(feature = "quickxml-parser")]
+pub struct QuickXmlParser;
+
+#[cfg(feature = "quickxml-parser")]
+impl XmlParser for QuickXmlParser {
+    fn parse(xml: &str) -> Result<ParsedDocument, ConversionError> {
+        read_document(xml, &DupAttr::Error).map_err(|e| ConversionError::Xml(XmlError(e)))
+    }
+
+    fn parse_with_config(xml: &str, config: &Config) -> Result<ParsedDocument, ConversionError> {
+        read_document(xml, &config.attributes.duplicate_source_attributes)
+            .map_err(|e| ConversionError::Xml(XmlError(e)))
+    }
+}
+
+/// Builds the tree of elements from the events. Texts, CDATA sections and comments are kept
+/// the same way `minidom` keeps them.
+#[cfg(feature = "quickxml-parser")]
+fn read_document(xml: &str, dup_attr: &DupAttr) -> Result<ParsedDocument, minidom::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    // the root element is at the bottom, the names are kept to check the end tags
+    let mut stack: Vec<(Element, String)> = Vec::new();
+    let mut side_table = SideTable::default();
+    let mut elements = 0;
+
+    loop {
+        match reader.read_event(&mut buf)? {
+            Event::Start(ref e) => {
+                let element = build_element(&reader, e, dup_attr, elements, &mut side_table)?;
+                elements += 1;
+                stack.push((element, std::str::from_utf8(e.name())?.to_owned()));
+            }
+            Event::Empty(ref e) => {
+                let element = build_element(&reader, e, dup_attr, elements, &mut side_table)?;
+                elements += 1;
+                match stack.last_mut() {
+                    Some((parent, _)) => {
+                        parent.append_child(element);
+                    }
+                    None => return Ok(ParsedDocument { root: element, side_table }),
+                }
+            }
+            Event::End(ref e) => {
+                let (element, name) = stack.pop().ok_or(minidom::Error::InvalidElementClosed)?;
+                if e.name() != name.as_bytes() {
+                    return Err(minidom::Error::InvalidElementClosed);
+                }
+                match stack.last_mut() {
+                    Some((parent, _)) => {
+                        parent.append_child(element);
+                    }
+                    None => return Ok(ParsedDocument { root: element, side_table }),
+                }
+            }
+            Event::Text(ref e) => {
+                if let Some((element, _)) = stack.last_mut() {
+                    let text = e.unescape_and_decode(&reader)?;
+                    if !text.is_empty() {
+                        element.append_text_node(text);
+                    }
+                }
+            }
+            Event::CData(ref e) => {
+                if let Some((element, _)) = stack.last_mut() {
+                    let text = reader.decode(e)?.to_owned();
+                    if !text.is_empty() {
+                        element.append_text_node(text);
+                    }
+                }
+            }
+            Event::Comment(ref e) => {
+                if let Some((element, _)) = stack.last_mut() {
+                    let comment = reader.decode(e)?.to_owned();
+                    if !comment.is_empty() {
+                        element.append_comment_node(comment);
+                    }
+                }
+            }
+            Event::Eof => return Err(minidom::Error::EndOfDocument),
+            Event::Decl(_) | Event::PI(_) | Event::DocType(_) => (),
+        }
+        buf.clear();
+    }
+}
+
+/// Creates an element with the name and the attributes of the start tag. Repeated attributes are rejected
+/// by `quick-xml` unless the policy keeps them. With `DupAttr::MergeArray` the element keeps the first value,
+/// and all values are added to the side table with the number of the element.
+#[cfg(feature = "quickxml-parser")]
+fn build_element(
+    reader: &Reader<&[u8]>,
+    start: &BytesStart,
+    dup_attr: &DupAttr,
+    element: usize,
+    side_table: &mut SideTable,
+) -> Result<Element, minidom::Error> {
+    let name = std::str::from_utf8(start.name())?;
+    if name.matches(':').count() > 1 {
+        return Err(minidom::Error::InvalidElement);
+    }
+
+    let mut attributes = start.attributes();
+    attributes.with_checks(*dup_attr == DupAttr::Error);
+    // all values of each attribute in document order
+    let mut attrs: Vec<(String, Vec<String>)> = Vec::new();
+    for attr in attributes {
+        let attr = attr?;
+        let key = std::str::from_utf8(attr.key)?;
+        if key == "xmlns" || key.starts_with("xmlns:") {
+            continue;
+        }
+        let value = attr.unescape_and_decode_value(reader)?;
+        match attrs.iter_mut().find(|(k, _)| k == key) {
+            None => attrs.push((key.to_owned(), vec![value])),
+            Some((_, values)) => match dup_attr {
+                DupAttr::Last => values[0] = value,
+                DupAttr::MergeArray => values.push(value),
+                DupAttr::First | DupAttr::Error => (),
+            },
+        }
+    }
+
+    let mut builder = Element::builder(name);
+    for (key, values) in attrs {
+        builder = builder.attr(key.clone(), values[0].clone());
+        if values.len() > 1 {
+            side_table.repeated_attrs.push((element, key, values));
+        }
+    }
+    Ok(builder.build())
+}
@@ -0,0 +1,115 @@
+//! Progress reports for the conversion of large documents read from readers, see `DiagnosticOptions::progress`.
+
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Arc;
+
+use super::Config;
+
+/// The counters passed to `ProgressConfig::callback`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Progress {
+    /// The number of bytes read from the reader so far
+    pub bytes_read: u64,
+    /// The number of elements converted so far
+    pub elements: u64,
+    /// The number of values produced so far, e.g. lines written by `xml_to_ndjson`
+    /// or 1 once a whole document was converted
+    pub records: u64,
+}
+
+/// Calls the callback every time another `every_bytes` bytes were read, and once more with the final counters
+/// after the conversion. The conversion continues once the callback returns, so a slow callback slows it down.
+/// Applies to `Converter::convert_reader`, `xml_to_ndjson` and the asynchronous readers.
+#[derive(Clone)]
+pub struct ProgressConfig {
+    pub every_bytes: u64,
+    pub callback: Arc<dyn Fn(Progress) + Send + Sync>,
+}
+
+impl ProgressConfig {
+    /// Creates a progress config calling the function every `every_bytes` bytes.
+    pub fn new<F>(every_bytes: u64, callback: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        ProgressConfig {
+            every_bytes,
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl fmt::Debug for ProgressConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ProgressConfig {{ every_bytes: {}, .. }}", self.every_bytes)
+    }
+}
+
+/// Keeps the counters of a single conversion and calls the callback of `DiagnosticOptions::progress`, if any.
+pub(crate) struct ProgressTracker {
+    config: Option<ProgressConfig>,
+    progress: Progress,
+    next_report: u64,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(config: &Config) -> Self {
+        ProgressTracker {
+            config: config.diagnostics.progress.clone(),
+            progress: Progress::default(),
+            next_report: config.diagnostics.progress.as_ref().map_or(0, |c| c.every_bytes.max(1)),
+        }
+    }
+
+    /// Counts the bytes read and reports the progress once the next `every_bytes` boundary is crossed.
+    pub(crate) fn add_bytes(&mut self, len: usize) {
+        self.progress.bytes_read += len as u64;
+        let config = match self.config.as_ref() {
+            Some(config) => config,
+            None => return,
+        };
+        if self.progress.bytes_read >= self.next_report {
+            let every_bytes = config.every_bytes.max(1);
+            self.next_report = (self.progress.bytes_read / every_bytes + 1) * every_bytes;
+            (config.callback)(self.progress);
+        }
+    }
+
+    /// Updates the conversion counters without reporting them.
+    pub(crate) fn set_converted(&mut self, elements: u64, records: u64) {
+        self.progress.elements = elements;
+        self.progress.records = records;
+    }
+
+    /// Reports the final counters.
+    pub(crate) fn finish(&mut self, elements: u64, records: u64) {
+        self.set_converted(elements, records);
+        if let Some(config) = self.config.as_ref() {
+            (config.callback)(self.progress);
+        }
+    }
+}
+
+/// A reader counting the bytes read from the wrapped reader.
+pub(crate) struct CountingReader<R> {
+    pub(crate) inner: R,
+    pub(crate) tracker: ProgressTracker,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub(crate) fn new(inner: R, config: &Config) -> Self {
+        CountingReader {
+            inner,
+            tracker: ProgressTracker::new(config),
+        }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.tracker.add_bytes(len);
+        Ok(len)
+    }
+}
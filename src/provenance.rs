@@ -0,0 +1,592 @@
+//! The record of the conversion settings embedded into the output by `InjectionOptions::embed_config`.
+//!
+//! The record is a JSON object with the crate version, the enabled features and the options of the `Config`.
+//! Options holding data are written as JSON, e.g. `"xml_attr_prefix":"@"` or `{"Separator":"|"}` for enum
+//! variants with a value, and can be restored with `Config::from_embedded_config`. Functions can't be written,
+//! so only the names of the options holding them are listed under `elided`. The options which can't be read
+//! back, e.g. the JSON type overrides or `InjectionOptions::node_id`, are described by their `Debug` output under
+//! `unrestored`.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use super::capabilities::capabilities;
+#[cfg(any(not(feature = "json_types"), not(feature = "encoding"), not(feature = "spill")))]
+use super::capabilities::missing_feature;
+use super::{
+    AbsentPolicy, CommentHandling, Config, ContextPlacement, ConversionError, ConversionProfile, DupAttr,
+    InferenceFlags, JsonStringPolicy, KeyCharset, LongNamePolicy, NamespaceDefaultHandling, NodeIdClash, NullValue,
+    NumberParser, PromoteAttrs, TailTextHandling, TextJoin, TextPropCollision,
+};
+#[cfg(feature = "encoding")]
+use super::OutputEncoding;
+#[cfg(feature = "json_types")]
+use super::PathAnchor;
+#[cfg(feature = "spill")]
+use super::SpillConfig;
+
+/// Returns the record of the config for `InjectionOptions::embed_config`.
+pub(crate) fn embedded_config(config: &Config) -> Value {
+    let mut options = Map::new();
+    let mut insert = |name: &str, value: Value| {
+        options.insert(name.to_owned(), value);
+    };
+
+    insert("leading_zero_as_string", Value::Bool(config.leading_zero_as_string));
+    insert("boolean_from_numeric", Value::Bool(config.values.boolean_from_numeric));
+    insert("coerce_bool_attrs", Value::Bool(config.values.coerce_bool_attrs));
+    insert("bool_truthy", strings(&config.values.bool_truthy));
+    insert("bool_falsy", strings(&config.values.bool_falsy));
+    insert("emit_number_as_hybrid", Value::Bool(config.values.emit_number_as_hybrid));
+    insert("trim_text", Value::Bool(config.text.trim_text));
+    insert("cdata_as_string", Value::Bool(config.text.cdata_as_string));
+    insert("strict_json_types", Value::Bool(config.values.strict_json_types));
+    insert("force_float_format", Value::Bool(config.values.force_float_format));
+    insert("json_string_policy_keys", Value::Bool(config.values.json_string_policy_keys));
+    insert("preserve_child_order", Value::Bool(config.injection.preserve_child_order));
+    insert("merge_same_name_objects", Value::Bool(config.arrays.merge_same_name_objects));
+    insert("ordered_content", Value::Bool(config.text.ordered_content));
+    insert("array_homogeneity_check", Value::Bool(config.diagnostics.array_homogeneity_check));
+    insert("auto_flatten_plural_wrappers", Value::Bool(config.arrays.auto_flatten_plural_wrappers));
+    insert("promote_root_attrs", Value::Bool(config.attributes.promote_root_attrs));
+    insert("emit_type_annotations", Value::Bool(config.output.emit_type_annotations));
+    insert("stop_after_priority_paths", Value::Bool(config.output.stop_after_priority_paths));
+    insert("strict_prolog", Value::Bool(config.input.strict_prolog));
+    insert("normalize_input", Value::Bool(config.input.normalize_input));
+
+    insert("xml_attr_prefix", Value::String(config.xml_attr_prefix.clone()));
+    insert("xml_text_node_prop_name", Value::String(config.xml_text_node_prop_name.clone()));
+    insert("child_order_key", Value::String(config.injection.child_order_key.clone()));
+    insert("content_key", Value::String(config.text.content_key.clone()));
+    insert("key_truncation_suffix", Value::String(config.keys.key_truncation_suffix.clone()));
+    insert("max_name_length", config.keys.max_name_length.map_or(Value::Null, Value::from));
+    insert("long_name_policy", unit(&config.keys.long_name_policy));
+    insert("ancestor_key_separator", Value::String(config.keys.ancestor_key_separator.clone()));
+    insert(
+        "promoted_attr_collision_suffix",
+        Value::String(config.attributes.promoted_attr_collision_suffix.clone()),
+    );
+
+    insert("key_length_limit", config.keys.key_length_limit.map_or(Value::Null, Value::from));
+    insert("max_json_depth", config.output.max_json_depth.map_or(Value::Null, Value::from));
+    insert("json_depth_sentinel", config.output.json_depth_sentinel.clone());
+    insert("max_xml_depth", config.input.max_xml_depth.map_or(Value::Null, Value::from));
+    insert(
+        "xml_attr_array_separator",
+        config.attributes.xml_attr_array_separator.clone().map_or(Value::Null, Value::String),
+    );
+    insert(
+        "allowed_versions",
+        config.input.allowed_versions.as_ref().map_or(Value::Null, |versions| strings(versions)),
+    );
+    insert("embed_config", config.injection.embed_config.clone().map_or(Value::Null, Value::String));
+    insert("trace_paths", strings(&config.diagnostics.trace_paths));
+    let mut forced_array_paths: Vec<&String> = config.arrays.forced_array_paths.iter().collect();
+    forced_array_paths.sort();
+    insert("forced_array_paths", strings(&forced_array_paths));
+    insert(
+        "force_array_above_child_count",
+        config.arrays.force_array_above_child_count.map_or(Value::Null, Value::from),
+    );
+    let mut always_array_elements: Vec<&String> = config.arrays.always_array_elements.iter().collect();
+    always_array_elements.sort();
+    insert("always_array_elements", strings(&always_array_elements));
+    insert("plural_wrapper_exclusions", strings(&config.arrays.plural_wrapper_exclusions));
+    insert("plural_pairs", sorted_map(&config.arrays.plural_pairs, |singular| Value::String(singular.clone())));
+    insert(
+        "absent_field_policy",
+        sorted_map(&config.injection.absent_field_policy, unit),
+    );
+
+    let inference = &config.values.inference;
+    let mut flags = Map::new();
+    flags.insert("numbers".to_owned(), Value::Bool(inference.numbers));
+    flags.insert("booleans".to_owned(), Value::Bool(inference.booleans));
+    flags.insert("nulls".to_owned(), Value::Bool(inference.nulls));
+    insert("inference", Value::Object(flags));
+
+    insert("profile", unit(&config.profile));
+    insert("empty_element_handling", unit(&config.empty_element_handling));
+    insert("injected_fields_on_clash", unit(&config.injection.injected_fields_on_clash));
+    insert("context_placement", unit(&config.injection.context_placement));
+    insert(
+        "text_prop_collision",
+        match config.text.text_prop_collision {
+            TextPropCollision::Suffix(ref suffix) => tagged("Suffix", Value::String(suffix.clone())),
+            TextPropCollision::Error => unit(&config.text.text_prop_collision),
+        },
+    );
+    insert(
+        "text_join",
+        match config.text.text_join {
+            TextJoin::Separator(ref separator) => tagged("Separator", Value::String(separator.clone())),
+            ref text_join => unit(text_join),
+        },
+    );
+    insert(
+        "tail_text_handling",
+        match config.text.tail_text_handling {
+            TailTextHandling::AsField(ref key) => tagged("AsField", Value::String(key.clone())),
+            ref handling => unit(handling),
+        },
+    );
+    insert(
+        "namespace_default_handling",
+        match config.input.namespace_default_handling {
+            NamespaceDefaultHandling::AddToElement(ref key) => tagged("AddToElement", Value::String(key.clone())),
+            NamespaceDefaultHandling::PrefixKeys(ref prefix) => tagged("PrefixKeys", Value::String(prefix.clone())),
+            ref handling => unit(handling),
+        },
+    );
+    insert("duplicate_source_attributes", unit(&config.attributes.duplicate_source_attributes));
+    insert(
+        "key_charset",
+        match config.keys.key_charset {
+            KeyCharset::AsciiOnly { ref replacement } => {
+                let mut fields = Map::new();
+                fields.insert("replacement".to_owned(), Value::String(replacement.clone()));
+                tagged("AsciiOnly", Value::Object(fields))
+            }
+            ref key_charset => unit(key_charset),
+        },
+    );
+    insert(
+        "json_string_policy",
+        match config.values.json_string_policy {
+            JsonStringPolicy::ReplaceWith(c) => tagged("ReplaceWith", Value::String(c.to_string())),
+            ref policy => unit(policy),
+        },
+    );
+    insert(
+        "processing_instructions_key",
+        config.text.processing_instructions_key.clone().map_or(Value::Null, Value::String),
+    );
+    insert(
+        "comment_handling",
+        match config.text.comment_handling {
+            CommentHandling::PreserveAsField(ref key) => tagged("PreserveAsField", Value::String(key.clone())),
+            CommentHandling::PreserveAsArray(ref key) => tagged("PreserveAsArray", Value::String(key.clone())),
+            ref handling => unit(handling),
+        },
+    );
+    insert(
+        "promote_attributes",
+        match config.attributes.promote_attributes {
+            PromoteAttrs::Named(ref names) => tagged("Named", strings(names)),
+            PromoteAttrs::Paths(ref paths) => tagged("Paths", strings(paths)),
+            ref promote => unit(promote),
+        },
+    );
+
+    #[cfg(feature = "json_types")]
+    {
+        insert("path_anchor", unit(&config.path_anchor));
+        insert("enum_mappings", sorted_map(&config.enum_mappings, |values| strings(values)));
+        insert("tuple_rules", sorted_map(&config.tuple_rules, |attrs| strings(attrs)));
+    }
+    #[cfg(feature = "encoding")]
+    insert(
+        "output_encoding",
+        match config.output.output_encoding {
+            OutputEncoding::Other(encoding) => tagged("Other", Value::String(encoding.name().to_owned())),
+            ref output_encoding => unit(output_encoding),
+        },
+    );
+    #[cfg(feature = "spill")]
+    insert(
+        "spill",
+        config.output.spill.as_ref().map_or(Value::Null, |spill| {
+            let mut map = Map::new();
+            map.insert("threshold_members".to_owned(), Value::from(spill.threshold_members));
+            map.insert("temp_dir".to_owned(), Value::String(spill.temp_dir.to_string_lossy().into_owned()));
+            Value::Object(map)
+        }),
+    );
+
+    // options with values that can't be restored from JSON
+    let mut unrestored = Map::new();
+    let mut describe = |name: &str, value: String| {
+        unrestored.insert(name.to_owned(), Value::String(value));
+    };
+    describe("dual_emit", format!("{:?}", config.keys.dual_emit));
+    describe("node_id", format!("{:?}", config.injection.node_id));
+    describe("injected_fields", format!("{:?}", config.injection.injected_fields));
+    #[cfg(feature = "json_types")]
+    {
+        let mut overrides: Vec<String> = config
+            .json_type_overrides
+            .iter()
+            .map(|(path, json_array)| format!("{}: {:?}", path, json_array))
+            .collect();
+        overrides.sort();
+        describe("json_type_overrides", overrides.join(", "));
+        describe("json_type_wildcard_overrides", format!("{:?}", config.json_type_wildcard_overrides));
+        describe("json_type_override_templates", format!("{:?}", config.json_type_override_templates));
+    }
+    #[cfg(feature = "regex_path")]
+    describe("json_regex_type_overrides", format!("{:?}", config.json_regex_type_overrides));
+    if !matches!(config.values.number_parser, NumberParser::Custom(_)) {
+        describe("number_parser", format!("{:?}", config.values.number_parser));
+    }
+
+    // functions are never written
+    let mut elided = Vec::new();
+    if !config.attributes.attr_value_transforms.is_empty() {
+        elided.push("attr_value_transforms");
+    }
+    if !config.attributes.virtual_attributes.is_empty() {
+        elided.push("virtual_attributes");
+    }
+    if config.injection.interceptor.is_some() {
+        elided.push("interceptor");
+    }
+    if config.values.text_node_mapper.is_some() {
+        elided.push("text_node_mapper");
+    }
+    if config.diagnostics.progress.is_some() {
+        elided.push("progress");
+    }
+    if let NumberParser::Custom(_) = config.values.number_parser {
+        elided.push("number_parser");
+    }
+
+    let mut record = Map::new();
+    record.insert("crate_version".to_owned(), Value::String(env!("CARGO_PKG_VERSION").to_owned()));
+    record.insert("features".to_owned(), strings(&capabilities().features()));
+    record.insert("options".to_owned(), Value::Object(options));
+    record.insert("unrestored".to_owned(), Value::Object(unrestored));
+    record.insert("elided".to_owned(), strings(&elided));
+    Value::Object(record)
+}
+
+
+fn strings<S: AsRef<str>>(values: &[S]) -> Value {
+    Value::Array(values.iter().map(|v| Value::String(v.as_ref().to_owned())).collect())
+}
+
+/// Writes the map sorted by its keys, so the record doesn't depend on the order of the `HashMap`.
+fn sorted_map<T, F: Fn(&T) -> Value>(map: &HashMap<String, T>, f: F) -> Value {
+    let mut entries: Vec<(&String, &T)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    Value::Object(entries.into_iter().map(|(k, v)| (k.clone(), f(v))).collect())
+}
+
+/// Writes an enum variant without values as its name, e.g. `"Latest"`.
+fn unit<T: Debug>(value: &T) -> Value {
+    Value::String(format!("{:?}", value))
+}
+
+/// Writes an enum variant with a value, e.g. `{"Separator":"|"}`.
+fn tagged(variant: &str, value: Value) -> Value {
+    let mut map = Map::new();
+    map.insert(variant.to_owned(), value);
+    Value::Object(map)
+}
+
+impl Config {
+    /// Restores the config from the record embedded by `InjectionOptions::embed_config`, e.g. to convert an archived
+    /// document again with the same settings. The options missing from the record keep their default values,
+    /// as do the options listed under `elided` and `unrestored` in the record, which have to be set again.
+    /// Returns `ConversionError::InvalidEmbeddedConfig` if an option has a value of the wrong type
+    /// and `ConversionError::MissingFeature` if an option needs a feature which is not enabled, see `Config::validate`.
+    pub fn from_embedded_config(record: &Value) -> Result<Config, ConversionError> {
+        let options = match record.get("options") {
+            Some(Value::Object(options)) => options,
+            _ => return Err(invalid("options")),
+        };
+        let mut config = Config::new_with_defaults();
+        let read = Reader { options };
+
+        read.bool("leading_zero_as_string", &mut config.leading_zero_as_string)?;
+        read.bool("boolean_from_numeric", &mut config.values.boolean_from_numeric)?;
+        read.bool("coerce_bool_attrs", &mut config.values.coerce_bool_attrs)?;
+        read.value("bool_truthy", &mut config.values.bool_truthy, string_list)?;
+        read.value("bool_falsy", &mut config.values.bool_falsy, string_list)?;
+        read.bool("emit_number_as_hybrid", &mut config.values.emit_number_as_hybrid)?;
+        read.bool("trim_text", &mut config.text.trim_text)?;
+        read.bool("cdata_as_string", &mut config.text.cdata_as_string)?;
+        read.bool("strict_json_types", &mut config.values.strict_json_types)?;
+        read.bool("force_float_format", &mut config.values.force_float_format)?;
+        read.bool("json_string_policy_keys", &mut config.values.json_string_policy_keys)?;
+        read.bool("preserve_child_order", &mut config.injection.preserve_child_order)?;
+        read.bool("merge_same_name_objects", &mut config.arrays.merge_same_name_objects)?;
+        read.bool("ordered_content", &mut config.text.ordered_content)?;
+        read.bool("array_homogeneity_check", &mut config.diagnostics.array_homogeneity_check)?;
+        read.bool("auto_flatten_plural_wrappers", &mut config.arrays.auto_flatten_plural_wrappers)?;
+        read.bool("promote_root_attrs", &mut config.attributes.promote_root_attrs)?;
+        read.bool("emit_type_annotations", &mut config.output.emit_type_annotations)?;
+        read.bool("stop_after_priority_paths", &mut config.output.stop_after_priority_paths)?;
+        read.bool("strict_prolog", &mut config.input.strict_prolog)?;
+        read.bool("normalize_input", &mut config.input.normalize_input)?;
+
+        read.string("xml_attr_prefix", &mut config.xml_attr_prefix)?;
+        read.string("xml_text_node_prop_name", &mut config.xml_text_node_prop_name)?;
+        read.string("child_order_key", &mut config.injection.child_order_key)?;
+        read.string("content_key", &mut config.text.content_key)?;
+        read.string("key_truncation_suffix", &mut config.keys.key_truncation_suffix)?;
+        read.optional("max_name_length", &mut config.keys.max_name_length, |v| v.as_u64().map(|n| n as usize))?;
+        read.variant("long_name_policy", &mut config.keys.long_name_policy, |variant, _| match variant {
+            "Error" => Some(LongNamePolicy::Error),
+            "TruncateWithHash" => Some(LongNamePolicy::TruncateWithHash),
+            _ => None,
+        })?;
+        read.string("ancestor_key_separator", &mut config.keys.ancestor_key_separator)?;
+        read.string("promoted_attr_collision_suffix", &mut config.attributes.promoted_attr_collision_suffix)?;
+
+        read.optional("key_length_limit", &mut config.keys.key_length_limit, |v| {
+            v.as_u64().map(|n| n as usize)
+        })?;
+        read.optional("max_json_depth", &mut config.output.max_json_depth, |v| v.as_u64().map(|n| n as usize))?;
+        if let Some(sentinel) = options.get("json_depth_sentinel") {
+            config.output.json_depth_sentinel = sentinel.clone();
+        }
+        read.optional("max_xml_depth", &mut config.input.max_xml_depth, |v| v.as_u64().map(|n| n as usize))?;
+        read.optional("xml_attr_array_separator", &mut config.attributes.xml_attr_array_separator, |v| {
+            v.as_str().map(str::to_owned)
+        })?;
+        read.optional("allowed_versions", &mut config.input.allowed_versions, string_list)?;
+        read.optional("embed_config", &mut config.injection.embed_config, |v| v.as_str().map(str::to_owned))?;
+        read.value("trace_paths", &mut config.diagnostics.trace_paths, string_list)?;
+        read.value("forced_array_paths", &mut config.arrays.forced_array_paths, |v| {
+            string_list(v).map(|paths| paths.into_iter().collect())
+        })?;
+        read.optional("force_array_above_child_count", &mut config.arrays.force_array_above_child_count, |v| {
+            v.as_u64().map(|n| n as usize)
+        })?;
+        read.value("always_array_elements", &mut config.arrays.always_array_elements, |v| {
+            string_list(v).map(|names| names.into_iter().collect())
+        })?;
+        read.value("plural_wrapper_exclusions", &mut config.arrays.plural_wrapper_exclusions, string_list)?;
+        read.value("plural_pairs", &mut config.arrays.plural_pairs, |v| {
+            string_map(v, |singular| singular.as_str().map(str::to_owned))
+        })?;
+        read.value("absent_field_policy", &mut config.injection.absent_field_policy, |v| {
+            string_map(v, |policy| match policy.as_str() {
+                Some("Omit") => Some(AbsentPolicy::Omit),
+                Some("Null") => Some(AbsentPolicy::Null),
+                _ => None,
+            })
+        })?;
+
+        read.value("inference", &mut config.values.inference, |v| {
+            let flag = |name: &str| v.get(name).and_then(Value::as_bool);
+            Some(InferenceFlags {
+                numbers: flag("numbers")?,
+                booleans: flag("booleans")?,
+                nulls: flag("nulls")?,
+            })
+        })?;
+
+        read.variant("profile", &mut config.profile, |variant, _| match variant {
+            "V0_6" => Some(ConversionProfile::V0_6),
+            "Latest" => Some(ConversionProfile::Latest),
+            _ => None,
+        })?;
+        read.variant("empty_element_handling", &mut config.empty_element_handling, |variant, _| {
+            match variant {
+                "Ignore" => Some(NullValue::Ignore),
+                "Null" => Some(NullValue::Null),
+                "EmptyObject" => Some(NullValue::EmptyObject),
+                "EmptyString" => Some(NullValue::EmptyString),
+                _ => None,
+            }
+        })?;
+        read.variant("injected_fields_on_clash", &mut config.injection.injected_fields_on_clash, |variant, _| {
+            match variant {
+                "Error" => Some(NodeIdClash::Error),
+                "KeepExisting" => Some(NodeIdClash::KeepExisting),
+                "Overwrite" => Some(NodeIdClash::Overwrite),
+                _ => None,
+            }
+        })?;
+        read.variant("context_placement", &mut config.injection.context_placement, |variant, _| match variant {
+            "Root" => Some(ContextPlacement::Root),
+            "Children" => Some(ContextPlacement::Children),
+            _ => None,
+        })?;
+        read.variant("text_prop_collision", &mut config.text.text_prop_collision, |variant, value| {
+            match variant {
+                "Suffix" => Some(TextPropCollision::Suffix(value?.as_str()?.to_owned())),
+                "Error" => Some(TextPropCollision::Error),
+                _ => None,
+            }
+        })?;
+        read.variant("text_join", &mut config.text.text_join, |variant, value| match variant {
+            "Concat" => Some(TextJoin::Concat),
+            "Space" => Some(TextJoin::Space),
+            "Separator" => Some(TextJoin::Separator(value?.as_str()?.to_owned())),
+            "KeepSegments" => Some(TextJoin::KeepSegments),
+            _ => None,
+        })?;
+        read.variant("tail_text_handling", &mut config.text.tail_text_handling, |variant, value| {
+            match variant {
+                "AppendToParent" => Some(TailTextHandling::AppendToParent),
+                "Ignore" => Some(TailTextHandling::Ignore),
+                "AsField" => Some(TailTextHandling::AsField(value?.as_str()?.to_owned())),
+                _ => None,
+            }
+        })?;
+        read.variant("namespace_default_handling", &mut config.input.namespace_default_handling, |variant, value| {
+            match variant {
+                "Ignore" => Some(NamespaceDefaultHandling::Ignore),
+                "AddToElement" => Some(NamespaceDefaultHandling::AddToElement(value?.as_str()?.to_owned())),
+                "PrefixKeys" => Some(NamespaceDefaultHandling::PrefixKeys(value?.as_str()?.to_owned())),
+                _ => None,
+            }
+        })?;
+        read.variant("duplicate_source_attributes", &mut config.attributes.duplicate_source_attributes, |variant, _| {
+            match variant {
+                "First" => Some(DupAttr::First),
+                "Last" => Some(DupAttr::Last),
+                "MergeArray" => Some(DupAttr::MergeArray),
+                "Error" => Some(DupAttr::Error),
+                _ => None,
+            }
+        })?;
+        read.variant("key_charset", &mut config.keys.key_charset, |variant, value| match variant {
+            "AllowAll" => Some(KeyCharset::AllowAll),
+            "AsciiOnly" => Some(KeyCharset::AsciiOnly {
+                replacement: value?.get("replacement")?.as_str()?.to_owned(),
+            }),
+            "ErrorOnNonAscii" => Some(KeyCharset::ErrorOnNonAscii),
+            _ => None,
+        })?;
+        read.variant("json_string_policy", &mut config.values.json_string_policy, |variant, value| {
+            match variant {
+                "Keep" => Some(JsonStringPolicy::Keep),
+                "StripControls" => Some(JsonStringPolicy::StripControls),
+                "ReplaceWith" => value?.as_str()?.chars().next().map(JsonStringPolicy::ReplaceWith),
+                "Error" => Some(JsonStringPolicy::Error),
+                _ => None,
+            }
+        })?;
+        read.optional("processing_instructions_key", &mut config.text.processing_instructions_key, |v| {
+            v.as_str().map(str::to_owned)
+        })?;
+        read.variant("comment_handling", &mut config.text.comment_handling, |variant, value| match variant {
+            "Ignore" => Some(CommentHandling::Ignore),
+            "PreserveAsField" => Some(CommentHandling::PreserveAsField(value?.as_str()?.to_owned())),
+            "PreserveAsArray" => Some(CommentHandling::PreserveAsArray(value?.as_str()?.to_owned())),
+            _ => None,
+        })?;
+        read.variant("promote_attributes", &mut config.attributes.promote_attributes, |variant, value| {
+            match variant {
+                "None" => Some(PromoteAttrs::None),
+                "All" => Some(PromoteAttrs::All),
+                "Named" => string_list(value?).map(PromoteAttrs::Named),
+                "Paths" => string_list(value?).map(PromoteAttrs::Paths),
+                _ => None,
+            }
+        })?;
+
+        #[cfg(feature = "json_types")]
+        {
+            read.variant("path_anchor", &mut config.path_anchor, |variant, _| match variant {
+                "Root" => Some(PathAnchor::Root),
+                "Anywhere" => Some(PathAnchor::Anywhere),
+                _ => None,
+            })?;
+            read.value("enum_mappings", &mut config.enum_mappings, |v| string_map(v, string_list))?;
+            read.value("tuple_rules", &mut config.tuple_rules, |v| string_map(v, string_list))?;
+        }
+        // the options of the record can't be dropped silently
+        #[cfg(not(feature = "json_types"))]
+        {
+            if options.get("path_anchor").is_some_and(|anchor| *anchor != "Root") {
+                return Err(missing_feature("path_anchor", "json_types"));
+            }
+            for name in &["enum_mappings", "tuple_rules"] {
+                if options.get(*name).and_then(Value::as_object).is_some_and(|map| !map.is_empty()) {
+                    return Err(missing_feature(name, "json_types"));
+                }
+            }
+        }
+        #[cfg(feature = "encoding")]
+        read.variant("output_encoding", &mut config.output.output_encoding, |variant, value| match variant {
+            "Utf8" => Some(OutputEncoding::Utf8),
+            "Iso8859_1" => Some(OutputEncoding::Iso8859_1),
+            "Other" => OutputEncoding::for_label(value?.as_str()?),
+            _ => None,
+        })?;
+        #[cfg(not(feature = "encoding"))]
+        {
+            if options.get("output_encoding").is_some_and(|encoding| *encoding != "Utf8") {
+                return Err(missing_feature("output_encoding", "encoding"));
+            }
+        }
+        #[cfg(feature = "spill")]
+        read.optional("spill", &mut config.output.spill, |v| {
+            let threshold_members = v.get("threshold_members")?.as_u64()? as usize;
+            Some(SpillConfig::new(threshold_members, v.get("temp_dir")?.as_str()?))
+        })?;
+        #[cfg(not(feature = "spill"))]
+        {
+            if options.get("spill").is_some_and(|spill| !spill.is_null()) {
+                return Err(missing_feature("spill", "spill"));
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Reads the options of the record into the fields of the config. Missing options are skipped.
+struct Reader<'a> {
+    options: &'a Map<String, Value>,
+}
+
+impl<'a> Reader<'a> {
+    fn value<T, F>(&self, name: &str, field: &mut T, parse: F) -> Result<(), ConversionError>
+    where
+        F: Fn(&Value) -> Option<T>,
+    {
+        if let Some(value) = self.options.get(name) {
+            *field = parse(value).ok_or_else(|| invalid(name))?;
+        }
+        Ok(())
+    }
+
+    fn optional<T, F>(&self, name: &str, field: &mut Option<T>, parse: F) -> Result<(), ConversionError>
+    where
+        F: Fn(&Value) -> Option<T>,
+    {
+        self.value(name, field, |value| match value {
+            Value::Null => Some(None),
+            value => parse(value).map(Some),
+        })
+    }
+
+    fn bool(&self, name: &str, field: &mut bool) -> Result<(), ConversionError> {
+        self.value(name, field, Value::as_bool)
+    }
+
+    fn string(&self, name: &str, field: &mut String) -> Result<(), ConversionError> {
+        self.value(name, field, |value| value.as_str().map(str::to_owned))
+    }
+
+    /// Reads an enum variant written as `"Name"` or `{"Name":value}`.
+    fn variant<T, F>(&self, name: &str, field: &mut T, parse: F) -> Result<(), ConversionError>
+    where
+        F: Fn(&str, Option<&Value>) -> Option<T>,
+    {
+        self.value(name, field, |value| match value {
+            Value::String(variant) => parse(variant, None),
+            Value::Object(map) if map.len() == 1 => map.iter().next().and_then(|(k, v)| parse(k, Some(v))),
+            _ => None,
+        })
+    }
+}
+
+fn string_list(value: &Value) -> Option<Vec<String>> {
+    value.as_array()?.iter().map(|v| v.as_str().map(str::to_owned)).collect()
+}
+
+fn string_map<T, F: Fn(&Value) -> Option<T>>(value: &Value, parse: F) -> Option<HashMap<String, T>> {
+    value.as_object()?.iter().map(|(k, v)| parse(v).map(|v| (k.clone(), v))).collect()
+}
+
+fn invalid(name: &str) -> ConversionError {
+    ConversionError::InvalidEmbeddedConfig(format!("invalid value of {}", name))
+}
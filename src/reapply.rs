@@ -0,0 +1,129 @@
+//! Applies the changes between two configs to documents converted before, without the XML.
+
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+use super::provenance::embedded_config;
+use super::{Config, ConversionError};
+
+/// The options changed by `reapply`, listed by the names of the `Config` fields.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReapplyReport {
+    /// The changes applied to the document
+    pub applied: Vec<String>,
+    /// The changes which need the XML, e.g. a change of `ValueOptions::inference` for values which were converted
+    /// into numbers already. Convert the document again with the new config if the list is not empty.
+    pub requires_reconversion: Vec<String>,
+}
+
+/// Updates the document converted with the `old` config to the output of the `new` config as far as possible
+/// without the XML, e.g. for archived documents when only the attribute prefix changed. The changes are
+/// detected by comparing the options written by `InjectionOptions::embed_config`, and only the renames of
+/// `xml_attr_prefix`, `xml_text_node_prop_name`, `child_order_key` and `content_key` are applied. The other changes,
+/// and renames clashing with other properties of the same object, are reported as requiring re-conversion.
+/// Functions like `InjectionOptions::interceptor` are assumed to be unchanged if both configs have them.
+///
+/// The embedded config of the old document is replaced with the new one only if all changes were applied.
+/// Returns `ConversionError::UnsupportedJson` if the document is not an object.
+pub fn reapply(value: &mut Value, old: &Config, new: &Config) -> Result<ReapplyReport, ConversionError> {
+    let root = match value {
+        Value::Object(root) => root,
+        _ => {
+            return Err(ConversionError::UnsupportedJson {
+                path: "/".to_owned(),
+                reason: "the converted document is not an object".to_owned(),
+            })
+        }
+    };
+    let embedded = old.injection.embed_config.as_ref().and_then(|key| root.remove(key));
+
+    let old_record = embedded_config(old);
+    let new_record = embedded_config(new);
+    let mut report = ReapplyReport::default();
+    for section in &["options", "unrestored"] {
+        let new_options = &new_record[*section];
+        for (name, old_value) in old_record[*section].as_object().into_iter().flatten() {
+            if new_options[name.as_str()] == *old_value {
+                continue;
+            }
+            if rename(root, name, old, new) {
+                report.applied.push(name.clone());
+            } else {
+                report.requires_reconversion.push(name.clone());
+            }
+        }
+    }
+    if old_record["elided"] != new_record["elided"] {
+        report.requires_reconversion.push("elided".to_owned());
+    }
+
+    if report.requires_reconversion.is_empty() {
+        if let Some(key) = &new.injection.embed_config {
+            root.insert(key.clone(), new_record);
+        }
+    } else if let (Some(key), Some(embedded)) = (&old.injection.embed_config, embedded) {
+        root.insert(key.clone(), embedded);
+    }
+    Ok(report)
+}
+
+/// Applies the change of the option if it only renames properties, returns `false` if it can't be applied.
+fn rename(root: &mut Map<String, Value>, name: &str, old: &Config, new: &Config) -> bool {
+    let (from, to) = match name {
+        // without a prefix the attributes can't be told apart from the child elements
+        "xml_attr_prefix" if !old.xml_attr_prefix.is_empty() => (&old.xml_attr_prefix, &new.xml_attr_prefix),
+        "xml_text_node_prop_name" => (&old.xml_text_node_prop_name, &new.xml_text_node_prop_name),
+        // without the option the document has no positions to rename
+        "child_order_key" if !old.injection.preserve_child_order => return true,
+        "child_order_key" => (&old.injection.child_order_key, &new.injection.child_order_key),
+        "content_key" if !old.text.ordered_content => return true,
+        "content_key" => (&old.text.content_key, &new.text.content_key),
+        _ => return false,
+    };
+
+    let renamed = |key: &str| -> Option<String> {
+        if name == "xml_attr_prefix" {
+            key.strip_prefix(from.as_str()).map(|attr| [to.as_str(), attr].concat())
+        } else if key == from {
+            Some(to.clone())
+        } else {
+            None
+        }
+    };
+    let mut root_value = Value::Object(std::mem::take(root));
+    let clash = has_clash(&root_value, &renamed);
+    if !clash {
+        rename_keys(&mut root_value, &renamed);
+    }
+    if let Value::Object(map) = root_value {
+        *root = map;
+    }
+    !clash
+}
+
+/// Returns `true` if any object would get two properties with the same name.
+fn has_clash(value: &Value, renamed: &dyn Fn(&str) -> Option<String>) -> bool {
+    match value {
+        Value::Object(map) => {
+            let mut keys = HashSet::new();
+            map.iter().any(|(key, value)| {
+                !keys.insert(renamed(key).unwrap_or_else(|| key.clone())) || has_clash(value, renamed)
+            })
+        }
+        Value::Array(items) => items.iter().any(|item| has_clash(item, renamed)),
+        _ => false,
+    }
+}
+
+fn rename_keys(value: &mut Value, renamed: &dyn Fn(&str) -> Option<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, mut value) in std::mem::take(map) {
+                rename_keys(&mut value, renamed);
+                map.insert(renamed(&key).unwrap_or(key), value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rename_keys(item, renamed)),
+        _ => (),
+    }
+}
@@ -0,0 +1,107 @@
+//! Validation of the converted documents against a JSON Schema.
+//! Requires `schema_validation` feature which uses [jsonschema](https://github.com/Stranger6667/jsonschema).
+
+use serde_json::Value;
+use std::fmt;
+
+use super::{xml_str_to_json, Config, ConversionError};
+
+/// The error of `xml_string_to_validated_json`.
+#[derive(Debug)]
+pub enum ValidationError {
+    /// The document could not be converted.
+    Conversion(ConversionError),
+    /// The schema itself is not valid.
+    InvalidSchema(String),
+    /// The converted document doesn't match the schema. `pointer` is the JSON Pointer of the value reported
+    /// by the validator, e.g. `/order/item/1/@qty`, and `xml_path` the XML path of the node it was
+    /// converted from, e.g. `/order/item/@qty`.
+    Invalid {
+        pointer: String,
+        xml_path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::Conversion(e) => write!(f, "{}", e),
+            ValidationError::InvalidSchema(e) => write!(f, "invalid JSON Schema: {}", e),
+            ValidationError::Invalid {
+                pointer,
+                xml_path,
+                message,
+            } => write!(f, "the value at {} (XML {}) is invalid: {}", pointer, xml_path, message),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ConversionError> for ValidationError {
+    fn from(e: ConversionError) -> Self {
+        ValidationError::Conversion(e)
+    }
+}
+
+/// Converts the given XML string into `serde::Value` using settings from `Config` struct and validates
+/// the result against the JSON Schema. Only the first validation error is reported, together with the XML path
+/// of the invalid value, e.g. a number where the schema expects a string because the type inference
+/// turned `<zip>01234</zip>` into a number.
+///
+/// The XML path is derived from the JSON Pointer: array indices of repeated elements are dropped, keys with
+/// `Config::xml_attr_prefix` become attributes and `Config::xml_text_node_prop_name` stands for the text
+/// of its element. With an empty attribute prefix the attributes are reported as child elements.
+pub fn xml_string_to_validated_json(xml: &str, config: &Config, schema: &Value) -> Result<Value, ValidationError> {
+    let validator = jsonschema::validator_for(schema).map_err(|e| ValidationError::InvalidSchema(e.to_string()))?;
+    let json = xml_str_to_json(xml, config)?;
+
+    let error = match validator.validate(&json) {
+        Ok(()) => None,
+        Err(e) => Some((e.instance_path.to_string(), e.to_string())),
+    };
+    match error {
+        None => Ok(json),
+        Some((pointer, message)) => Err(ValidationError::Invalid {
+            xml_path: pointer_to_xml_path(&json, &pointer, config),
+            pointer,
+            message,
+        }),
+    }
+}
+
+/// Follows the JSON Pointer through the converted document and returns the XML path of the value.
+fn pointer_to_xml_path(json: &Value, pointer: &str, config: &Config) -> String {
+    let mut xml_path = String::new();
+    let mut value = Some(json);
+
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        value = match value {
+            // the items of an array share the path of the repeated element
+            Some(Value::Array(items)) => {
+                value = segment.parse::<usize>().ok().and_then(|index| items.get(index));
+                continue;
+            }
+            Some(Value::Object(map)) => map.get(&segment),
+            _ => None,
+        };
+
+        if segment == config.xml_text_node_prop_name {
+            continue;
+        }
+        match segment.strip_prefix(config.xml_attr_prefix.as_str()) {
+            Some(attr) if !config.xml_attr_prefix.is_empty() => {
+                xml_path.push_str("/@");
+                xml_path.push_str(attr);
+            }
+            _ => {
+                xml_path.push('/');
+                xml_path.push_str(&segment);
+            }
+        }
+    }
+
+    xml_path
+}
@@ -0,0 +1,109 @@
+//! Snapshot tests for a directory of XML files, e.g. a corpus of real-world documents.
+//! Every file is converted with each of the given configs and compared with the JSON committed next to it,
+//! so any change to the output fails the test. Requires `test_utils` feature.
+
+use serde_json::Value;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::diff::{diff, DiffOptions};
+use super::{xml_str_to_json, Config};
+
+/// Set this environment variable to write the current output into the snapshot files instead of
+/// comparing them, e.g. `UPDATE_SNAPSHOTS=1 cargo test`. Review the changes with `git diff` before committing.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Converts every `.xml` file in `dir` with each of the named configs and compares the output with
+/// `<dir>/snapshots/<config name>/<file name>.json`. Panics with a report listing the differences
+/// of all files that don't match, as well as conversion errors and missing snapshots.
+/// # Example
+/// ```no_run
+/// use quickxml_to_serde::{snapshot::assert_snapshot_dir, Config};
+///
+/// let mut custom = Config::new_with_defaults();
+/// custom.xml_attr_prefix = "_".to_owned();
+/// assert_snapshot_dir("tests/corpus", &[("defaults", Config::new_with_defaults()), ("custom", custom)]);
+/// ```
+pub fn assert_snapshot_dir<P: AsRef<Path>>(dir: P, configs: &[(&str, Config)]) {
+    let dir = dir.as_ref();
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some();
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("can't read {:?}: {}", dir, e))
+        .map(|entry| entry.expect("directory entry").path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    entries.sort();
+    assert!(!entries.is_empty(), "no XML files in {:?}", dir);
+
+    let mut report = String::new();
+    let mut failures = 0;
+
+    for (name, config) in configs {
+        let snapshot_dir = dir.join("snapshots").join(name);
+        for entry in &entries {
+            let snapshot =
+                snapshot_dir.join(entry.with_extension("json").file_name().expect("file name"));
+            let result = match check_file(entry, &snapshot, config, update) {
+                Ok(()) => continue,
+                Err(result) => result,
+            };
+            failures += 1;
+            let _ = writeln!(report, "{:?} with `{}`: {}", entry, name, result);
+        }
+    }
+
+    if failures > 0 {
+        panic!(
+            "{} snapshot(s) don't match, run the tests with {}=1 to accept the current output:\n{}",
+            failures, UPDATE_SNAPSHOTS_VAR, report
+        );
+    }
+}
+
+/// Compares the conversion of the file with the snapshot, or updates the snapshot.
+/// Returns the description of the mismatch.
+fn check_file(
+    xml_file: &Path,
+    snapshot: &Path,
+    config: &Config,
+    update: bool,
+) -> Result<(), String> {
+    let xml = fs::read_to_string(xml_file).map_err(|e| format!("can't read the file: {}", e))?;
+    let json = xml_str_to_json(&xml, config).map_err(|e| format!("conversion failed: {}", e))?;
+    let expected: Option<Value> = fs::read_to_string(snapshot)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+
+    match expected {
+        Some(ref expected) if expected == &json => Ok(()),
+        _ if update => {
+            let written = snapshot
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| {
+                    fs::write(
+                        snapshot,
+                        serde_json::to_string_pretty(&json).expect("JSON") + "\n",
+                    )
+                });
+            written.map_err(|e| format!("can't write {:?}: {}", snapshot, e))
+        }
+        None => Err(format!("missing or malformed snapshot {:?}", snapshot)),
+        Some(expected) => {
+            let mut description = "the output changed:".to_owned();
+            for entry in diff(&expected, &json, &DiffOptions::new()) {
+                let _ = write!(
+                    description,
+                    "\n  {:?} {}: {} -> {}",
+                    entry.kind,
+                    entry.path,
+                    entry.old.map_or("-".to_owned(), |v| v.to_string()),
+                    entry.new.map_or("-".to_owned(), |v| v.to_string())
+                );
+            }
+            Err(description)
+        }
+    }
+}
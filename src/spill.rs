@@ -0,0 +1,302 @@
+//! Conversion of documents with very large repeat groups, which are moved to temporary files while converting
+//! and written into the output from there, see `OutputOptions::spill`. Requires `spill` feature.
+
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::progress::CountingReader;
+use super::{parse_xml, prolog_nodes, xml_to_map, CommentHandling, Config, Context, ConversionError, Hooks};
+
+/// The number of the next `SpillFiles`, so conversions running at the same time don't share file names.
+static NEXT_CONVERSION: AtomicUsize = AtomicUsize::new(0);
+
+/// Moves arrays of repeated child elements with more than `threshold_members` members to temporary files
+/// in `temp_dir`, see `OutputOptions::spill`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpillConfig {
+    pub threshold_members: usize,
+    pub temp_dir: PathBuf,
+}
+
+impl SpillConfig {
+    /// Creates a spill config for arrays with more than `threshold_members` members.
+    pub fn new<P: Into<PathBuf>>(threshold_members: usize, temp_dir: P) -> Self {
+        SpillConfig {
+            threshold_members,
+            temp_dir: temp_dir.into(),
+        }
+    }
+}
+
+/// Reads the XML document from the reader, converts it and writes the JSON text into the writer.
+/// The output is the same as `serde_json::to_writer` of `xml_reader_to_json`.
+///
+/// With `OutputOptions::spill` the arrays of repeated child elements with more than `SpillConfig::threshold_members`
+/// members are written into temporary files while converting, so only the rest of the output is kept
+/// in memory, and are copied into the output from there. The document itself is still read into memory.
+/// The files are removed once the output was written, the conversion failed or panicked.
+/// Besides the repeated child elements, the arrays of `ArrayOptions::auto_flatten_plural_wrappers` and
+/// `TextOptions::ordered_content` are spilled as well.
+/// `OutputOptions::emit_type_annotations`, `OutputOptions::max_json_depth`,
+/// `DiagnosticOptions::array_homogeneity_check` and the interceptors of the parent elements don't see the members of
+/// the spilled arrays, so they can't be combined with spilling and return `ConversionError::SpillUnsupported`.
+pub fn xml_to_json_writer<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    config: &Config,
+) -> Result<(), ConversionError> {
+    let spill = match config.output.spill {
+        Some(ref spill) => spill,
+        None => {
+            let value = super::xml_reader_to_json(reader, config)?;
+            serde_json::to_writer(&mut writer, &value).map_err(io::Error::from)?;
+            return Ok(writer.flush()?);
+        }
+    };
+    if config.output.emit_type_annotations {
+        return Err(unsupported("emit_type_annotations"));
+    }
+    if config.output.max_json_depth.is_some() {
+        return Err(unsupported("max_json_depth"));
+    }
+    if config.injection.interceptor.is_some() {
+        return Err(unsupported("interceptor"));
+    }
+    if config.diagnostics.array_homogeneity_check {
+        return Err(unsupported("array_homogeneity_check"));
+    }
+
+    let mut reader = CountingReader::new(reader, config);
+    let mut xml = String::new();
+    reader.read_to_string(&mut xml)?;
+    let doc = parse_xml(&xml, config)?;
+    // the parsers drop the nodes before the root element
+    let keeps_prolog = config.text.comment_handling != CommentHandling::Ignore
+        || config.text.processing_instructions_key.is_some();
+    let (comments, instructions) = if keeps_prolog {
+        prolog_nodes(&xml)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let hooks = Hooks {
+        prolog_comments: &comments,
+        prolog_instructions: &instructions,
+        side_table: Some(&doc.side_table),
+        ..Hooks::default()
+    };
+
+    let mut ctx = Context {
+        spill: Some(SpillFiles::new(spill)),
+        ..Context::default()
+    };
+    let value = xml_to_map(&doc.root, config, &mut ctx, &hooks)?;
+    drop(comments);
+    drop(instructions);
+    drop(doc);
+    drop(xml);
+
+    // the guard removes the files when it goes out of scope, including on errors and panics
+    let mut files = ctx.spill.take().expect("the spill files");
+    files.flush()?;
+    write_value(&value, &mut writer, &files)?;
+    writer.flush()?;
+    reader.tracker.finish(ctx.elements, 1);
+    Ok(())
+}
+
+fn unsupported(option: &str) -> ConversionError {
+    ConversionError::SpillUnsupported {
+        option: option.to_owned(),
+    }
+}
+
+/// The temporary files of the arrays spilled during a single conversion. Removes them when dropped.
+pub(crate) struct SpillFiles {
+    temp_dir: PathBuf,
+    conversion: usize,
+    /// The start of the placeholder strings of this conversion, followed by the number of the file.
+    /// It is random, so no string of the document or the config can be taken for a spilled array.
+    token: String,
+    files: Vec<SpillFile>,
+}
+
+/// The file of a spilled array with the number of members written so far.
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    members: usize,
+}
+
+impl SpillFiles {
+    fn new(config: &SpillConfig) -> Self {
+        SpillFiles {
+            temp_dir: config.temp_dir.clone(),
+            conversion: NEXT_CONVERSION.fetch_add(1, Ordering::Relaxed),
+            token: format!("spill-{:016x}{:016x}:", random(), random()),
+            files: Vec::new(),
+        }
+    }
+
+    /// Writes the members into a new file and returns the placeholder of the array.
+    fn spill(&mut self, members: &[Value]) -> io::Result<Value> {
+        let name = format!(
+            "quickxml_to_serde-{}-{}-{}.json",
+            std::process::id(),
+            self.conversion,
+            self.files.len()
+        );
+        let path = self.temp_dir.join(name);
+        let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+        self.files.push(SpillFile {
+            path,
+            writer: BufWriter::new(file),
+            members: 0,
+        });
+        let id = self.files.len() - 1;
+        for member in members {
+            self.append(id, member)?;
+        }
+        Ok(Value::String(format!("{}{}", self.token, id)))
+    }
+
+    /// Writes another member of the array into its file. The arrays spilled inside of the member are complete
+    /// by now, so they are copied into it.
+    fn append(&mut self, id: usize, member: &Value) -> io::Result<()> {
+        let mut nested = Vec::new();
+        find_spilled(member, self, &mut nested);
+        for nested in nested {
+            self.files[nested].writer.flush()?;
+        }
+        let mut json = Vec::new();
+        write_value(member, &mut json, self)?;
+
+        let file = &mut self.files[id];
+        if file.members > 0 {
+            file.writer.write_all(b",")?;
+        }
+        file.members += 1;
+        file.writer.write_all(&json)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.files.iter_mut().try_for_each(|file| file.writer.flush())
+    }
+
+    /// Returns the number of the file if the value is the placeholder of an array spilled by this conversion.
+    fn spilled_id(&self, value: &Value) -> Option<usize> {
+        let id = value.as_str()?.strip_prefix(self.token.as_str())?.parse().ok()?;
+        if id < self.files.len() {
+            Some(id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns a random number from the randomly seeded hasher of the standard library.
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+impl Drop for SpillFiles {
+    fn drop(&mut self) {
+        for file in self.files.drain(..) {
+            // the file is closed first, open files can't be removed on some platforms
+            drop(file.writer.into_parts());
+            let _ = fs::remove_file(file.path);
+        }
+    }
+}
+
+/// Collects the numbers of the files of the arrays spilled inside of the value.
+fn find_spilled(value: &Value, files: &SpillFiles, ids: &mut Vec<usize>) {
+    match value {
+        Value::Object(map) => map.values().for_each(|value| find_spilled(value, files, ids)),
+        Value::Array(items) => items.iter().for_each(|item| find_spilled(item, files, ids)),
+        _ => ids.extend(files.spilled_id(value)),
+    }
+}
+
+/// Adds the member to the spilled array of the value and returns `true`, or returns `false` if the value
+/// is not a spilled array.
+pub(crate) fn append_spilled(value: &Value, member: &Value, ctx: &mut Context) -> Result<bool, ConversionError> {
+    let files = match ctx.spill.as_mut() {
+        Some(files) => files,
+        None => return Ok(false),
+    };
+    match files.spilled_id(value) {
+        Some(id) => {
+            files.append(id, member)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Replaces the array with a placeholder once it has more members than `SpillConfig::threshold_members`.
+/// Returns `ConversionError::SpillRequired` outside of `xml_to_json_writer`.
+pub(crate) fn spill_array(
+    value: &mut Value,
+    name: &str,
+    config: &Config,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    let threshold_members = match config.output.spill {
+        Some(ref spill) => spill.threshold_members,
+        None => return Ok(()),
+    };
+    let members = match value {
+        Value::Array(members) if members.len() > threshold_members => members,
+        _ => return Ok(()),
+    };
+    match ctx.spill.as_mut() {
+        Some(files) => {
+            *value = files.spill(members)?;
+            Ok(())
+        }
+        None => Err(ConversionError::SpillRequired {
+            path: format!("{}/{}", ctx.path, name),
+        }),
+    }
+}
+
+/// Writes the value as JSON text, with the spilled arrays copied from their files.
+fn write_value<W: Write>(value: &Value, writer: &mut W, files: &SpillFiles) -> io::Result<()> {
+    match value {
+        Value::Object(map) => {
+            writer.write_all(b"{")?;
+            for (index, (key, value)) in map.iter().enumerate() {
+                if index > 0 {
+                    writer.write_all(b",")?;
+                }
+                serde_json::to_writer(&mut *writer, key)?;
+                writer.write_all(b":")?;
+                write_value(value, writer, files)?;
+            }
+            writer.write_all(b"}")
+        }
+        Value::Array(items) => {
+            writer.write_all(b"[")?;
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    writer.write_all(b",")?;
+                }
+                write_value(item, writer, files)?;
+            }
+            writer.write_all(b"]")
+        }
+        _ => match files.spilled_id(value).map(|id| &files.files[id]) {
+            Some(file) => {
+                writer.write_all(b"[")?;
+                io::copy(&mut File::open(&file.path)?, writer)?;
+                writer.write_all(b"]")
+            }
+            None => Ok(serde_json::to_writer(writer, value)?),
+        },
+    }
+}
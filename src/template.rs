@@ -0,0 +1,112 @@
+//! Filling JSON templates with values extracted from the converted XML.
+
+use serde_json::Value;
+
+use super::{xml_string_to_json, Config, ConversionError};
+
+/// Converts the given XML string using settings from `Config` struct and fills the JSON template with
+/// the values at the XML paths of its `{{path}}` placeholders, e.g. `{"total": "{{/orders/order/total}}"}`.
+///
+/// A string consisting of a single placeholder is replaced with the value as-is, keeping its JSON type,
+/// while placeholders inside longer strings are replaced with their text, e.g. `"Order {{/order/@id}}"`.
+/// Placeholders can be anywhere in the template, including nested objects and arrays, but not in property names.
+///
+/// The paths use the syntax of `json_type_overrides`: element names and attribute names prefixed with `@`.
+/// The path of an element with attributes refers to its text. Repeated elements resolve to the first one,
+/// e.g. `/orders/order/@id` is the id of the first order.
+/// An invalid template returns `ConversionError::InvalidTemplate` and a path not found in the document
+/// returns `ConversionError::MissingTemplatePath`.
+pub fn xml_string_to_json_template(
+    xml: String,
+    template: &str,
+    config: &Config,
+) -> Result<Value, ConversionError> {
+    // an invalid template fails before spending any time on the conversion
+    let mut template: Value =
+        serde_json::from_str(template).map_err(|e| ConversionError::InvalidTemplate(e.to_string()))?;
+    let json = xml_string_to_json(xml, config)?;
+    fill(&mut template, &json, config)?;
+    Ok(template)
+}
+
+/// Replaces the placeholders in the strings of the template.
+fn fill(template: &mut Value, json: &Value, config: &Config) -> Result<(), ConversionError> {
+    match template {
+        Value::String(s) => {
+            if let Some(filled) = fill_string(s, json, config)? {
+                *template = filled;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                fill(item, json, config)?;
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                fill(value, json, config)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Returns the string with its placeholders replaced or `None` if it has none.
+fn fill_string(s: &str, json: &Value, config: &Config) -> Result<Option<Value>, ConversionError> {
+    if !s.contains("{{") {
+        return Ok(None);
+    }
+
+    // a single placeholder keeps the type of the value
+    if let Some(path) = s.strip_prefix("{{").and_then(|rest| rest.strip_suffix("}}")) {
+        if !path.contains("{{") && !path.contains("}}") {
+            return lookup(path, json, config).map(Some);
+        }
+    }
+
+    let mut filled = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..].find("}}").ok_or_else(|| {
+            ConversionError::InvalidTemplate(format!("unterminated placeholder in {:?}", s))
+        })?;
+        filled.push_str(&rest[..start]);
+        match lookup(&rest[start + 2..start + end], json, config)? {
+            Value::String(text) => filled.push_str(&text),
+            value => filled.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    filled.push_str(rest);
+    Ok(Some(Value::String(filled)))
+}
+
+/// Returns the value at the XML path in the converted document.
+fn lookup(path: &str, json: &Value, config: &Config) -> Result<Value, ConversionError> {
+    let missing = || ConversionError::MissingTemplatePath {
+        path: path.trim().to_owned(),
+    };
+
+    let mut value = json;
+    for step in path.trim().split('/').filter(|step| !step.is_empty()) {
+        // repeated elements resolve to the first one
+        if let Value::Array(items) = value {
+            value = items.first().ok_or_else(missing)?;
+        }
+        let key = match step.strip_prefix('@') {
+            Some(attr) => [config.xml_attr_prefix.as_str(), attr].concat(),
+            None => step.to_owned(),
+        };
+        value = value.get(&key).ok_or_else(missing)?;
+    }
+
+    if let Value::Array(items) = value {
+        value = items.first().ok_or_else(missing)?;
+    }
+    // elements with attributes keep their text in a property
+    if let Some(text) = value.get(&config.xml_text_node_prop_name) {
+        value = text;
+    }
+    Ok(value.clone())
+}
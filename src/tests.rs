@@ -1,7 +1,5 @@
 use super::*;
-use serde_json::{json, to_string_pretty};
-use std::fs::File;
-use std::io::prelude::*;
+use serde_json::json;
 
 #[test]
 fn test_numbers() {
@@ -24,17 +22,17 @@ fn test_empty_elements_valid() {
     let xml = r#"<a b="1"><x/></a>"#;
 
     let expected = json!({ "a": {"b":1, "x":{}} });
-    let result = xml_string_to_json(xml.to_owned(), &conf);
+    let result = xml_string_to_json(xml, &conf);
     assert_eq!(expected, result.unwrap());
 
     conf.empty_element_handling = NullValue::Null;
     let expected = json!({ "a": {"b":1, "x":null} });
-    let result = xml_string_to_json(xml.to_owned(), &conf);
+    let result = xml_string_to_json(xml, &conf);
     assert_eq!(expected, result.unwrap());
 
     conf.empty_element_handling = NullValue::Ignore;
     let expected = json!({ "a": {"b":1} });
-    let result = xml_string_to_json(xml.to_owned(), &conf);
+    let result = xml_string_to_json(xml, &conf);
     assert_eq!(expected, result.unwrap());
 }
 
@@ -44,11 +42,11 @@ fn test_empty_elements_invalid() {
     let expected = json!({ "a": null });
 
     let xml = r#"<a><x/></a>"#;
-    let result = xml_string_to_json(xml.to_owned(), &conf);
+    let result = xml_string_to_json(xml, &conf);
     assert_eq!(expected, result.unwrap());
 
     let xml = r#"<a />"#;
-    let result = xml_string_to_json(xml.to_owned(), &conf);
+    let result = xml_string_to_json(xml, &conf);
     assert_eq!(expected, result.unwrap());
 }
 
@@ -91,12 +89,12 @@ fn test_add_json_type_override() {
     // check if it adds the leading slash
     let config = Config::new_with_defaults()
         .add_json_type_override("a/@attr1", JsonArray::Infer(JsonType::AlwaysString));
-    assert!(config.json_type_overrides.get("/a/@attr1").is_some());
+    assert!(config.json_type_overrides.contains_key("/a/@attr1"));
 
     // check if it doesn't add any extra slashes
     let config = Config::new_with_defaults()
         .add_json_type_override("/a/@attr1", JsonArray::Infer(JsonType::AlwaysString));
-    assert!(config.json_type_overrides.get("/a/@attr1").is_some());
+    assert!(config.json_type_overrides.contains_key("/a/@attr1"));
 }
 
 #[cfg(feature = "json_types")]
@@ -305,6 +303,13 @@ fn test_parse_text() {
     assert_eq!("0.22Test", parse_text("0.22Test", true, &JsonType::Infer));
     assert_eq!("0044951", parse_text("0044951", true, &JsonType::Infer));
     assert_eq!(1, parse_text("1", true, &JsonType::Infer));
+    assert_eq!(-1, parse_text("-1", false, &JsonType::Infer));
+    assert_eq!(-42, parse_text("-42", true, &JsonType::Infer));
+    assert_eq!(-1000, parse_text("-1000", false, &JsonType::Infer));
+    assert_eq!(i64::MIN, parse_text("-9223372036854775808", false, &JsonType::Infer));
+    assert_eq!(-9223372036854775809.0, parse_text("-9223372036854775809", false, &JsonType::Infer));
+    assert_eq!(-1.5, parse_text("-1.5", false, &JsonType::Infer));
+    assert_eq!(-7, parse_text("-007", true, &JsonType::Infer));
     assert_eq!(false, parse_text("false", false, &JsonType::Infer));
     assert_eq!(true, parse_text("true", true, &JsonType::Infer));
     assert_eq!("True", parse_text("True", true, &JsonType::Infer));
@@ -337,44 +342,24 @@ fn test_parse_text() {
 }
 
 /// A shortcut for testing the conversion using XML files.
-/// Place your XML files in `./test_xml_files` directory and run `cargo test`.
-/// They will be converted into JSON and saved in the saved directory.
+/// Place your XML files in `./test_xml_files` directory and run `UPDATE_SNAPSHOTS=1 cargo test`.
+/// Their JSON will be saved in `./test_xml_files/snapshots` and compared with the output of later runs.
 #[test]
 fn convert_test_files() {
-    // get the list of files in the text directory
-    let mut entries = std::fs::read_dir("./test_xml_files")
-        .unwrap()
-        .map(|res| res.map(|e| e.path()))
-        .collect::<Result<Vec<_>, std::io::Error>>()
-        .unwrap();
-
-    entries.sort();
-
-    let conf = Config::new_with_custom_values(true, "", "text", NullValue::Null);
-
-    for mut entry in entries {
-        // only XML files should be processed
-        if entry.extension().unwrap() != "xml" {
-            continue;
-        }
-
-        // read the XML file
-        let mut file = File::open(&entry).unwrap();
-        let mut xml_contents = String::new();
-        file.read_to_string(&mut xml_contents).unwrap();
-
-        // convert to json
-        let json = xml_string_to_json(xml_contents, &conf).unwrap();
+    #[allow(unused_mut)]
+    let mut configs = vec![
+        ("defaults", Config::new_with_defaults()),
+        ("custom_prefix", Config::new_with_custom_values(true, "", "text", NullValue::Null)),
+    ];
+    #[cfg(feature = "json_types")]
+    configs.push((
+        "json_types",
+        Config::new_with_defaults()
+            .add_json_type_override("/Root/TaxRate", JsonArray::Always(JsonType::Infer))
+            .add_json_type_override("/Root/Data/Price", JsonArray::Infer(JsonType::AlwaysString)),
+    ));
 
-        // save as json
-        entry.set_extension("json");
-        let mut file = File::create(&entry).unwrap();
-        assert!(
-            file.write_all(to_string_pretty(&json).unwrap().as_bytes())
-                .is_ok(),
-            format!("Failed on {:?}", entry.as_os_str())
-        );
-    }
+    snapshot::assert_snapshot_dir("./test_xml_files", &configs);
 }
 
 #[test]
@@ -461,4 +446,3164 @@ fn test_regex_json_type_overrides() {
     let result = xml_string_to_json(String::from(xml), &config);
     assert_eq!(expected, result.unwrap());
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_json_string_policy() {
+    let xml = "<a b=\"x\u{0B}y\"><c>1\u{0B}2</c><d>ok</d></a>";
+
+    // the values are kept as-is by default, but reported as warnings
+    let config = Config::new_with_defaults();
+    let (result, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    assert_eq!(json!({"a": {"@b": "x\u{0B}y", "c": "1\u{0B}2", "d": "ok"}}), result);
+    assert_eq!(
+        vec![Warning::IllegalJsonCharacters {
+            count: 2,
+            paths: vec!["/a/@b".to_owned(), "/a/c".to_owned()]
+        }],
+        warnings
+    );
+
+    let mut config = Config::new_with_defaults();
+    config.values.json_string_policy = JsonStringPolicy::StripControls;
+    let (result, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    // the type is inferred before the characters are stripped
+    assert_eq!(json!({"a": {"@b": "xy", "c": "12", "d": "ok"}}), result);
+    assert_eq!(1, warnings.len());
+
+    config.values.json_string_policy = JsonStringPolicy::ReplaceWith(' ');
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!({"a": {"@b": "x y", "c": "1 2", "d": "ok"}}), result);
+
+    config.values.json_string_policy = JsonStringPolicy::Error;
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::IllegalCharacter { path }) => assert_eq!("/a/@b", path),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // no warnings for clean documents, tabs and line breaks are legal in JSON strings
+    let (_, warnings) = xml_str_to_json_with_warnings("<a>x\ty\nz</a>", &config).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_json_string_policy_nul_entity() {
+    // `&#0;` is illegal even in XML 1.1, so the parser rejects it
+    let mut config = Config::new_with_defaults();
+    config.values.json_string_policy = JsonStringPolicy::StripControls;
+    assert!(matches!(xml_str_to_json("<a>x&#0;y</a>", &config), Err(ConversionError::Xml(_))));
+
+    // but NUL itself is not rejected, e.g. when an entity resolver returns it
+    let resolver = |name: &str| match name {
+        "nul" => Some("\u{0}".to_owned()),
+        _ => None,
+    };
+    let result = xml_string_to_json_with_entity_resolver("<a>x&nul;y</a>".to_owned(), &config, resolver);
+    assert_eq!(json!({"a": "xy"}), result.unwrap());
+
+    let (result, warnings) = xml_str_to_json_with_warnings("<a>x\u{0}y</a>", &config).unwrap();
+    assert_eq!(json!({"a": "xy"}), result);
+    assert_eq!(1, warnings.len());
+}
+
+#[test]
+fn test_entity_resolver() {
+    let xml = r#"<?xml version="1.0"?>
+        <!DOCTYPE a SYSTEM "entities.dtd">
+        <a b="&company;"><c>&company; &amp; &product;</c><d>&#65;</d><e><![CDATA[&company;]]></e></a>"#;
+    let resolver = |name: &str| match name {
+        "company" => Some("ACME".to_owned()),
+        "product" => Some("Rockets".to_owned()),
+        _ => None,
+    };
+
+    let result = xml_string_to_json_with_entity_resolver(
+        xml.to_owned(),
+        &Config::new_with_defaults(),
+        resolver,
+    );
+    assert_eq!(
+        json!({"a": {"@b": "ACME", "c": "ACME & Rockets", "d": "A", "e": "&company;"}}),
+        result.unwrap()
+    );
+
+    // unresolved entities fail the conversion
+    let result = xml_string_to_json_with_entity_resolver(
+        "<a>&unknown;</a>".to_owned(),
+        &Config::new_with_defaults(),
+        resolver,
+    );
+    match result {
+        Err(ConversionError::UnknownEntity { name }) => assert_eq!("unknown", name),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_converter() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Converter>();
+
+    let config = Config::new_with_custom_values(true, "", "text", NullValue::Null);
+    let documents = [
+        r#"<a attr1="007"><b attr1="7">true</b><b>8</b></a>"#,
+        r#"<Root><TaxRate>7.25</TaxRate><Data><Category>A</Category></Data><Data /></Root>"#,
+        r#"<x a="1">text</x>"#,
+    ];
+
+    // a reused converter must produce the same output as the free functions
+    let mut converter = Converter::new(&config);
+    for _ in 0..2 {
+        for xml in &documents {
+            let expected = xml_str_to_json(xml, &config).unwrap();
+            assert_eq!(expected, converter.convert_str(xml).unwrap());
+            assert_eq!(expected, converter.convert_bytes(xml.as_bytes()).unwrap());
+            assert_eq!(expected, converter.convert_reader(xml.as_bytes()).unwrap());
+        }
+    }
+
+    // errors don't leave any state behind
+    assert!(converter.convert_str("<a><b></a>").is_err());
+    assert!(converter.convert_bytes(b"<a>\xFF</a>").is_err());
+    assert_eq!(json!({"a": 1}), converter.convert_str("<a>1</a>").unwrap());
+
+    // an owned config
+    let mut converter = Converter::new(Config::new_with_defaults());
+    assert_eq!(json!({"a": {"@b": 1}}), converter.convert_str(r#"<a b="1"/>"#).unwrap());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_enum_mapping() {
+    let statuses = vec!["PENDING".to_owned(), "ACTIVE".to_owned(), "CLOSED".to_owned()];
+    let config = Config::new_with_defaults()
+        .add_enum_mapping("/orders/order/@status", statuses.clone())
+        .add_enum_mapping("orders/order/state", statuses)
+        .add_json_type_override("/orders/order/state", JsonArray::Always(JsonType::Infer));
+    assert!(config.enum_mappings.contains_key("/orders/order/state"));
+
+    let xml = r#"<orders>
+        <order status="PENDING"><state>CLOSED</state></order>
+        <order status="ACTIVE"><state>1</state></order>
+    </orders>"#;
+    let expected = json!({
+        "orders": {
+            "order": [
+                { "@status": 0, "state": [2] },
+                { "@status": 1, "state": ["1"] }
+            ]
+        }
+    });
+    let result = xml_str_to_json(xml, &config);
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn test_diff() {
+    use diff::{diff, DiffEntry, DiffKind, DiffOptions};
+
+    let config = Config::new_with_defaults();
+    let yesterday = xml_str_to_json(
+        r#"<feed updated="2020-01-01"><item id="1"><price>10</price></item><vendor>ACME</vendor></feed>"#,
+        &config,
+    )
+    .unwrap();
+    let today = xml_str_to_json(
+        r#"<feed updated="2020-01-02"><item id="1"><price>n/a</price></item><code>7</code></feed>"#,
+        &config,
+    )
+    .unwrap();
+
+    let options = DiffOptions::new().ignore_path("feed/@updated");
+    assert_eq!(
+        vec![
+            DiffEntry {
+                path: "/feed/item/price".to_owned(),
+                kind: DiffKind::TypeChanged,
+                old: Some(json!(10)),
+                new: Some(json!("n/a")),
+            },
+            DiffEntry {
+                path: "/feed/vendor".to_owned(),
+                kind: DiffKind::Removed,
+                old: Some(json!("ACME")),
+                new: None,
+            },
+            DiffEntry {
+                path: "/feed/code".to_owned(),
+                kind: DiffKind::Added,
+                old: None,
+                new: Some(json!(7)),
+            },
+        ],
+        diff(&yesterday, &today, &options)
+    );
+
+    // the volatile timestamp is reported only if it's not ignored
+    let entries = diff(&yesterday, &today, &DiffOptions::new());
+    assert_eq!(4, entries.len());
+    assert_eq!("/feed/@updated", entries[0].path);
+    assert_eq!(DiffKind::Changed, entries[0].kind);
+}
+
+#[test]
+fn test_diff_scalar_array_equivalence() {
+    use diff::{diff, DiffKind, DiffOptions};
+
+    let a = json!({"feed": {"item": {"@id": 1}, "tag": "x"}});
+    let b = json!({"feed": {"item": [{"@id": 1}], "tag": ["x", "y"]}});
+
+    // strict comparison reports every scalar-vs-array flap
+    let entries = diff(&a, &b, &DiffOptions::new());
+    assert_eq!(2, entries.len());
+    assert_eq!("/feed/item", entries[0].path);
+    assert_eq!(DiffKind::ShapeChanged, entries[0].kind);
+    assert_eq!("/feed/tag", entries[1].path);
+    assert_eq!(DiffKind::ShapeChanged, entries[1].kind);
+
+    // `X` and `[X]` are equal, but `X` and `[X, Y]` are not
+    let mut options = DiffOptions::new();
+    options.scalar_array_equivalence = true;
+    let entries = diff(&a, &b, &options);
+    assert_eq!(1, entries.len());
+    assert_eq!("/feed/tag", entries[0].path);
+    assert_eq!(DiffKind::ShapeChanged, entries[0].kind);
+
+    // array indices are not part of ignored paths
+    let a = json!({"feed": {"item": [{"@ts": 1}, {"@ts": 2}]}});
+    let b = json!({"feed": {"item": [{"@ts": 3}, {"@ts": 4}, {"@ts": 5}]}});
+    let entries = diff(&a, &b, &DiffOptions::new());
+    assert_eq!(3, entries.len());
+    assert_eq!("/feed/item[0]/@ts", entries[0].path);
+    assert_eq!("/feed/item[2]", entries[2].path);
+    assert_eq!(DiffKind::Added, entries[2].kind);
+    let entries = diff(&a, &b, &DiffOptions::new().ignore_path("/feed/item/@ts"));
+    assert_eq!(1, entries.len());
+}
+
+#[test]
+fn test_cancelable() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    let config = Config::new_with_defaults();
+    let xml = ["<a>", &"<b><c>1</c><d x=\"y\" /></b>".repeat(20_000), "</a>"].concat();
+
+    let cancel = AtomicBool::new(false);
+    assert!(xml_string_to_json_cancelable("<a>1</a>".to_owned(), &config, &cancel).is_ok());
+    cancel.store(true, Ordering::Relaxed);
+    match xml_string_to_json_cancelable("<a>1</a>".to_owned(), &config, &cancel) {
+        Err(ConversionError::Canceled) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // cancel from another thread while the conversions are running
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        flag.store(true, Ordering::Relaxed);
+    });
+    let mut converter = Converter::new(&config);
+    loop {
+        match converter.convert_str_cancelable(&xml, &cancel) {
+            Ok(_) => continue,
+            Err(ConversionError::Canceled) => break,
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_node_id() {
+    let xml = r#"<a><b><c>1</c></b><b xml:id="second"><c>2</c><c>3</c><d x="1"/></b><e>4</e></a>"#;
+
+    let mut config = Config::new_with_defaults();
+    config.injection.node_id = Some(NodeIdConfig::new("_id", NodeIdSource::SyntheticPath));
+    let expected = json!({
+        "a": {
+            "_id": "/a",
+            "b": [
+                { "_id": "/a/b[0]", "c": 1 },
+                { "_id": "/a/b[1]", "@xml:id": "second", "c": [2, 3], "d": { "_id": "/a/b[1]/d[0]", "@x": 1 } }
+            ],
+            "e": 4
+        }
+    });
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(expected, result);
+    // the ids are deterministic
+    assert_eq!(result, xml_str_to_json(xml, &config).unwrap());
+
+    config.injection.node_id = Some(NodeIdConfig::new("_id", NodeIdSource::Attr("xml:id".to_owned())));
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!("second"), result["a"]["b"][1]["_id"]);
+    assert_eq!(None, result["a"]["b"][0].get("_id"));
+    assert_eq!(None, result["a"].get("_id"));
+}
+
+#[test]
+fn test_node_id_clash() {
+    let xml = r#"<a><b id="x"><c>1</c></b></a>"#;
+    let mut config = Config::new_with_custom_values(false, "", "#text", NullValue::Null);
+    let mut node_id = NodeIdConfig::new("id", NodeIdSource::SyntheticPath);
+
+    config.injection.node_id = Some(node_id.clone());
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::NodeIdClash { path }) => assert_eq!("/a/b", path),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    node_id.on_clash = NodeIdClash::KeepExisting;
+    config.injection.node_id = Some(node_id.clone());
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!({"a": {"id": "/a", "b": {"id": "x", "c": 1}}}), result);
+
+    node_id.on_clash = NodeIdClash::Overwrite;
+    config.injection.node_id = Some(node_id);
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!({"a": {"id": "/a", "b": {"id": "/a/b[0]", "c": 1}}}), result);
+}
+
+#[test]
+fn test_preserve_child_order() {
+    let xml = r#"<a><b x="1"/><c y="2"><d>5</d></c><e>text</e><b x="3"/></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.injection.preserve_child_order = true;
+
+    let expected = json!({
+        "a": {
+            "b": [{ "@x": 1, "_order": 0 }, { "@x": 3, "_order": 3 }],
+            "c": { "@y": 2, "d": 5, "_order": 1 },
+            "e": "text"
+        }
+    });
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.injection.child_order_key = "#pos".to_owned();
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!(1), result["a"]["c"]["#pos"]);
+}
+
+#[cfg(feature = "jq")]
+#[test]
+fn test_jq_transform() {
+    let config = Config::new_with_defaults();
+    let xml = r#"<root><a>1</a><b c="x">2</b></root>"#;
+
+    let result = xml_string_to_json_transformed(xml.to_owned(), &config, ".root | to_entries");
+    let expected = json!([
+        {"key": "a", "value": 1},
+        {"key": "b", "value": {"#text": 2, "@c": "x"}}
+    ]);
+    assert_eq!(expected, result.unwrap());
+
+    // no outputs and multiple outputs are collected into an array
+    let result = xml_string_to_json_transformed(xml.to_owned(), &config, ".root[] | strings");
+    assert_eq!(json!([]), result.unwrap());
+    let result = xml_string_to_json_transformed(xml.to_owned(), &config, ".root.a, .root.b.\"@c\"");
+    assert_eq!(json!([1, "x"]), result.unwrap());
+
+    // invalid filters and runtime errors
+    match xml_string_to_json_transformed(xml.to_owned(), &config, ".root | to_entries(") {
+        Err(ConversionError::TransformError(_)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match xml_string_to_json_transformed(xml.to_owned(), &config, ".root.a | keys") {
+        Err(ConversionError::TransformError(_)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_xml_to_ndjson() {
+    let xml = r#"<export date="2020-01-01">
+        <source>erp</source>
+        <meta><v>1</v></meta>
+        <rows>
+            <row id="1"><name>A</name></row>
+            <row id="2"><name>B</name><qty>3</qty></row>
+            <other>x</other>
+            <row>7</row>
+        </rows>
+    </export>"#;
+    let config = Config::new_with_defaults();
+
+    let mut output = Vec::new();
+    let lines = xml_to_ndjson(xml.as_bytes(), &mut output, "/export/rows/row", &config, false).unwrap();
+    assert_eq!(3, lines);
+    let output = String::from_utf8(output).unwrap();
+    let parsed: Vec<Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(
+        vec![json!({"@id": 1, "name": "A"}), json!({"@id": 2, "name": "B", "qty": 3}), json!(7)],
+        parsed
+    );
+
+    // attributes and simple text children of the ancestors are merged into every line
+    let mut output = Vec::new();
+    let lines = xml_to_ndjson(xml.as_bytes(), &mut output, "export/rows/row", &config, true).unwrap();
+    assert_eq!(3, lines);
+    let output = String::from_utf8(output).unwrap();
+    let parsed: Vec<Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(
+        json!({"export.@date": "2020-01-01", "export.source": "erp", "rows.other": "x", "@id": 1, "name": "A"}),
+        parsed[0]
+    );
+    assert_eq!(
+        json!({"export.@date": "2020-01-01", "export.source": "erp", "rows.other": "x", "#text": 7}),
+        parsed[2]
+    );
+
+    // no records
+    let mut output = Vec::new();
+    assert_eq!(0, xml_to_ndjson(xml.as_bytes(), &mut output, "/other/row", &config, true).unwrap());
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_key_length_limit() {
+    let xml = r#"<root abcde="1"><abcd>2</abcd><abcdef>3</abcdef><abcdefghijklmnop>4</abcdefghijklmnop></root>"#;
+    let mut config = Config::new_with_custom_values(false, "", "#text", NullValue::Null);
+    config.keys.key_length_limit = Some(5);
+
+    // at the limit, one over and well over the limit
+    let expected = json!({"root": {"abcd": 2, "abcde": [1, 3, 4]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.keys.key_truncation_suffix = "..".to_owned();
+    let expected = json!({"root": {"abcd": 2, "abcde": 1, "abc..": [3, 4]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // the attribute prefix counts towards the limit
+    config.xml_attr_prefix = "@".to_owned();
+    let expected = json!({"root": {"@ab..": 1, "abcd": 2, "abc..": [3, 4]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    assert_eq!("ab", truncate_key("abcdef".to_owned(), 2, "..."));
+    assert_eq!("ä…", truncate_key("äöüß".to_owned(), 2, "…"));
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_schema_aware() {
+    let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+        <xs:element name="order" type="Order"/>
+        <xs:complexType name="Order">
+            <xs:sequence>
+                <xs:element name="number" type="xs:string"/>
+                <xs:element name="date" type="xs:date"/>
+                <xs:element name="paid" type="xs:boolean"/>
+                <xs:element name="item" maxOccurs="unbounded">
+                    <xs:complexType>
+                        <xs:simpleContent>
+                            <xs:extension base="xs:decimal">
+                                <xs:attribute name="sku" type="Sku"/>
+                                <xs:attribute name="qty" type="xs:positiveInteger"/>
+                            </xs:extension>
+                        </xs:simpleContent>
+                    </xs:complexType>
+                </xs:element>
+                <xs:element name="note" type="xs:anyType" minOccurs="0"/>
+            </xs:sequence>
+        </xs:complexType>
+        <xs:simpleType name="Sku">
+            <xs:restriction base="xs:token"/>
+        </xs:simpleType>
+    </xs:schema>"#;
+    let xml = r#"<order><number>0042</number><date>2020</date><paid>1</paid><item sku="100" qty="2">9.5</item><note>7</note></order>"#;
+
+    // without the schema everything is inferred
+    let config = Config::new_with_defaults();
+    let expected = json!({"order": {
+        "number": 42, "date": 2020, "paid": 1, "item": {"@sku": 100, "@qty": 2, "#text": 9.5}, "note": 7
+    }});
+    assert_eq!(expected, xml_string_to_json(xml, &config).unwrap());
+
+    let expected = json!({"order": {
+        "number": "0042", "date": "2020", "paid": true, "item": [{"@sku": "100", "@qty": 2, "#text": 9.5}], "note": 7
+    }});
+    assert_eq!(expected, xml_string_to_json_schema_aware(xml.to_owned(), xsd, &config).unwrap());
+
+    // explicit overrides take precedence over the schema
+    let config = Config::new_with_defaults()
+        .add_json_type_override("/order/number", JsonArray::Infer(JsonType::Infer));
+    let result = xml_string_to_json_schema_aware(xml.to_owned(), xsd, &config).unwrap();
+    assert_eq!(json!(42), result["order"]["number"]);
+
+    // recursive declarations and element references are expanded once
+    let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+        <xs:element name="node">
+            <xs:complexType>
+                <xs:sequence>
+                    <xs:element name="name" type="xs:string"/>
+                    <xs:element ref="node" minOccurs="0" maxOccurs="unbounded"/>
+                </xs:sequence>
+            </xs:complexType>
+        </xs:element>
+    </xs:schema>"#;
+    let xml = r#"<node><name>1</name><node><name>2</name><node><name>3</name></node></node></node>"#;
+    let expected = json!({"node": {"name": "1", "node": [{"name": "2", "node": {"name": 3}}]}});
+    assert_eq!(expected, xml_string_to_json_schema_aware(xml.to_owned(), xsd, &config).unwrap());
+
+    assert!(xml_string_to_json_schema_aware(xml.to_owned(), "<a></b>", &config).is_err());
+}
+
+#[test]
+fn test_inference_flags() {
+    let xml = r#"<a b="7"><c>true</c><d>null</d><e>0.5</e></a>"#;
+    let mut config = Config::new_with_defaults();
+
+    let cases = vec![
+        (false, false, false, json!({"a": {"@b": "7", "c": "true", "d": "null", "e": "0.5"}})),
+        (true, false, false, json!({"a": {"@b": 7, "c": "true", "d": "null", "e": 0.5}})),
+        (false, true, false, json!({"a": {"@b": "7", "c": true, "d": "null", "e": "0.5"}})),
+        (false, false, true, json!({"a": {"@b": "7", "c": "true", "d": null, "e": "0.5"}})),
+        (true, true, false, json!({"a": {"@b": 7, "c": true, "d": "null", "e": 0.5}})),
+        (true, false, true, json!({"a": {"@b": 7, "c": "true", "d": null, "e": 0.5}})),
+        (false, true, true, json!({"a": {"@b": "7", "c": true, "d": null, "e": "0.5"}})),
+        (true, true, true, json!({"a": {"@b": 7, "c": true, "d": null, "e": 0.5}})),
+    ];
+    for (numbers, booleans, nulls, expected) in cases {
+        config.values.inference = InferenceFlags { numbers, booleans, nulls };
+        assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    }
+
+    // the defaults infer numbers and booleans only
+    assert_eq!(
+        InferenceFlags { numbers: true, booleans: true, nulls: false },
+        Config::new_with_defaults().values.inference
+    );
+
+    // overrides still force their types
+    #[cfg(feature = "json_types")]
+    {
+        let mut config = Config::new_with_defaults()
+            .add_json_type_override("/a/c", JsonArray::Infer(JsonType::Bool(vec!["true"])))
+            .add_json_type_override("/a/@b", JsonArray::Infer(JsonType::AlwaysString));
+        config.values.inference = InferenceFlags { numbers: true, booleans: false, nulls: false };
+        let expected = json!({"a": {"@b": "7", "c": true, "d": "null", "e": 0.5}});
+        assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    }
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_path_anchor() {
+    let xml = r#"<a id="01"><price>1.50</price><b id="02"><price>2</price><c><price>3</price></c></b></a>"#;
+    let mut config = Config::new_with_defaults()
+        .add_json_type_override("/price", JsonArray::Infer(JsonType::AlwaysString))
+        .add_json_type_override("/@id", JsonArray::Infer(JsonType::AlwaysString))
+        .add_json_type_override("/a/b/price", JsonArray::Always(JsonType::Infer))
+        .add_json_type_override("c/price", JsonArray::Always(JsonType::AlwaysString));
+
+    // only the exact paths match by default
+    let expected = json!({"a": {"@id": 1, "price": 1.5, "b": {"@id": 2, "price": [2], "c": {"price": 3}}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // exact matches take priority, then the longest suffix
+    config.path_anchor = PathAnchor::Anywhere;
+    let expected = json!({"a": {"@id": "01", "price": "1.50", "b": {"@id": "02", "price": [2], "c": {"price": ["3"]}}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // enum mappings follow the same rules
+    let xml = r#"<a><s>ON</s><b><s>OFF</s></b></a>"#;
+    let mut config = Config::new_with_defaults().add_enum_mapping("/s", vec!["OFF".to_owned(), "ON".to_owned()]);
+    config.path_anchor = PathAnchor::Anywhere;
+    assert_eq!(json!({"a": {"s": 1, "b": {"s": 0}}}), xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_records_to_rows() {
+    use flatten::{records_to_rows, ArrayPolicy, FlattenOptions};
+
+    let xml = r#"<items>
+        <item id="1"><name>A</name><price currency="EUR">1.5</price></item>
+        <item id="2"><name>B</name><tag>x</tag><tag>y</tag></item>
+        <item id="3"><price>3</price><tag>z</tag></item>
+        <item/>
+    </items>"#;
+    let json = xml_str_to_json(xml, &Config::new_with_defaults()).unwrap();
+    let records = json["items"]["item"].as_array().unwrap();
+
+    // arrays explode into numbered columns, missing cells are null
+    let (columns, rows) = records_to_rows(records, &FlattenOptions::new());
+    assert_eq!(
+        vec!["@id", "name", "price.#text", "price.@currency", "tag.0", "tag.1", "price", "tag"],
+        columns
+    );
+    assert_eq!(
+        vec![
+            vec![json!(1), json!("A"), json!(1.5), json!("EUR"), Value::Null, Value::Null, Value::Null, Value::Null],
+            vec![json!(2), json!("B"), Value::Null, Value::Null, json!("x"), json!("y"), Value::Null, Value::Null],
+            vec![json!(3), Value::Null, Value::Null, Value::Null, Value::Null, Value::Null, json!(3), json!("z")],
+            vec![Value::Null; 8],
+        ],
+        rows
+    );
+
+    // arrays over the limit are stringified
+    let records = vec![json!({"a": [1, [2, 3]], "b": [{"c": 1}, {"c": 2}, {"c": 3}], "d": []})];
+    let options = FlattenOptions::new().arrays(ArrayPolicy::Explode { limit: 2 });
+    let (columns, rows) = records_to_rows(&records, &options);
+    assert_eq!(vec!["a.0", "a.1.0", "a.1.1", "b", "d"], columns);
+    assert_eq!(
+        vec![vec![json!(1), json!(2), json!(3), json!(r#"[{"c":1},{"c":2},{"c":3}]"#), Value::Null]],
+        rows
+    );
+
+    let mut options = FlattenOptions::new().arrays(ArrayPolicy::Stringify);
+    options.separator = "_".to_owned();
+    let records = vec![json!({"a": [1, [2, 3]], "b": {"c": [{"d": 1}]}}), json!(5)];
+    let (columns, rows) = records_to_rows(&records, &options);
+    assert_eq!(vec!["a", "b_c", ""], columns);
+    assert_eq!(
+        vec![
+            vec![json!("[1,[2,3]]"), json!(r#"[{"d":1}]"#), Value::Null],
+            vec![Value::Null, Value::Null, json!(5)],
+        ],
+        rows
+    );
+}
+
+#[test]
+fn test_comment_handling() {
+    let xml = r#"<a><!-- first --><b>1<!--b note--></b><c x="1"/><!--second--><d/></a>"#;
+    let mut config = Config::new_with_defaults();
+
+    let expected = json!({"a": {"b": 1, "c": {"@x": 1}, "d": {}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.text.comment_handling = CommentHandling::PreserveAsField("#comment".to_owned());
+    let expected = json!({"a": {
+        "#comment": "second", "b": {"#comment": "b note", "#text": 1}, "c": {"@x": 1}, "d": {}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.text.comment_handling = CommentHandling::PreserveAsArray("#comments".to_owned());
+    let expected = json!({"a": {
+        "#comments": ["first", "second"], "b": {"#comments": ["b note"], "#text": 1}, "c": {"@x": 1}, "d": {}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_tuple_rules() {
+    let xml = r#"<cloud><point x="1" y="2.5"/><point y="3" x="0"/><point x="4"/><point x="5" y="6" z="7"/><point x="8">9</point></cloud>"#;
+    let config = Config::new_with_defaults()
+        .add_tuple_rule("/cloud/point", &["x", "y"])
+        .add_json_type_override("/cloud/point/@y", JsonArray::Infer(JsonType::AlwaysString));
+
+    let (result, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    let expected = json!({"cloud": {"point": [
+        [1, "2.5"], [0, "3"], [4, null], {"@x": 5, "@y": "6", "@z": 7}, {"@x": 8, "#text": 9}
+    ]}});
+    assert_eq!(expected, result);
+    assert_eq!(
+        vec![Warning::TupleRuleMismatch {
+            count: 2,
+            paths: vec!["/cloud/point".to_owned(), "/cloud/point".to_owned()],
+        }],
+        warnings
+    );
+
+    // a single tuple is still wrapped into an array
+    let result = xml_str_to_json(r#"<cloud><point x="1" y="2"/></cloud>"#, &config).unwrap();
+    assert_eq!(json!({"cloud": {"point": [[1, "2"]]}}), result);
+}
+
+#[test]
+fn test_number_parser() {
+    let xml = r#"<a><u>340282366920938463463374607431768211455</u><i>-170141183460469231731687303715884105728</i><s>18446744073709551616</s><n>-5</n><f>1.5</f><z>007</z></a>"#;
+    let mut config = Config::new_with_defaults();
+
+    // the standard parser loses the precision of large integers
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!(-5), result["a"]["n"]);
+    assert!(result["a"]["s"].is_f64());
+
+    // the old profiles parse negative integers as floats
+    config.profile = ConversionProfile::V0_6;
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!(-5.0), result["a"]["n"]);
+    config.profile = ConversionProfile::Latest;
+
+    config.values.number_parser = NumberParser::I128U128;
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!(-5), result["a"]["n"]);
+    assert_eq!(json!(1.5), result["a"]["f"]);
+    assert_eq!(json!(7), result["a"]["z"]);
+    #[cfg(not(feature = "arbitrary_precision"))]
+    {
+        // values beyond `u64` and `i64` are kept as strings
+        assert_eq!(json!("340282366920938463463374607431768211455"), result["a"]["u"]);
+        assert_eq!(json!("-170141183460469231731687303715884105728"), result["a"]["i"]);
+        assert_eq!(json!("18446744073709551616"), result["a"]["s"]);
+    }
+    #[cfg(feature = "arbitrary_precision")]
+    assert_eq!(
+        r#"{"a":{"f":1.5,"i":-170141183460469231731687303715884105728,"n":-5,"s":18446744073709551616,"u":340282366920938463463374607431768211455,"z":7}}"#,
+        result.to_string()
+    );
+
+    config.leading_zero_as_string = true;
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!("007"), result["a"]["z"]);
+
+    // a custom parser decides what is a number
+    config.values.number_parser = NumberParser::Custom(Arc::new(|text: &str| {
+        text.strip_prefix("-").and_then(|t| t.parse::<u64>().ok()).map(|v| json!(v))
+    }));
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!(5), result["a"]["n"]);
+    assert_eq!(json!("1.5"), result["a"]["f"]);
+}
+
+#[cfg(feature = "arbitrary_precision")]
+#[test]
+fn test_number_parser_arbitrary_precision() {
+    let xml = r#"<a><pi>3.14159265358979323846264338327950288</pi><n>-12345678901234567890123</n><z>007</z></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.values.number_parser = NumberParser::ArbitraryPrecision;
+
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(
+        r#"{"a":{"n":-12345678901234567890123,"pi":3.14159265358979323846264338327950288,"z":7}}"#,
+        result.to_string()
+    );
+}
+
+#[test]
+fn test_xml_string_to_json_resume() {
+    let xml = r#"<?xml version="1.0"?>
+<!-- feed -->
+<feed xmlns:x="urn:x" updated="today">
+    <item id="1"><name>A</name><!-- <b> --></item>
+    <x:item id="2"/>
+    <item id="3"><name><![CDATA[</item> > ]]></name></item>
+    <other a='>'>1</other>
+</feed>"#;
+    let config = Config::new_with_defaults();
+
+    // split inside the attribute of the second item
+    let split = xml.find(r#"id="2""#).unwrap() + 2;
+    let mut cursor = XmlConversionCursor::new();
+    let first = xml_string_to_json_resume(&xml[..split], &config, &mut cursor).unwrap();
+    assert_eq!(vec![json!({"item": {"@id": 1, "name": "A"}})], first);
+    assert!(!cursor.is_finished());
+
+    let second = xml_string_to_json_resume(&xml[split..], &config, &mut cursor).unwrap();
+    assert_eq!(
+        vec![
+            json!({"item": {"@id": 2}}),
+            json!({"item": {"@id": 3, "name": "</item> >"}}),
+            json!({"other": {"@a": ">", "#text": 1}}),
+        ],
+        second
+    );
+    assert!(cursor.is_finished());
+
+    // any split produces the same elements
+    let expected: Vec<Value> = first.into_iter().chain(second).collect();
+    for split in 0..=xml.len() {
+        let mut cursor = XmlConversionCursor::new();
+        let mut values = xml_string_to_json_resume(&xml[..split], &config, &mut cursor).unwrap();
+        values.extend(xml_string_to_json_resume(&xml[split..], &config, &mut cursor).unwrap());
+        assert_eq!(expected, values, "split at {}", split);
+        assert!(cursor.is_finished());
+    }
+
+    // one character at a time
+    let mut cursor = XmlConversionCursor::new();
+    let mut values = Vec::new();
+    for i in 0..xml.len() {
+        values.extend(xml_string_to_json_resume(&xml[i..i + 1], &config, &mut cursor).unwrap());
+    }
+    assert_eq!(expected, values);
+
+    // malformed elements are reported once they are complete
+    let mut cursor = XmlConversionCursor::new();
+    assert!(xml_string_to_json_resume("<a><b><c></b>", &config, &mut cursor).unwrap().is_empty());
+    assert!(xml_string_to_json_resume("</b>", &config, &mut cursor).is_err());
+}
+
+#[test]
+fn test_promote_attributes() {
+    let xml = r#"<users><user id="7" role="admin"><name>x</name></user><user id="8"><id>u8</id></user></users>"#;
+    let mut config = Config::new_with_defaults();
+
+    config.attributes.promote_attributes = PromoteAttrs::Named(vec!["id".to_owned()]);
+    let expected = json!({"users": {"user": [
+        {"id": 7, "@role": "admin", "name": "x"},
+        {"id_attr": 8, "id": "u8"}
+    ]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.attributes.promote_attributes = PromoteAttrs::All;
+    config.attributes.promoted_attr_collision_suffix = "-a".to_owned();
+    let expected = json!({"users": {"user": [
+        {"id": 7, "role": "admin", "name": "x"},
+        {"id-a": 8, "id": "u8"}
+    ]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.attributes.promote_attributes = PromoteAttrs::Paths(vec!["users/user/@role".to_owned()]);
+    let expected = json!({"users": {"user": [
+        {"@id": 7, "role": "admin", "name": "x"},
+        {"@id": 8, "id": "u8"}
+    ]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_attr_value_transform() {
+    let xml = r##"<a color="#FF0000" d="P1Y2M3D"><b color="#00ff00">#FF0000</b><c d=" 12 "/></a>"##;
+    let config = Config::new_with_defaults()
+        .add_attr_value_transform("color", |v| {
+            u32::from_str_radix(v.trim_start_matches('#'), 16).map_or(v.to_owned(), |c| c.to_string())
+        })
+        .add_attr_value_transform("d", |v| v.replace("P", "").replace("Y", "."));
+
+    // the transforms apply to attributes with these names only, the results are inferred as usual
+    let expected = json!({"a": {
+        "@color": 16711680, "@d": "1.2M3D", "b": {"@color": 65280, "#text": "#FF0000"}, "c": {"@d": 12}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_trace_paths() {
+    let xml = r#"<a><b>12</b><b>-1.5</b><b>007</b><b>true</b><b>null</b><b> x </b><c>12</c></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.leading_zero_as_string = true;
+    config.values.inference.nulls = true;
+    config.diagnostics.trace_paths = vec!["a/b".to_owned()];
+
+    let (_, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    let decisions: Vec<(&str, Branch, &str)> = warnings
+        .iter()
+        .filter_map(|w| match w {
+            Warning::TypeDecision { path, decision } => {
+                assert_eq!("/a/b", path);
+                Some((decision.input_text.as_str(), decision.branch, decision.output_type))
+            }
+            _ => None,
+        })
+        .collect();
+    let expected = vec![
+        ("12", Branch::U64, "number"),
+        ("-1.5", Branch::F64, "number"),
+        ("007", Branch::LeadingZeroString, "string"),
+        ("true", Branch::Bool, "bool"),
+        ("null", Branch::SentinelNull, "null"),
+        ("x", Branch::FallbackString, "string"),
+    ];
+    assert_eq!(expected, decisions);
+
+    // nothing is recorded for the other paths
+    config.diagnostics.trace_paths.clear();
+    let (_, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_max_json_depth() {
+    let xml = r#"<a x="1"><b><c><d>1</d></c><c>2</c></b><e>3</e></a>"#;
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"a": {"@x": 1, "b": {"c": [{"d": 1}, 2]}, "e": 3}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // the array of `c` elements is at level 4
+    config.output.max_json_depth = Some(3);
+    let expected = json!({"a": {"@x": 1, "b": {"c": "…"}, "e": 3}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.output.max_json_depth = Some(5);
+    config.output.json_depth_sentinel = Value::Null;
+    let expected = json!({"a": {"@x": 1, "b": {"c": [{"d": null}, 2]}, "e": 3}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // shallower documents are not affected
+    config.output.max_json_depth = Some(3);
+    let xml = r#"<a x="1"><b>2</b></a>"#;
+    assert_eq!(json!({"a": {"@x": 1, "b": 2}}), xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_json_to_xml() {
+    let mut config = Config::new_with_defaults();
+
+    let json = json!({"a": {"@x": 1, "b": [1, "<2>"], "c": {"@y": "\"q\"\n", "#text": true}}});
+    let expected = r#"<a x="1"><b>1</b><b>&lt;2&gt;</b><c y="&quot;q&quot;&#10;">true</c></a>"#;
+    assert_eq!(expected, json_to_xml(&json, &config).unwrap());
+    // the output converts back into the same JSON
+    assert_eq!(json, xml_str_to_json(expected, &config).unwrap());
+
+    // an array of texts repeats the element with its attributes
+    let json = json!({"a": {"tags": {"@k": "v", "#text": ["x", null, 3]}}});
+    let expected = r#"<a><tags k="v">x</tags><tags k="v"/><tags k="v">3</tags></a>"#;
+    assert_eq!(expected, json_to_xml(&json, &config).unwrap());
+    let json = json!({"a": {"tags": {"#text": ["x", "y"], "b": 1}}});
+    assert!(matches!(
+        json_to_xml(&json, &config),
+        Err(ConversionError::UnsupportedJson { ref path, .. }) if path == "/a/tags"
+    ));
+
+    // arrays under attribute keys need a separator
+    let json = json!({"a": {"@tags": ["x", null, "y"]}});
+    assert!(matches!(
+        json_to_xml(&json, &config),
+        Err(ConversionError::UnsupportedJson { ref path, .. }) if path == "/a/@tags"
+    ));
+    config.attributes.xml_attr_array_separator = Some(" ".to_owned());
+    assert_eq!(r#"<a tags="x  y"/>"#, json_to_xml(&json, &config).unwrap());
+
+    // nulls become empty elements and attributes
+    let json = json!({"a": {"@x": null, "b": [1, null, {"c": null}], "d": null}});
+    let expected = r#"<a x=""><b>1</b><b/><b><c/></b><d/></a>"#;
+    assert_eq!(expected, json_to_xml(&json, &config).unwrap());
+
+    // or are skipped if they can't come from empty elements
+    config.empty_element_handling = NullValue::Ignore;
+    assert_eq!("<a><b>1</b><b></b></a>", json_to_xml(&json, &config).unwrap());
+    assert_eq!(r#"<a tags="x y"/>"#, json_to_xml(&json!({"a": {"@tags": ["x", null, "y"]}}), &config).unwrap());
+    let json = json!({"a": {"tags": {"#text": ["x", null]}}});
+    assert_eq!("<a><tags>x</tags></a>", json_to_xml(&json, &config).unwrap());
+    assert_eq!("<a/>", json_to_xml(&json!({"a": null}), &config).unwrap());
+
+    // values with no XML form
+    for json in &[
+        json!([1]),
+        json!({"a": 1, "b": 2}),
+        json!({"a": [1, 2]}),
+        json!({"a": {"b": [[1], 2]}}),
+        json!({"a": {"@b": {"c": 1}}}),
+        json!({"a": {"#text": {"c": 1}}}),
+    ] {
+        assert!(matches!(
+            json_to_xml(json, &config),
+            Err(ConversionError::UnsupportedJson { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_promote_root_attrs() {
+    let mut config = Config::new_with_defaults();
+    config.attributes.promote_root_attrs = true;
+
+    let xml = r#"<root lang="en" v="2"><a x="1">1</a><b lang="de"/></root>"#;
+    let expected = json!({"@lang": "en", "@v": 2, "root": {"a": {"@x": 1, "#text": 1}, "b": {"@lang": "de"}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // the root element without the attributes is converted as usual
+    let xml = r#"<root lang="en">text</root>"#;
+    assert_eq!(json!({"@lang": "en", "root": "text"}), xml_str_to_json(xml, &config).unwrap());
+    let xml = r#"<root lang="en"/>"#;
+    assert_eq!(json!({"@lang": "en", "root": {}}), xml_str_to_json(xml, &config).unwrap());
+
+    // the root element wins over an attribute with the same name
+    config.xml_attr_prefix = String::new();
+    let xml = r#"<root root="x" y="1"><a>1</a></root>"#;
+    assert_eq!(json!({"y": 1, "root": {"a": 1}}), xml_str_to_json(xml, &config).unwrap());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_xml_string_to_json_with_overrides() {
+    let xml = r#"<a b="007"><c>1</c><c>2</c><d>true</d></a>"#;
+    let config = Config::new_with_defaults()
+        .add_json_type_override("/a/@b", JsonArray::Infer(JsonType::AlwaysString))
+        .add_json_type_override("/a/d", JsonArray::Always(JsonType::Infer));
+
+    let expected = json!({"a": {"@b": "007", "c": [1, 2], "d": [true]}});
+    assert_eq!(expected, xml_string_to_json_with_overrides(xml.to_owned(), &config, &[]).unwrap());
+
+    // the temporary overrides replace the ones from the config for this call only
+    let overrides = vec![
+        ("/a/@b".to_owned(), JsonType::Infer),
+        ("a/c".to_owned(), JsonType::AlwaysString),
+        ("/a/d".to_owned(), JsonType::AlwaysString),
+    ];
+    let expected = json!({"a": {"@b": 7, "c": ["1", "2"], "d": "true"}});
+    assert_eq!(expected, xml_string_to_json_with_overrides(xml.to_owned(), &config, &overrides).unwrap());
+
+    let expected = json!({"a": {"@b": "007", "c": [1, 2], "d": [true]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_injected_fields() {
+    let xml = r#"<feed><item id="1"/><item id="2"><name>x</name></item><item>3</item></feed>"#;
+    let mut config = Config::new_with_defaults()
+        .add_injected_field("/feed", "feed_id", InjectedValue::Constant(json!(42)))
+        .add_injected_field("feed", "source", InjectedValue::SourceName)
+        .add_injected_field("/feed/item", "converted", InjectedValue::TimestampRfc3339);
+
+    let result = xml_str_to_json_with_source(xml, &config, "feed.xml").unwrap();
+    assert_eq!(json!(42), result["feed"]["feed_id"]);
+    assert_eq!(json!("feed.xml"), result["feed"]["source"]);
+    // every object of the repeated element gets the same time, scalar values are left as-is
+    let items = result["feed"]["item"].as_array().unwrap();
+    let timestamp = items[0]["converted"].as_str().unwrap();
+    assert_eq!(20, timestamp.len());
+    assert!(timestamp.ends_with('Z'));
+    assert_eq!(json!(timestamp), items[1]["converted"]);
+    assert_eq!(json!(3), items[2]);
+
+    // no source name
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(Value::Null, result["feed"]["source"]);
+
+    // clashes with existing properties
+    config.injection.injected_fields =
+        vec![("/feed/item".to_owned(), "@id".to_owned(), InjectedValue::Constant(json!(0)))];
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::InjectedFieldClash { path, key }) => {
+            assert_eq!("/feed/item", path);
+            assert_eq!("@id", key);
+        }
+        r => panic!("unexpected result {:?}", r),
+    }
+    config.injection.injected_fields_on_clash = NodeIdClash::KeepExisting;
+    let expected = json!({"feed": {"item": [{"@id": 1}, {"@id": 2, "name": "x"}, 3]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    config.injection.injected_fields_on_clash = NodeIdClash::Overwrite;
+    let expected = json!({"feed": {"item": [{"@id": 0}, {"@id": 0, "name": "x"}, 3]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_rfc3339() {
+    assert_eq!("1970-01-01T00:00:00Z", rfc3339(0));
+    assert_eq!("2000-02-29T23:59:59Z", rfc3339(951868799));
+    assert_eq!("2023-11-14T22:13:20Z", rfc3339(1700000000));
+}
+
+#[test]
+fn test_emit_type_annotations() {
+    let xml = r#"<a b="x"><c>42</c><c>-7</c><d>1.5</d><e>true</e><f/></a>"#;
+    let mut config = Config::new_with_custom_values(false, "@", "#text", NullValue::Null);
+    config.output.emit_type_annotations = true;
+
+    let expected = json!({"a": {"$t": "object", "$val": {
+        "@b": {"$val": "x", "$t": "string"},
+        "c": {"$t": "array", "$val": [{"$val": 42, "$t": "u64"}, {"$val": -7, "$t": "i64"}]},
+        "d": {"$val": 1.5, "$t": "f64"},
+        "e": {"$val": true, "$t": "bool"},
+        "f": {"$val": null, "$t": "null"}
+    }}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_max_name_length() {
+    let long_a = format!("{}a", "x".repeat(5000));
+    let long_b = format!("{}b", "x".repeat(5000));
+    let xml = format!(r#"<root><{a}>1</{a}><{b} {a}="2"/><short>3</short></root>"#, a = long_a, b = long_b);
+    let mut config = Config::new_with_defaults();
+
+    match xml_str_to_json(&xml, &config) {
+        Err(ConversionError::NameTooLong { start, length }) => {
+            assert_eq!("x".repeat(64), start);
+            assert_eq!(5001, length);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // names sharing the prefix stay distinct after truncation
+    config.keys.long_name_policy = LongNamePolicy::TruncateWithHash;
+    let json = xml_str_to_json(&xml, &config).unwrap();
+    let root = json["root"].as_object().unwrap();
+    assert_eq!(3, root.len());
+    assert_eq!(json!(3), root["short"]);
+    let keys: Vec<&String> = root.keys().filter(|key| *key != "short").collect();
+    assert!(keys.iter().all(|key| key.len() == 4096 && key.starts_with("xxx")));
+    assert_ne!(keys[0], keys[1]);
+    // the same name always gives the same key
+    assert_eq!(json, xml_str_to_json(&xml, &config).unwrap());
+
+    // the attribute prefix is part of the name
+    let element = root.values().find(|value| value.is_object()).unwrap().as_object().unwrap();
+    assert!(element.keys().all(|key| key.len() == 4096 && key.starts_with("@xxx")));
+
+    config.keys.max_name_length = None;
+    let json = xml_str_to_json(&xml, &config).unwrap();
+    assert_eq!(json!(1), json["root"][long_a.as_str()]);
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn test_xml_string_to_cbor() {
+    let config = Config::new_with_defaults();
+    let xml = r#"<a b="x"><c>1</c><c>2.5</c><d>true</d><e/></a>"#;
+
+    let bytes = xml_string_to_cbor(xml.to_owned(), &config).unwrap();
+    let decoded: Value = ciborium::de::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), decoded);
+    // numbers with arbitrary precision are encoded as maps with their text
+    #[cfg(not(feature = "arbitrary_precision"))]
+    assert!(bytes.len() < decoded.to_string().len());
+
+    assert!(xml_string_to_cbor("<a></b>".to_owned(), &config).is_err());
+}
+
+/// Returns the data a few bytes at a time and counts the reads, with a pending read before each chunk.
+#[cfg(feature = "async")]
+struct ChunkedReader {
+    data: Vec<u8>,
+    pos: usize,
+    chunk_size: usize,
+    reads: std::rc::Rc<std::cell::Cell<usize>>,
+    ready: bool,
+}
+
+#[cfg(feature = "async")]
+impl tokio::io::AsyncRead for ChunkedReader {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return std::task::Poll::Pending;
+        }
+        self.ready = false;
+        self.reads.set(self.reads.get() + 1);
+        let end = std::cmp::min(self.pos + self.chunk_size, self.data.len());
+        buf.put_slice(&self.data[self.pos..end]);
+        self.pos = end;
+        std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "async")]
+fn chunked_reader(xml: &str, chunk_size: usize) -> (ChunkedReader, std::rc::Rc<std::cell::Cell<usize>>) {
+    let reads = std::rc::Rc::new(std::cell::Cell::new(0));
+    let reader = ChunkedReader {
+        data: xml.as_bytes().to_vec(),
+        pos: 0,
+        chunk_size,
+        reads: reads.clone(),
+        ready: false,
+    };
+    (reader, reads)
+}
+
+/// Runs the future to completion on a single-threaded runtime.
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// Returns the next item of the stream.
+#[cfg(feature = "async")]
+fn next_record<S: futures_core::Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    block_on(std::future::poll_fn(|cx| std::pin::Pin::new(&mut *stream).poll_next(cx)))
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_xml_async_reader_to_json() {
+    let config = Config::new_with_defaults();
+    // multi-byte characters are split between the chunks
+    let xml = r#"<a b="ü"><c>1</c><c>2</c><d>ñandú</d></a>"#;
+
+    let (reader, _) = chunked_reader(xml, 3);
+    let json = block_on(xml_async_reader_to_json(reader, &config)).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), json);
+
+    let (reader, _) = chunked_reader("<a></b>", 3);
+    assert!(block_on(xml_async_reader_to_json(reader, &config)).is_err());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_xml_async_reader_to_records() {
+    let config = Config::new_with_defaults();
+    let xml = r#"<feed><item id="1"/><item id="2">ñ</item><item id="3"/></feed>"#;
+    let (reader, reads) = chunked_reader(xml, 2);
+    let mut records = xml_async_reader_to_records(reader, &config);
+
+    // the records are produced as soon as they are complete
+    assert_eq!(json!({"item": {"@id": 1}}), next_record(&mut records).unwrap().unwrap());
+    assert_eq!(10, reads.get());
+    let expected = json!({"item": {"@id": 2, "#text": "ñ"}});
+    assert_eq!(expected, next_record(&mut records).unwrap().unwrap());
+    assert_eq!(json!({"item": {"@id": 3}}), next_record(&mut records).unwrap().unwrap());
+    assert!(next_record(&mut records).is_none());
+
+    // truncated documents end with an error
+    let (reader, _) = chunked_reader(&xml[..30], 4);
+    let mut records = xml_async_reader_to_records(reader, &config);
+    assert!(next_record(&mut records).unwrap().is_ok());
+    assert!(matches!(next_record(&mut records), Some(Err(ConversionError::Io(_)))));
+    assert!(next_record(&mut records).is_none());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_xml_string_to_json_stream_async() {
+    let config = Arc::new(Config::new_with_defaults());
+    let xml = r#"<feed><item id="1"/><item id="2">ñ</item><item id="3"/></feed>"#;
+    let mut stream = xml_string_to_json_stream_async(xml.to_owned(), config.clone());
+    let mut records = Vec::new();
+    while let Some(record) = next_record(&mut stream) {
+        records.push(record.unwrap());
+    }
+    let batch = xml_string_to_json_resume(xml, &config, &mut XmlConversionCursor::new()).unwrap();
+    assert_eq!(batch, records);
+
+    // truncated documents end with an error
+    let mut stream = xml_string_to_json_stream_async(xml[..30].to_owned(), config);
+    assert!(next_record(&mut stream).unwrap().is_ok());
+    assert!(matches!(next_record(&mut stream), Some(Err(ConversionError::Io(_)))));
+    assert!(next_record(&mut stream).is_none());
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_xml_string_to_msgpack() {
+    let config = Config::new_with_defaults();
+    let xml = r#"<a b="x"><c>1</c><c>2.5</c><c>300</c><d>true</d><e/></a>"#;
+
+    let bytes = xml_string_to_msgpack(xml.to_owned(), &config).unwrap();
+    let decoded: Value = rmp_serde::from_slice(&bytes).unwrap();
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json, decoded);
+    // numbers with arbitrary precision are encoded as maps with their text
+    #[cfg(not(feature = "arbitrary_precision"))]
+    assert_eq!((31, 48), (bytes.len(), json.to_string().len()));
+
+    assert!(xml_string_to_msgpack("<a></b>".to_owned(), &config).is_err());
+}
+
+#[test]
+fn test_leading_bom_and_whitespace() {
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"a": {"b": 1}});
+
+    let documents = [
+        "\u{feff}<?xml version=\"1.0\" encoding=\"UTF-8\"?><a><b>1</b></a>",
+        "\u{feff}<a><b>1</b></a>",
+        "\n  <?xml version=\"1.0\"?>\n<a><b>1</b></a>",
+        "\n  <!-- comment -->\n<a><b>1</b></a>",
+    ];
+    for xml in &documents {
+        assert_eq!(expected, xml_str_to_json(xml, &config).unwrap(), "{:?}", xml);
+        let mut converter = Converter::new(&config);
+        assert_eq!(expected, converter.convert_bytes(xml.as_bytes()).unwrap(), "{:?}", xml);
+    }
+
+    // whitespace before the declaration isn't allowed by the spec
+    config.input.strict_prolog = true;
+    for xml in &documents[..2] {
+        assert_eq!(expected, xml_str_to_json(xml, &config).unwrap(), "{:?}", xml);
+    }
+    assert!(matches!(
+        xml_str_to_json(documents[2], &config),
+        Err(ConversionError::MisplacedXmlDeclaration)
+    ));
+    assert!(matches!(
+        xml_str_to_json("\u{feff} <?xml version=\"1.0\"?><a/>", &config),
+        Err(ConversionError::MisplacedXmlDeclaration)
+    ));
+    assert_eq!(expected, xml_str_to_json(documents[3], &config).unwrap());
+
+    // UTF-16 documents can't be converted from bytes
+    let mut converter = Converter::new(&config);
+    for bom in &[[0xFF, 0xFE], [0xFE, 0xFF]] {
+        let mut xml = bom.to_vec();
+        xml.extend("<a/>".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        let error = converter.convert_bytes(&xml).unwrap_err();
+        assert!(matches!(error, ConversionError::UnsupportedEncoding { .. }));
+        assert!(error.to_string().contains("UTF-16"));
+    }
+}
+
+#[cfg(feature = "raw_value")]
+#[test]
+fn test_xml_string_to_raw_json() {
+    let xml = r#"<order id="42"><item qty="2">pen</item><item qty="1">ink</item><note>"a" &amp; b</note></order>"#;
+    let config = Config::new_with_defaults();
+    let raw = xml_string_to_raw_json(xml.to_owned(), &config).unwrap();
+    let value = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(serde_json::to_string(&value).unwrap(), raw.get());
+    assert_eq!(value, serde_json::from_str::<Value>(raw.get()).unwrap());
+
+    // the text is embedded as is
+    let mut response = std::collections::BTreeMap::new();
+    response.insert("body", &raw);
+    let wrapped = serde_json::to_string(&response).unwrap();
+    assert_eq!(format!(r#"{{"body":{}}}"#, raw.get()), wrapped);
+
+    assert!(xml_string_to_raw_json("<a></b>".to_owned(), &config).is_err());
+}
+
+#[test]
+fn test_decimal_string() {
+    let normalized = JsonType::DecimalString { normalize: true };
+    assert_eq!("1.5", parse_text("1.500", false, &normalized));
+    assert_eq!("0", parse_text("0.000", false, &normalized));
+    assert_eq!("10", parse_text("10", false, &normalized));
+    assert_eq!("-0.05", parse_text("-0.0500", false, &normalized));
+    assert_eq!("0", parse_text("-0.0", false, &normalized));
+    assert_eq!("3", parse_text("3.", false, &normalized));
+    assert_eq!("0.5", parse_text(".50", false, &normalized));
+    assert_eq!("1.2.3", parse_text("1.2.3", false, &normalized));
+    assert_eq!("-", parse_text("-", false, &normalized));
+    assert_eq!("1e3", parse_text("1e3", false, &normalized));
+    // digits beyond the precision of f64 are kept
+    assert_eq!(
+        "0.12345678901234567890123",
+        parse_text("0.123456789012345678901230", false, &normalized)
+    );
+
+    let kept = JsonType::DecimalString { normalize: false };
+    assert_eq!("1.500", parse_text("1.500", false, &kept));
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_decimal_string_override() {
+    let xml = r#"<a price="1.500"><b>-0.0500</b><b>n/a</b></a>"#;
+    let config = Config::new_with_defaults()
+        .add_json_type_override("/a/@price", JsonArray::Infer(JsonType::DecimalString { normalize: true }))
+        .add_json_type_override("/a/b", JsonArray::Infer(JsonType::DecimalString { normalize: true }));
+    let expected = json!({"a": {"@price": "1.5", "b": ["-0.05", "n/a"]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_boolean_from_numeric() {
+    let xml = r#"<a active="1"><b>0</b><b>1</b><b>01</b><b>2</b><b>1.0</b><b>true</b></a>"#;
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"a": {"@active": 1, "b": [0, 1, 1, 2, 1.0, true]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.values.boolean_from_numeric = true;
+    let expected = json!({"a": {"@active": true, "b": [false, true, 1, 2, 1.0, true]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // numbers with leading zeros are not booleans
+    config.leading_zero_as_string = true;
+    let expected = json!({"a": {"@active": true, "b": [false, true, "01", 2, 1.0, true]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    #[cfg(feature = "json_types")]
+    {
+        let config = config.add_json_type_override("/a/@active", JsonArray::Infer(JsonType::AlwaysString));
+        let json = xml_str_to_json(xml, &config).unwrap();
+        assert_eq!(json!("1"), json["a"]["@active"]);
+    }
+}
+
+#[test]
+fn test_generate_xml() {
+    use fixture::{generate_xml, FixtureSpec};
+
+    let shapes = [
+        FixtureSpec::default(),
+        FixtureSpec { depth: 1, breadth: 50, repeat: 5..=10, max_attributes: 8, ..FixtureSpec::default() },
+        FixtureSpec { depth: 20, breadth: 1, repeat: 1..=1, ..FixtureSpec::default() },
+        FixtureSpec { mixed_content: true, text_len: 0..=30, ..FixtureSpec::default() },
+        FixtureSpec { namespaces: 5, max_attributes: 5, ..FixtureSpec::default() },
+    ];
+    let config = Config::new_with_defaults();
+    for spec in &shapes {
+        for seed in 0..10 {
+            let xml = generate_xml(spec, seed);
+            assert_eq!(xml, generate_xml(spec, seed));
+            assert_ne!(xml, generate_xml(spec, seed + 1));
+            let json = xml_str_to_json(&xml, &config).unwrap_or_else(|e| panic!("{}: {}", e, xml));
+            assert!(json["root"].is_object() || json["root"].is_string(), "{}", xml);
+        }
+    }
+
+    // the depth is preserved in the output
+    let spec = FixtureSpec { depth: 20, breadth: 1, repeat: 1..=1, max_attributes: 0, ..FixtureSpec::default() };
+    let json = xml_str_to_json(&generate_xml(&spec, 1), &config).unwrap();
+    let mut value = &json["root"];
+    for _ in 0..20 {
+        value = &value["e0"];
+    }
+    assert!(!value.is_null() && !value.is_object());
+}
+
+#[test]
+fn test_array_homogeneity_check() {
+    let mut config = Config::new_with_defaults();
+    config.diagnostics.array_homogeneity_check = true;
+
+    // same keys in every object, and arrays of scalars
+    let xml = r#"<a><b x="1" y="2"/><b y="3" x="4"/><c>1</c><c>2</c></a>"#;
+    let (_, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    assert!(warnings.is_empty());
+
+    let xml = r#"<a><b x="1" y="2"/><b x="3" z="4"/><b x="5"/><c><d>1</d><d w="2">3</d></c></a>"#;
+    let (_, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    assert_eq!(
+        vec![
+            Warning::HeterogeneousArray {
+                path: "/a/c/d".to_owned(),
+                differing_keys: vec!["#text".to_owned(), "@w".to_owned()],
+            },
+            Warning::HeterogeneousArray {
+                path: "/a/b".to_owned(),
+                differing_keys: vec!["@y".to_owned(), "@z".to_owned()],
+            },
+        ],
+        warnings
+    );
+
+    config.diagnostics.array_homogeneity_check = false;
+    let (_, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_prolog() {
+    let config = Config::new_with_defaults();
+    let xml = r#"<?xml version="1.1" encoding='UTF-8' standalone = "no"?><a>1</a>"#;
+    let (json, prolog) = xml_str_to_json_with_prolog(xml, &config).unwrap();
+    assert_eq!(json!({"a": 1}), json);
+    let expected = Prolog {
+        version: Some("1.1".to_owned()),
+        encoding: Some("UTF-8".to_owned()),
+        standalone: Some(false),
+    };
+    assert_eq!(expected, prolog);
+
+    // no declaration
+    let (_, prolog) = xml_str_to_json_with_prolog("<a>1</a>", &config).unwrap();
+    assert_eq!(Prolog::default(), prolog);
+    assert_eq!(None, prolog.version);
+    assert_eq!(None, prolog.standalone);
+
+    let prolog = Prolog::from_xml("\u{feff} <?xml version=\"1.0\" standalone=\"yes\"?><a/>");
+    assert_eq!(Some("1.0".to_owned()), prolog.version);
+    assert_eq!(None, prolog.encoding);
+    assert_eq!(Some(true), prolog.standalone);
+}
+
+#[test]
+fn test_allowed_versions() {
+    let mut config = Config::new_with_defaults();
+    config.input.allowed_versions = Some(vec!["1.0".to_owned()]);
+
+    let xml = r#"<?xml version="1.1"?><a>1</a>"#;
+    assert!(matches!(
+        xml_str_to_json(xml, &config),
+        Err(ConversionError::UnsupportedXmlVersion { ref version }) if version == "1.1"
+    ));
+    assert_eq!(json!({"a": 1}), xml_str_to_json(r#"<?xml version="1.0"?><a>1</a>"#, &config).unwrap());
+    // documents without a declaration are XML 1.0
+    assert_eq!(json!({"a": 1}), xml_str_to_json("<a>1</a>", &config).unwrap());
+
+    config.input.allowed_versions = None;
+    assert_eq!(json!({"a": 1}), xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_xml_string_to_json_with_context() {
+    let xml = r#"<orders batch="7"><order id="1"><total>9.5</total></order><order id="2" trace_id="own"/><note>x</note></orders>"#;
+    let mut context = HashMap::new();
+    context.insert("trace_id".to_owned(), json!("abc"));
+    context.insert("@batch".to_owned(), json!("context"));
+
+    let mut config = Config::new_with_defaults();
+    config.xml_attr_prefix = String::new();
+    let json = xml_string_to_json_with_context(xml.to_owned(), &config, context.clone()).unwrap();
+    let expected = json!({"orders": {
+        "batch": 7,
+        "@batch": "context",
+        "trace_id": "abc",
+        "order": [{"id": 1, "total": 9.5}, {"id": 2, "trace_id": "own"}],
+        "note": "x"
+    }});
+    assert_eq!(expected, json);
+
+    // converted values are never overwritten
+    config.injection.context_placement = ContextPlacement::Children;
+    context.remove("@batch");
+    let json = xml_string_to_json_with_context(xml.to_owned(), &config, context).unwrap();
+    let expected = json!({"orders": {
+        "batch": 7,
+        "order": [{"id": 1, "total": 9.5, "trace_id": "abc"}, {"id": 2, "trace_id": "own"}],
+        "note": "x"
+    }});
+    assert_eq!(expected, json);
+}
+
+#[test]
+fn test_value_interceptor() {
+    use std::sync::Mutex;
+
+    /// Records the calls, drops `secret` attributes and doubles the numbers.
+    #[derive(Default)]
+    struct Recorder {
+        values: Mutex<Vec<(String, NodeKind, String)>>,
+        objects: Mutex<Vec<String>>,
+    }
+
+    impl ValueInterceptor for Recorder {
+        fn on_value(&self, path: &str, kind: NodeKind, raw: &str, parsed: Value) -> Value {
+            self.values.lock().unwrap().push((path.to_owned(), kind, raw.to_owned()));
+            match parsed {
+                Value::Number(n) => json!(n.as_f64().unwrap() * 2.0),
+                v => v,
+            }
+        }
+
+        fn on_object(&self, path: &str, obj: &mut Map<String, Value>) {
+            self.objects.lock().unwrap().push(path.to_owned());
+            obj.remove("@secret");
+        }
+    }
+
+    let xml = r#"<a secret="x"><b id=" 1 "><c>2</c><c>text</c></b><d/></a>"#;
+    let recorder = Arc::new(Recorder::default());
+    let mut config = Config::new_with_defaults();
+    config.injection.interceptor = Some(recorder.clone());
+
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!({"a": {"b": {"@id": 2.0, "c": [4.0, "text"]}, "d": {}}}), json);
+    assert_eq!(
+        vec![
+            ("/a/@secret".to_owned(), NodeKind::Attribute, "x".to_owned()),
+            ("/a/b/@id".to_owned(), NodeKind::Attribute, "1".to_owned()),
+            ("/a/b/c".to_owned(), NodeKind::Text, "2".to_owned()),
+            ("/a/b/c".to_owned(), NodeKind::Text, "text".to_owned()),
+        ],
+        *recorder.values.lock().unwrap()
+    );
+    assert_eq!(vec!["/a/b", "/a/d", "/a"], *recorder.objects.lock().unwrap());
+}
+
+#[test]
+fn test_emit_number_as_hybrid() {
+    let xml = r#"<a id="007"><price>42.50</price><qty> 3 </qty><neg>-1</neg><name>pen</name><ok>true</ok><b/></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.values.emit_number_as_hybrid = true;
+    let expected = json!({"a": {
+        "@id": {"$num": 7, "$str": "007"},
+        "price": {"$num": 42.5, "$str": "42.50"},
+        "qty": {"$num": 3, "$str": "3"},
+        "neg": {"$num": -1, "$str": "-1"},
+        "name": "pen",
+        "ok": true,
+        "b": {}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // numbers kept as strings are not wrapped
+    config.leading_zero_as_string = true;
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!("007"), json["a"]["@id"]);
+}
+
+#[test]
+fn test_text_prop_collision() {
+    let xml = r#"<a>hi<text>x</text><b><text>y</text></b></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.xml_text_node_prop_name = "text".to_owned();
+    let expected = json!({"a": {"text": "hi", "text_elem": "x", "b": {"text": "y"}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // nothing collides without text
+    let json = xml_str_to_json(r#"<r><text>1</text></r>"#, &config).unwrap();
+    assert_eq!(json!({"r": {"text": 1}}), json);
+
+    // repeated elements are still collected into an array
+    let json = xml_str_to_json(r#"<a>hi<text>x</text><text>y</text></a>"#, &config).unwrap();
+    assert_eq!(json!({"a": {"text": "hi", "text_elem": ["x", "y"]}}), json);
+
+    config.text.text_prop_collision = TextPropCollision::Suffix("_".to_owned());
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!({"a": {"text": "hi", "text_": "x", "b": {"text": "y"}}}), json);
+
+    config.text.text_prop_collision = TextPropCollision::Error;
+    assert!(matches!(
+        xml_str_to_json(xml, &config),
+        Err(ConversionError::TextPropCollision { ref path }) if path == "/a/text"
+    ));
+    assert!(xml_str_to_json(r#"<a><b><text>y</text></b></a>"#, &config).is_ok());
+
+    // the names are compared after the renames
+    config.text.text_prop_collision = TextPropCollision::Suffix("_elem".to_owned());
+    config.xml_text_node_prop_name = "ns_text".to_owned();
+    config.input.namespace_default_handling = NamespaceDefaultHandling::PrefixKeys("ns_".to_owned());
+    let json = xml_str_to_json(r#"<a xmlns="urn:x">hi<text>x</text></a>"#, &config).unwrap();
+    assert_eq!(json!({"ns_a": {"ns_text": "hi", "ns_text_elem": "x"}}), json);
+}
+
+#[test]
+fn test_xml_string_to_json_template() {
+    let xml = r#"<orders><order id="17"><total currency="EUR">9.50</total><item>pen</item></order><order id="18"/></orders>"#;
+    let config = Config::new_with_defaults();
+    let template = r#"{
+        "order_id": "{{/orders/order/@id}}",
+        "total": "{{/orders/order/total}}",
+        "label": "Order {{/orders/order/@id}}: {{ /orders/order/item }} in {{/orders/order/total/@currency}}",
+        "items": ["{{/orders/order/item}}", 1],
+        "fixed": {"source": "feed", "empty": null}
+    }"#;
+    let expected = json!({
+        "order_id": 17,
+        "total": 9.5,
+        "label": "Order 17: pen in EUR",
+        "items": ["pen", 1],
+        "fixed": {"source": "feed", "empty": null}
+    });
+    assert_eq!(expected, xml_string_to_json_template(xml.to_owned(), template, &config).unwrap());
+
+    assert!(matches!(
+        xml_string_to_json_template(xml.to_owned(), r#"{"a": "{{/orders/order/@missing}}"}"#, &config),
+        Err(ConversionError::MissingTemplatePath { ref path }) if path == "/orders/order/@missing"
+    ));
+    assert!(matches!(
+        xml_string_to_json_template(xml.to_owned(), r#"{"a": "x {{/orders"}"#, &config),
+        Err(ConversionError::InvalidTemplate(_))
+    ));
+    assert!(matches!(
+        xml_string_to_json_template(xml.to_owned(), "{", &config),
+        Err(ConversionError::InvalidTemplate(_))
+    ));
+}
+
+#[cfg(feature = "compact_value")]
+#[test]
+fn test_compact_value() {
+    use std::sync::Arc;
+
+    let config = Config::new_with_defaults();
+    let mut interner = KeyInterner::new();
+    let spec = fixture::FixtureSpec { max_attributes: 3, ..fixture::FixtureSpec::default() };
+    for seed in 0..20 {
+        let xml = fixture::generate_xml(&spec, seed);
+        let json = xml_str_to_json(&xml, &config).unwrap();
+        let compact = xml_str_to_compact_value(&xml, &config, &mut interner).unwrap();
+        assert_eq!(serde_json::to_string(&json).unwrap(), serde_json::to_string(&compact).unwrap());
+        assert_eq!(json, Value::from(compact));
+    }
+
+    // the keys of both documents are shared
+    let a = xml_str_to_compact_value(r#"<a x="1"><b>2</b></a>"#, &config, &mut interner).unwrap();
+    let b = xml_str_to_compact_value(r#"<a x="3"><b>4</b></a>"#, &config, &mut interner).unwrap();
+    match (a, b) {
+        (CompactValue::Object(a), CompactValue::Object(b)) => assert!(Arc::ptr_eq(&a[0].0, &b[0].0)),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_merge_same_name_objects() {
+    let xml = r#"<a><b id="1"><x>1</x><n><p>1</p></n></b><b id="2"><y>2</y><n><q>2</q></n></b><c>1</c><c>2</c></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.arrays.merge_same_name_objects = true;
+    // later values win, nested objects are merged
+    let expected = json!({"a": {"b": {"@id": 2, "x": 1, "y": 2, "n": {"p": 1, "q": 2}}, "c": [1, 2]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // an object and a scalar can't be merged
+    let json = xml_str_to_json(r#"<a><b x="1"/><b>2</b><b y="3"/></a>"#, &config).unwrap();
+    assert_eq!(json!({"a": {"b": [{"@x": 1}, 2, {"@y": 3}]}}), json);
+
+    config.arrays.merge_same_name_objects = false;
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!([{"@id": 1, "x": 1, "n": {"p": 1}}, {"@id": 2, "y": 2, "n": {"q": 2}}]), json["a"]["b"]);
+
+    #[cfg(feature = "json_types")]
+    {
+        let mut config = Config::new_with_defaults().add_json_type_override("/a/b", JsonArray::Always(JsonType::Infer));
+        config.arrays.merge_same_name_objects = true;
+        let json = xml_str_to_json(xml, &config).unwrap();
+        assert_eq!(2, json["a"]["b"].as_array().unwrap().len());
+    }
+}
+
+#[test]
+fn test_absent_field_policy() {
+    let mut config = Config::new_with_defaults()
+        .add_absent_field_policy("/order/note", AbsentPolicy::Null)
+        .add_absent_field_policy("order/@priority", AbsentPolicy::Null)
+        .add_absent_field_policy("/order/tag", AbsentPolicy::Omit);
+    config.empty_element_handling = NullValue::Ignore;
+
+    // both present
+    let xml = r#"<order priority="2"><id>1</id><note>fragile</note><tag>x</tag></order>"#;
+    let expected = json!({"order": {"@priority": 2, "id": 1, "note": "fragile", "tag": "x"}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // both absent, `note` is dropped as an empty element
+    let xml = r#"<order><id>1</id><note/></order>"#;
+    let expected = json!({"order": {"@priority": null, "id": 1, "note": null}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // the parent has text
+    let xml = r#"<order priority="2">text</order>"#;
+    let expected = json!({"order": {"@priority": 2, "#text": "text", "note": null}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // a scalar parent has no object
+    assert_eq!(json!({"order": 1}), xml_str_to_json("<order>1</order>", &config).unwrap());
+}
+
+#[test]
+fn test_xml_string_to_json_with_parser() {
+    let xml = r#"<a x="1"><b>text</b></a>"#;
+    let config = Config::new_with_defaults();
+    let json = xml_string_to_json_with_parser::<MinidomParser>(xml.to_owned(), &config).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), json);
+}
+
+#[cfg(feature = "quickxml-parser")]
+#[test]
+fn test_quickxml_parser() {
+    let mut config = Config::new_with_defaults();
+    config.text.comment_handling = CommentHandling::PreserveAsArray("#comments".to_owned());
+    let documents = [
+        r#"<?xml version="1.0"?><!-- before --><a x="1 &amp; 2"><b>1</b><b>2</b><!-- note --><c/></a>"#,
+        r#"<r xmlns="urn:a" xmlns:n="urn:n"><n:b n:y="2">x<![CDATA[<y>]]>&lt;z</n:b></r>"#,
+        "\u{feff}<a>\n  <b>  padded  </b>\n</a>",
+    ];
+    for xml in &documents {
+        let expected = xml_string_to_json_with_parser::<MinidomParser>(xml.to_string(), &config).unwrap();
+        let json = xml_string_to_json_with_parser::<QuickXmlParser>(xml.to_string(), &config).unwrap();
+        assert_eq!(expected, json, "{}", xml);
+    }
+
+    let spec = fixture::FixtureSpec { mixed_content: true, namespaces: 3, ..fixture::FixtureSpec::default() };
+    for seed in 0..20 {
+        let xml = fixture::generate_xml(&spec, seed);
+        let expected = xml_string_to_json_with_parser::<MinidomParser>(xml.clone(), &config).unwrap();
+        let json = xml_string_to_json_with_parser::<QuickXmlParser>(xml, &config).unwrap();
+        assert_eq!(expected, json);
+    }
+
+    for xml in &["<a><b></a>", "<a>", "<a:b:c/>"] {
+        let result = xml_string_to_json_with_parser::<QuickXmlParser>(xml.to_string(), &config);
+        assert!(matches!(result, Err(ConversionError::Xml(_))), "{}", xml);
+    }
+}
+
+#[test]
+fn test_normalize_input() {
+    let canonical = "<a xmlns=\"urn:a\" x=\"1\" y=\"2\"><b>line 1\nline 2</b><c xmlns=\"urn:a\"/></a>";
+    let other = "<a y=\"2\" x=\"1\" xmlns=\"urn:a\"><b>line 1\r\nline 2</b><c xmlns=\"urn:a\"></c></a>";
+
+    let mut config = Config::new_with_defaults();
+    config.profile = ConversionProfile::V0_6;
+    assert_ne!(xml_str_to_json(canonical, &config).unwrap(), xml_str_to_json(other, &config).unwrap());
+
+    config.input.normalize_input = true;
+    let expected = json!({"a": {"@x": 1, "@y": 2, "b": "line 1\nline 2", "c": {}}});
+    assert_eq!(expected, xml_str_to_json(canonical, &config).unwrap());
+    assert_eq!(expected, xml_str_to_json(other, &config).unwrap());
+
+    // the latest rules normalize line endings anyway
+    let config = Config::new_with_defaults();
+    assert_eq!(expected, xml_str_to_json(other, &config).unwrap());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_json_type_override_templates() {
+    let xml = r#"<root><acme><config value="007"/></acme><globex><config value="007"/></globex></root>"#;
+    let mut vars = HashMap::new();
+    vars.insert("tenant".to_owned(), "acme".to_owned());
+    let config = Config::new_with_defaults().add_json_type_override_template(
+        "/root/{tenant}/config/@value",
+        vars,
+        JsonType::AlwaysString,
+    );
+    assert!(config.json_type_override_templates.is_empty());
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!("007"), json["root"]["acme"]["config"]["@value"]);
+    assert_eq!(json!(7), json["root"]["globex"]["config"]["@value"]);
+
+    // variables resolved later
+    let config = Config::new_with_defaults().add_json_type_override_template(
+        "root/{tenant}/{section}/@value",
+        HashMap::new(),
+        JsonType::AlwaysString,
+    );
+    assert_eq!(1, config.json_type_override_templates.len());
+    assert_eq!(json!(7), xml_str_to_json(xml, &config).unwrap()["root"]["globex"]["config"]["@value"]);
+
+    let mut vars = HashMap::new();
+    vars.insert("tenant".to_owned(), "globex".to_owned());
+    let partial = config.expand_template_overrides(&vars);
+    assert_eq!("root/globex/{section}/@value", partial.json_type_override_templates[0].0);
+    vars.insert("section".to_owned(), "config".to_owned());
+    let resolved = partial.expand_template_overrides(&vars);
+    assert!(resolved.json_type_override_templates.is_empty());
+    let json = xml_str_to_json(xml, &resolved).unwrap();
+    assert_eq!(json!("007"), json["root"]["globex"]["config"]["@value"]);
+    assert_eq!(json!(7), json["root"]["acme"]["config"]["@value"]);
+}
+
+#[test]
+fn test_json_type_chain() {
+    let chain = JsonType::Chain(vec![JsonType::AlwaysInt, JsonType::Null(vec!["N/A"]), JsonType::AlwaysString]);
+    assert_eq!(json!(42), parse_text("42", false, &chain));
+    assert_eq!(json!(-7), parse_text("-7", false, &chain));
+    assert_eq!(Value::Null, parse_text("N/A", false, &chain));
+    assert_eq!(json!("4.5"), parse_text("4.5", false, &chain));
+    assert_eq!(json!("true"), parse_text("true", false, &chain));
+
+    // `Infer` fails for strings and `Bool` for values not in the list
+    let chain = JsonType::Chain(vec![JsonType::Bool(vec!["yes"]), JsonType::Infer, JsonType::Null(vec!["-"])]);
+    assert_eq!(json!(true), parse_text("yes", false, &chain));
+    assert_eq!(json!(1.5), parse_text("1.5", false, &chain));
+    assert_eq!(Value::Null, parse_text("-", false, &chain));
+    assert_eq!(json!("no"), parse_text("no", false, &chain));
+
+    // one level of nested chains
+    let nested = JsonType::Chain(vec![JsonType::Chain(vec![JsonType::AlwaysInt]), JsonType::AlwaysString]);
+    assert_eq!(json!(3), parse_text("3", false, &nested));
+    let too_deep = JsonType::Chain(vec![JsonType::Chain(vec![JsonType::Chain(vec![JsonType::AlwaysInt])])]);
+    assert_eq!(json!("3"), parse_text("3", false, &too_deep));
+
+    assert_eq!(json!(7), parse_text("007", true, &JsonType::AlwaysInt));
+    assert_eq!(json!("x"), parse_text("x", false, &JsonType::AlwaysInt));
+    assert_eq!(Value::Null, parse_text("-", false, &JsonType::Null(vec!["-"])));
+    assert_eq!(json!("1"), parse_text("1", false, &JsonType::Null(vec!["-"])));
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_json_type_chain_override() {
+    let chain = JsonType::Chain(vec![JsonType::AlwaysInt, JsonType::Null(vec!["N/A"]), JsonType::AlwaysString]);
+    let config = Config::new_with_defaults().add_json_type_override("/a/qty", JsonArray::Infer(chain));
+    let documents = [
+        ("<a><qty>12</qty></a>", json!(12)),
+        ("<a><qty>N/A</qty></a>", Value::Null),
+        ("<a><qty>1e3</qty></a>", json!("1e3")),
+    ];
+    for (xml, expected) in &documents {
+        assert_eq!(*expected, xml_str_to_json(xml, &config).unwrap()["a"]["qty"]);
+    }
+}
+
+#[test]
+fn test_virtual_attributes() {
+    let xml = r#"<users><user first="Ada" last="Lovelace" full_name="x"/><user first="Alan" last="Turing"/><user first="Grace"/><group first="a" last="b"/></users>"#;
+    let config = Config::new_with_defaults()
+        .add_virtual_attribute("/users/user", "full_name", |attrs| {
+            let first = attrs.get("@first")?.as_str()?;
+            let last = attrs.get("@last")?.as_str()?;
+            Some(json!(format!("{} {}", first, last)))
+        })
+        .add_virtual_attribute("users/user", "@full_name", |_| Some(json!("clash")));
+
+    let expected = json!({"users": {
+        "user": [
+            {"@first": "Ada", "@last": "Lovelace", "@full_name": "x", "full_name": "Ada Lovelace"},
+            {"@first": "Alan", "@last": "Turing", "full_name": "Alan Turing", "@full_name": "clash"},
+            {"@first": "Grace", "@full_name": "clash"}
+        ],
+        "group": {"@first": "a", "@last": "b"}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_text_join() {
+    let comment_split = r#"<p>foo<!--x-->bar</p>"#;
+    let cdata_split = r#"<p id="1">foo <![CDATA[<bar>]]> 42 </p>"#;
+    let mut config = Config::new_with_defaults();
+
+    assert_eq!(json!({"p": "foobar"}), xml_str_to_json(comment_split, &config).unwrap());
+    assert_eq!(json!({"p": {"@id": 1, "#text": "foo <bar> 42"}}), xml_str_to_json(cdata_split, &config).unwrap());
+
+    config.text.text_join = TextJoin::Space;
+    assert_eq!(json!({"p": "foo bar"}), xml_str_to_json(comment_split, &config).unwrap());
+    assert_eq!(json!({"p": {"@id": 1, "#text": "foo <bar> 42"}}), xml_str_to_json(cdata_split, &config).unwrap());
+
+    config.text.text_join = TextJoin::Separator("|".to_owned());
+    assert_eq!(json!({"p": "foo|bar"}), xml_str_to_json(comment_split, &config).unwrap());
+    assert_eq!(json!({"p": {"@id": 1, "#text": "foo|<bar>|42"}}), xml_str_to_json(cdata_split, &config).unwrap());
+
+    config.text.text_join = TextJoin::KeepSegments;
+    assert_eq!(json!({"p": {"#text": ["foo", "bar"]}}), xml_str_to_json(comment_split, &config).unwrap());
+    assert_eq!(
+        json!({"p": {"@id": 1, "#text": ["foo", "<bar>", 42]}}),
+        xml_str_to_json(cdata_split, &config).unwrap()
+    );
+    // a single text node is not affected
+    assert_eq!(json!({"p": "foo"}), xml_str_to_json("<p>foo</p>", &config).unwrap());
+    // segments around child elements
+    let json = xml_str_to_json("<p>foo<b>1</b>bar</p>", &config).unwrap();
+    assert_eq!(json!({"p": {"#text": ["foo", "bar"], "b": 1}}), json);
+}
+
+#[test]
+fn test_xml_string_to_json_focused() {
+    let xml = r#"<doc><meta>m</meta><body><a>1</a><b>2</b></body><trailer id="t"/></doc>"#;
+    let mut config = Config::new_with_defaults();
+    let full = xml_str_to_json(xml, &config).unwrap();
+
+    // the order of the conversion doesn't change the output
+    let priority_paths = ["/doc/trailer/@id", "/doc/body/b"];
+    assert_eq!(full, xml_string_to_json_focused(xml.to_owned(), &config, &priority_paths).unwrap());
+
+    config.output.stop_after_priority_paths = true;
+    let json = xml_string_to_json_focused(xml.to_owned(), &config, &priority_paths).unwrap();
+    assert_eq!(json!({"doc": {"body": {"a": 1, "b": 2}, "trailer": {"@id": "t"}}}), json);
+
+    let json = xml_string_to_json_focused(xml.to_owned(), &config, &["/doc/body/b"]).unwrap();
+    assert_eq!(json!({"doc": {"body": {"b": 2}}}), json);
+
+    // paths not in the document never stop the conversion
+    let json = xml_string_to_json_focused(xml.to_owned(), &config, &["/doc/body/b", "/doc/missing"]).unwrap();
+    assert_eq!(full, json);
+}
+
+#[test]
+fn test_progress() {
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    /// Returns at most 8 bytes per read, like a slow file
+    struct Trickle<'a>(&'a [u8]);
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = std::cmp::min(std::cmp::min(8, buf.len()), self.0.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    let xml = r#"<export><row id="1"><v>a</v></row><row id="2"><v>b</v></row><row id="3"/></export>"#;
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let collected = reports.clone();
+    let mut config = Config::new_with_defaults();
+    config.diagnostics.progress =
+        Some(ProgressConfig::new(20, move |progress| collected.lock().unwrap().push(progress)));
+
+    let check = |records: u64, elements: u64| {
+        let reports = std::mem::take(&mut *reports.lock().unwrap());
+        // a report every 20 bytes and the final one
+        assert_eq!(xml.len() / 20 + 1, reports.len());
+        for pair in reports.windows(2) {
+            assert!(pair[0].bytes_read <= pair[1].bytes_read);
+            assert!(pair[0].elements <= pair[1].elements && pair[0].records <= pair[1].records);
+        }
+        let last = Progress {
+            bytes_read: xml.len() as u64,
+            elements,
+            records,
+        };
+        assert_eq!(Some(&last), reports.last());
+    };
+
+    let json = Converter::new(&config).convert_reader(Trickle(xml.as_bytes())).unwrap();
+    assert_eq!(json, xml_str_to_json(xml, &config).unwrap());
+    check(1, 6);
+
+    let mut output = Vec::new();
+    let lines = xml_to_ndjson(Trickle(xml.as_bytes()), &mut output, "/export/row", &config, false).unwrap();
+    assert_eq!(3, lines);
+    check(3, 5);
+}
+
+#[test]
+fn test_text_node_mapper() {
+    let xml = r#"<a id="7"><date>2020-01-02</date><n>12</n></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.values.text_node_mapper = Some(TextNodeMapper(Arc::new(|path, text| {
+        if path.ends_with("/date") {
+            json!({ "date": text })
+        } else {
+            Value::String([path, "=", text].concat())
+        }
+    })));
+    let expected = json!({"a": {"@id": "/a/@id=7", "date": {"date": "2020-01-02"}, "n": "/a/n=12"}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // path-specific overrides take precedence
+    #[cfg(feature = "json_types")]
+    {
+        let config = config.add_json_type_override("/a/n", JsonArray::Infer(JsonType::AlwaysString));
+        let expected = json!({"a": {"@id": "/a/@id=7", "date": {"date": "2020-01-02"}, "n": "12"}});
+        assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    }
+}
+
+#[test]
+fn test_auto_flatten_plural_wrappers() {
+    let xml = r#"<doc>
+        <items><item>1</item><item id="2"/></items>
+        <boxes><box>a</box></boxes>
+        <people><person>Ada</person><person>Alan</person></people>
+        <bus><bu>x</bu></bus>
+        <tags count="1"><tag>t</tag></tags>
+        <mixed><mix>1</mix><other>2</other></mixed>
+    </doc>"#;
+    let mut config = Config::new_with_defaults();
+    let (json, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    assert_eq!(json!({"item": [1, {"@id": 2}]}), json["doc"]["items"]);
+    assert!(warnings.is_empty());
+
+    config.arrays.auto_flatten_plural_wrappers = true;
+    config.arrays.plural_pairs.insert("people".to_owned(), "person".to_owned());
+    config.arrays.plural_wrapper_exclusions.push("/doc/bus".to_owned());
+    let (json, warnings) = xml_str_to_json_with_warnings(xml, &config).unwrap();
+    let expected = json!({"doc": {
+        "items": [1, {"@id": 2}],
+        "boxes": ["a"],
+        "people": ["Ada", "Alan"],
+        "bus": {"bu": "x"},
+        "tags": {"@count": 1, "tag": "t"},
+        "mixed": {"mix": 1, "other": 2}
+    }});
+    assert_eq!(expected, json);
+    let flattened = |path: &str, item: &str| Warning::PluralWrapperFlattened {
+        path: path.to_owned(),
+        item: item.to_owned(),
+    };
+    assert_eq!(
+        vec![
+            flattened("/doc/items", "item"),
+            flattened("/doc/boxes", "box"),
+            flattened("/doc/people", "person")
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn test_xml_string_into_map() {
+    let config = Config::new_with_defaults();
+    let mut map = Map::new();
+
+    xml_string_into_map("<a><b>1</b></a>".to_owned(), &config, &mut map).unwrap();
+    assert_eq!(json!({"a": {"b": 1}}), Value::Object(map.clone()));
+
+    // the map is not cleared between the calls
+    xml_string_into_map("<c x=\"y\"/>".to_owned(), &config, &mut map).unwrap();
+    assert_eq!(json!({"a": {"b": 1}, "c": {"@x": "y"}}), Value::Object(map.clone()));
+
+    xml_string_into_map("<a>2</a>".to_owned(), &config, &mut map).unwrap();
+    assert_eq!(json!({"a": 2, "c": {"@x": "y"}}), Value::Object(map.clone()));
+
+    // failed conversions leave the map as it was
+    assert!(xml_string_into_map("<a></b>".to_owned(), &config, &mut map).is_err());
+    assert_eq!(json!({"a": 2, "c": {"@x": "y"}}), Value::Object(map.clone()));
+
+    map.clear();
+    xml_string_into_map("<a><b>1</b></a>".to_owned(), &config, &mut map).unwrap();
+    assert_eq!(json!({"a": {"b": 1}}), Value::Object(map));
+
+    // the converter adds to the map the same way
+    let mut converter = Converter::new(&config);
+    let mut map = Map::new();
+    map.insert("id".to_owned(), json!(7));
+    map.insert("a".to_owned(), json!("old"));
+    converter.convert_str_into_map("<a><b>1</b></a>", &mut map).unwrap();
+    assert_eq!(json!({"id": 7, "a": {"b": 1}}), Value::Object(map.clone()));
+    converter.convert_str_into_map("<c/>", &mut map).unwrap();
+    assert_eq!(json!({"id": 7, "a": {"b": 1}, "c": {}}), Value::Object(map));
+}
+
+#[test]
+fn test_dual_emit() {
+    let xml = r#"<a id="1"><b k="x">t</b><c>2</c></a>"#;
+    let mut config = Config::new_with_defaults();
+    let mut dual = DualEmitConfig::new("", "value");
+    dual.attrs_key = Some("attrs".to_owned());
+    config.keys.dual_emit = Some(dual.clone());
+
+    let json = xml_str_to_json(xml, &config).unwrap();
+    let expected = json!({"a": {
+        "@id": 1,
+        "attrs": {"id": 1},
+        "b": {"@k": "x", "#text": "t", "attrs": {"k": "x"}, "value": "t"},
+        "c": 2
+    }});
+    assert_eq!(expected, json);
+    assert_eq!(json["a"]["b"]["#text"], json["a"]["b"]["value"]);
+
+    // flat keys with the legacy ones marked as deprecated
+    dual.attrs_key = None;
+    dual.deprecate = Some(KeySet::Legacy);
+    config.keys.dual_emit = Some(dual.clone());
+    let json = xml_str_to_json(xml, &config).unwrap();
+    let expected = json!({"a": {
+        "deprecated_@id": 1,
+        "id": 1,
+        "b": {"deprecated_@k": "x", "deprecated_#text": "t", "k": "x", "value": "t"},
+        "c": 2
+    }});
+    assert_eq!(expected, json);
+
+    // new keys clashing with child elements
+    let err = xml_str_to_json(r#"<a id="1"><id>2</id></a>"#, &config).unwrap_err();
+    assert!(matches!(err, ConversionError::DualKeyClash { ref path, ref key } if path == "/a" && key == "id"));
+
+    // the text property collision is still detected
+    config.text.text_prop_collision = TextPropCollision::Error;
+    config.xml_text_node_prop_name = "text".to_owned();
+    assert!(matches!(
+        xml_str_to_json(r#"<a id="1">t<text>x</text></a>"#, &config),
+        Err(ConversionError::TextPropCollision { .. })
+    ));
+}
+
+#[test]
+fn test_xml_string_to_json_with_progress() {
+    use std::cell::RefCell;
+
+    let rows: String = (0..250).map(|i| format!("<row>{}</row>", i)).collect();
+    let xml = format!("<?xml version=\"1.0\"?><!-- rows --><rows>{}</rows>", rows);
+    let calls = RefCell::new(Vec::new());
+    let config = Config::new_with_defaults();
+
+    let json = xml_string_to_json_with_progress(xml.clone(), &config, |processed, total| {
+        calls.borrow_mut().push((processed, total))
+    })
+    .unwrap();
+    assert_eq!(xml_str_to_json(&xml, &config).unwrap(), json);
+
+    let calls = calls.into_inner();
+    assert!(calls.len() > 100);
+    assert_eq!(Some(&(251, 251)), calls.last());
+    for pair in calls.windows(2) {
+        assert!(pair[0].0 <= pair[1].0);
+    }
+    assert!(calls.iter().all(|(processed, total)| processed <= total && *total == 251));
+
+    // small documents get at least the final call
+    let calls = RefCell::new(Vec::new());
+    xml_string_to_json_with_progress("<a/>".to_owned(), &config, |processed, total| {
+        calls.borrow_mut().push((processed, total))
+    })
+    .unwrap();
+    assert_eq!(vec![(1, 1), (1, 1)], calls.into_inner());
+}
+
+#[cfg(feature = "schema_validation")]
+#[test]
+fn test_xml_string_to_validated_json() {
+    let schema = json!({
+        "type": "object",
+        "properties": {"order": {
+            "type": "object",
+            "properties": {
+                "@id": {"type": "string"},
+                "item": {"type": "array", "items": {"properties": {"zip": {"type": "string"}}}}
+            }
+        }}
+    });
+    let config = Config::new_with_defaults();
+
+    // inference turns the zip code into a number
+    let xml = r#"<order id="a1"><item><zip>A-1</zip></item><item><zip>01234</zip></item></order>"#;
+    match xml_string_to_validated_json(xml, &config, &schema) {
+        Err(ValidationError::Invalid { pointer, xml_path, .. }) => {
+            assert_eq!("/order/item/1/zip", pointer);
+            assert_eq!("/order/item/zip", xml_path);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // attributes
+    let xml = r#"<order id="7"><item><zip>A-1</zip></item></order>"#;
+    match xml_string_to_validated_json(xml, &config, &schema) {
+        Err(ValidationError::Invalid { pointer, xml_path, .. }) => {
+            assert_eq!("/order/@id", pointer);
+            assert_eq!("/order/@id", xml_path);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let xml = r#"<order id="a1"><item><zip>A-1</zip></item><item><zip>B-2</zip></item></order>"#;
+    let json = xml_string_to_validated_json(xml, &config, &schema).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), json);
+
+    assert!(matches!(
+        xml_string_to_validated_json("<a></b>", &config, &schema),
+        Err(ValidationError::Conversion(_))
+    ));
+}
+
+#[test]
+fn test_tail_text_handling() {
+    let xml = r#"<p>before<b>x</b>after <i>y</i> end<br/></p>"#;
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"p": {"#text": "beforeafter  end", "b": "x", "i": "y", "br": {}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.text.tail_text_handling = TailTextHandling::Ignore;
+    let expected = json!({"p": {"#text": "before", "b": "x", "i": "y", "br": {}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.text.tail_text_handling = TailTextHandling::AsField("tail".to_owned());
+    let expected = json!({"p": {
+        "#text": "before",
+        "b": {"#text": "x", "tail": "after"},
+        "i": {"#text": "y", "tail": "end"},
+        "br": {}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // the tail text of a child doesn't make the parent an element with text
+    let json = xml_str_to_json(r#"<p><b id="1">x</b>after</p>"#, &config).unwrap();
+    assert_eq!(json!({"p": {"b": {"@id": 1, "#text": "x", "tail": "after"}}}), json);
+
+    // older profiles drop the child elements of elements with text
+    config.profile = ConversionProfile::V0_6;
+    config.text.tail_text_handling = TailTextHandling::AppendToParent;
+    assert_eq!(json!({"p": "beforeafter  end"}), xml_str_to_json(xml, &config).unwrap());
+    config.text.tail_text_handling = TailTextHandling::Ignore;
+    assert_eq!(json!({"p": "before"}), xml_str_to_json(xml, &config).unwrap());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_empty_configured_arrays() {
+    let configured =
+        Config::new_with_defaults().add_json_type_override("/doc/items/item", JsonArray::Always(JsonType::Infer));
+    let not_configured = Config::new_with_defaults();
+
+    let cases = [
+        // absent container
+        ("<doc><x>1</x></doc>", json!({"doc": {"x": 1}}), json!({"doc": {"x": 1}})),
+        // empty container
+        ("<doc><items/></doc>", json!({"doc": {"items": {"item": []}}}), json!({"doc": {"items": {}}})),
+        (
+            "<doc><items><other>2</other></items></doc>",
+            json!({"doc": {"items": {"other": 2, "item": []}}}),
+            json!({"doc": {"items": {"other": 2}}}),
+        ),
+        // non-empty container
+        (
+            "<doc><items><item>1</item></items></doc>",
+            json!({"doc": {"items": {"item": [1]}}}),
+            json!({"doc": {"items": {"item": 1}}}),
+        ),
+    ];
+    for (xml, expected_configured, expected_not_configured) in cases.iter() {
+        assert_eq!(*expected_configured, xml_str_to_json(xml, &configured).unwrap(), "{}", xml);
+        assert_eq!(*expected_not_configured, xml_str_to_json(xml, &not_configured).unwrap(), "{}", xml);
+    }
+
+    // the absent container follows the absent field policy
+    let config = configured.add_absent_field_policy("/doc/items", AbsentPolicy::Null);
+    assert_eq!(json!({"doc": {"x": 1, "items": null}}), xml_str_to_json("<doc><x>1</x></doc>", &config).unwrap());
+
+    // empty containers get the array whatever the empty element handling
+    let mut config = config;
+    config.empty_element_handling = NullValue::Null;
+    let json = xml_str_to_json("<doc><items/></doc>", &config).unwrap();
+    assert_eq!(json!({"doc": {"items": {"item": []}}}), json);
+
+    // with `PathAnchor::Anywhere` the parent path may be a suffix of the path of the container
+    let mut config = Config::new_with_defaults().add_json_type_override("/items/item", JsonArray::Always(JsonType::Infer));
+    config.path_anchor = PathAnchor::Anywhere;
+    let mut converter = Converter::new(&config);
+    for xml in ["<doc><items/></doc>", "<a><b><items/></b><myitems/></a>"].iter() {
+        let json = converter.convert_str(xml).unwrap();
+        assert_eq!(converter.convert_str(xml).unwrap(), json);
+        assert_eq!(xml_str_to_json(xml, &config).unwrap(), json);
+    }
+    let json = converter.convert_str("<a><b><items/></b><myitems/></a>").unwrap();
+    assert_eq!(json!({"a": {"b": {"items": {"item": []}}, "myitems": {}}}), json);
+}
+
+#[test]
+fn test_namespace_default_handling() {
+    let xml = r#"<html xmlns="http://www.w3.org/1999/xhtml">
+        <head><title>T</title></head>
+        <body class="x"><p>hi</p><svg:svg xmlns:svg="http://www.w3.org/2000/svg" width="1"/></body>
+    </html>"#;
+    let xhtml = "http://www.w3.org/1999/xhtml";
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"html": {
+        "head": {"title": "T"},
+        "body": {"@class": "x", "p": "hi", "svg": {"@width": 1}}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.input.namespace_default_handling = NamespaceDefaultHandling::AddToElement("_ns".to_owned());
+    let expected = json!({"html": {
+        "_ns": xhtml,
+        "head": {"_ns": xhtml, "title": "T"},
+        "body": {"_ns": xhtml, "@class": "x", "p": "hi", "svg": {"@width": 1}}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.input.namespace_default_handling = NamespaceDefaultHandling::PrefixKeys("h:".to_owned());
+    let expected = json!({"h:html": {
+        "h:head": {"h:title": "T"},
+        "h:body": {"@class": "x", "h:p": "hi", "svg": {"@width": 1}}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // documents without a default namespace are not affected
+    assert_eq!(json!({"a": {"b": 1}}), xml_str_to_json("<a><b>1</b></a>", &config).unwrap());
+}
+
+#[test]
+fn test_embed_config() {
+    let xml = r#"<doc id="007"><items><item>1</item><item>2</item></items><note>a <b>b</b> c</note><e/></doc>"#;
+    let mut config = Config::new_with_defaults();
+    config.leading_zero_as_string = true;
+    config.xml_attr_prefix = "_".to_owned();
+    config.empty_element_handling = NullValue::Null;
+    config.text.text_join = TextJoin::Separator("|".to_owned());
+    config.text.tail_text_handling = TailTextHandling::AsField("tail".to_owned());
+    config.attributes.promote_attributes = PromoteAttrs::Named(vec!["id".to_owned()]);
+    config.values.inference.booleans = false;
+    config.output.max_json_depth = Some(8);
+    config.values.text_node_mapper = Some(TextNodeMapper(Arc::new(|_, text| Value::String(text.to_owned()))));
+    config.injection.embed_config = Some("_config".to_owned());
+
+    let mut json = xml_str_to_json(xml, &config).unwrap();
+    let record = json.as_object_mut().unwrap().remove("_config").unwrap();
+    assert_eq!(env!("CARGO_PKG_VERSION"), record["crate_version"]);
+    assert_eq!(json!(["text_node_mapper"]), record["elided"]);
+    assert_eq!(json!({"Separator": "|"}), record["options"]["text_join"]);
+
+    // the restored config gives the same output, apart from the functions which have to be set again
+    let mut restored = Config::from_embedded_config(&record).unwrap();
+    assert_eq!(Some("_config".to_owned()), restored.injection.embed_config);
+    assert!(restored.values.text_node_mapper.is_none());
+    restored.values.text_node_mapper = config.values.text_node_mapper.clone();
+    let mut again = xml_str_to_json(xml, &restored).unwrap();
+    assert_eq!(record, again.as_object_mut().unwrap().remove("_config").unwrap());
+    assert_eq!(json, again);
+
+    let mut broken = record.clone();
+    broken["options"]["max_json_depth"] = json!("eight");
+    assert!(matches!(
+        Config::from_embedded_config(&broken),
+        Err(ConversionError::InvalidEmbeddedConfig(_))
+    ));
+}
+
+#[test]
+fn test_mixed_content_text_around_children() {
+    let xml = r#"<p>a <x/> b</p>"#;
+    let mut config = Config::new_with_defaults();
+
+    // the child elements are kept next to the text, which is concatenated as-is and trimmed
+    assert_eq!(json!({"p": {"#text": "a  b", "x": {}}}), xml_str_to_json(xml, &config).unwrap());
+    let json = xml_str_to_json("<p>hello <b>world</b> again</p>", &config).unwrap();
+    assert_eq!(json!({"p": {"#text": "hello  again", "b": "world"}}), json);
+
+    // the pieces are joined according to `TextOptions::text_join`
+    config.text.text_join = TextJoin::Space;
+    assert_eq!(json!({"p": {"#text": "a b", "x": {}}}), xml_str_to_json(xml, &config).unwrap());
+    config.text.text_join = TextJoin::KeepSegments;
+    assert_eq!(json!({"p": {"#text": ["a", "b"], "x": {}}}), xml_str_to_json(xml, &config).unwrap());
+
+    // only the 0.6 rules drop the child elements
+    config.text.text_join = TextJoin::Concat;
+    config.profile = ConversionProfile::V0_6;
+    assert_eq!(json!({"p": "a  b"}), xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_reapply() {
+    let xml = r#"<doc id="1"><item sku="a">x<b>1</b></item><item sku="b">y</item></doc>"#;
+    let mut old = Config::new_with_defaults();
+    old.injection.embed_config = Some("_config".to_owned());
+
+    // renames are applied to the converted document
+    let mut new = old.clone();
+    new.xml_attr_prefix = "attr_".to_owned();
+    new.xml_text_node_prop_name = "value".to_owned();
+    let mut json = xml_str_to_json(xml, &old).unwrap();
+    let report = reapply(&mut json, &old, &new).unwrap();
+    assert_eq!(vec!["xml_attr_prefix", "xml_text_node_prop_name"], report.applied);
+    assert!(report.requires_reconversion.is_empty());
+    assert_eq!(xml_str_to_json(xml, &new).unwrap(), json);
+
+    // the numbers can't be turned back into the original text
+    let mut new = old.clone();
+    new.values.inference.numbers = false;
+    let mut json = xml_str_to_json(xml, &old).unwrap();
+    let converted = json.clone();
+    let report = reapply(&mut json, &old, &new).unwrap();
+    assert!(report.applied.is_empty());
+    assert_eq!(vec!["inference"], report.requires_reconversion);
+    assert_eq!(converted, json);
+
+    // renames clashing with other properties need the XML too
+    let mut new = old.clone();
+    new.xml_attr_prefix = String::new();
+    let mut json = xml_str_to_json(r#"<a b="1"><b>2</b></a>"#, &old).unwrap();
+    let report = reapply(&mut json, &old, &new).unwrap();
+    assert_eq!(vec!["xml_attr_prefix"], report.requires_reconversion);
+}
+
+#[test]
+fn test_coerce_bool_attrs() {
+    let xml = r#"<x enabled="True" visible="FALSE" shown="YES" hidden="no" mode="On"><flag>True</flag></x>"#;
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"x": {
+        "@enabled": "True", "@visible": "FALSE", "@shown": "YES", "@hidden": "no", "@mode": "On", "flag": "True"
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // only the attributes are coerced
+    config.values.coerce_bool_attrs = true;
+    let expected = json!({"x": {
+        "@enabled": true, "@visible": false, "@shown": true, "@hidden": false, "@mode": "On", "flag": "True"
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    config.values.bool_truthy = vec!["on".to_owned()];
+    config.values.bool_falsy = vec!["off".to_owned()];
+    let expected = json!({"x": {
+        "@enabled": "True", "@visible": "FALSE", "@shown": "YES", "@hidden": "no", "@mode": true, "flag": "True"
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    #[cfg(feature = "json_types")]
+    {
+        let config = config.add_json_type_override("/x/@mode", JsonArray::Infer(JsonType::AlwaysString));
+        assert_eq!(json!("On"), xml_str_to_json(xml, &config).unwrap()["x"]["@mode"]);
+    }
+}
+
+#[test]
+fn test_xml_string_to_json_string_types() {
+    let xml = r#"<a b="1">x</a>"#;
+    let config = Config::new_with_defaults();
+    let expected = xml_string_to_json(String::from(xml), &config).unwrap();
+    assert_eq!(expected, xml_string_to_json(xml, &config).unwrap());
+    assert_eq!(expected, xml_string_to_json(Arc::<str>::from(xml), &config).unwrap());
+    assert_eq!(expected, xml_string_to_json(std::borrow::Cow::Borrowed(xml), &config).unwrap());
+}
+
+#[test]
+fn test_ordered_content() {
+    let xml = r#"<doc>
+        <p class="intro">one <em>two <b>3</b> four</em> five</p>
+        <p>x<br/>y</p>
+    </doc>"#;
+    let mut config = Config::new_with_defaults();
+    config.text.ordered_content = true;
+    let expected = json!({"doc": {"#content": [
+        {"p": {
+            "@class": "intro",
+            "#content": ["one ", {"em": {"#content": ["two ", {"b": 3}, " four"]}}, " five"]
+        }},
+        {"p": {"#content": ["x", {"br": {}}, "y"]}}
+    ]}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // elements without child elements are converted as usual
+    config.text.content_key = "$c".to_owned();
+    let json = xml_str_to_json(r#"<a><b id="1">x</b><c>2</c></a>"#, &config).unwrap();
+    assert_eq!(json!({"a": {"$c": [{"b": {"@id": 1, "#text": "x"}}, {"c": 2}]}}), json);
+}
+
+#[test]
+fn test_key_charset() {
+    let xml = r#"<данные id="1"><имя>A</имя><x😀>2</x😀><x😃>3</x😃></данные>"#;
+    let mut config = Config::new_with_defaults();
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!({"данные": {"@id": 1, "имя": "A", "x😀": 2, "x😃": 3}}), json);
+
+    config.keys.key_charset = KeyCharset::AsciiOnly {
+        replacement: "_".to_owned(),
+    };
+    let json = xml_str_to_json(xml, &config).unwrap();
+    let data = json["______"].as_object().unwrap();
+    assert_eq!(json!(1), data["@id"]);
+    assert_eq!(json!("A"), data["___"]);
+    assert_eq!(json!(2), data["x_"]);
+    // the second name with the same ASCII form gets a hash instead of being merged into an array
+    let hashed: Vec<&String> = data.keys().filter(|key| key.starts_with("x_~")).collect();
+    assert_eq!(1, hashed.len());
+    assert_eq!(11, hashed[0].len());
+    assert_eq!(json!(3), data[hashed[0].as_str()]);
+    assert_eq!(json, xml_str_to_json(xml, &config).unwrap());
+
+    // the options ending up in property names must be ASCII too
+    config.xml_attr_prefix = "§".to_owned();
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::NonAsciiKeyOption { option }) => assert_eq!("xml_attr_prefix", option),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    config.xml_attr_prefix = "@".to_owned();
+    config.keys.key_charset = KeyCharset::ErrorOnNonAscii;
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::NonAsciiKey { path, key }) => {
+            assert_eq!("/данные", path);
+            assert_eq!("данные", key);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    let json = xml_str_to_json(r#"<a b="1"><c>2</c></a>"#, &config).unwrap();
+    assert_eq!(json!({"a": {"@b": 1, "c": 2}}), json);
+}
+
+#[cfg(feature = "memoize")]
+#[test]
+fn test_xml_string_to_json_memoized() {
+    let cache = XmlToJsonCache::new();
+    let config = Config::new_with_defaults();
+    let xml = r#"<a b="1"><c>x</c></a>"#;
+
+    let first = xml_string_to_json_memoized(xml.to_owned(), &config, &cache).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), *first);
+    let second = xml_string_to_json_memoized(xml.to_owned(), &config, &cache).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(1, cache.len());
+
+    // other documents and configs are converted again
+    let other = xml_string_to_json_memoized(r#"<a b="2"/>"#.to_owned(), &config, &cache).unwrap();
+    assert_eq!(json!({"a": {"@b": 2}}), *other);
+    let mut config = config;
+    config.xml_attr_prefix = "_".to_owned();
+    let renamed = xml_string_to_json_memoized(xml.to_owned(), &config, &cache).unwrap();
+    assert_eq!(json!({"a": {"_b": 1, "c": "x"}}), *renamed);
+    assert_eq!(3, cache.len());
+
+    // functions may give a different output every time
+    config.values.text_node_mapper = Some(TextNodeMapper(Arc::new(|_, text| Value::String(text.to_owned()))));
+    let mapped = xml_string_to_json_memoized(xml.to_owned(), &config, &cache).unwrap();
+    assert_eq!(json!({"a": {"_b": "1", "c": "x"}}), *mapped);
+    assert_eq!(3, cache.len());
+}
+
+#[test]
+fn test_xml_reader_to_json() {
+    let config = Config::new_with_defaults();
+    let file = std::fs::File::open("./test_xml_files/numerical.xml").unwrap();
+    let json = xml_reader_to_json(file, &config).unwrap();
+    let xml = std::fs::read_to_string("./test_xml_files/numerical.xml").unwrap();
+    assert_eq!(xml_str_to_json(&xml, &config).unwrap(), json);
+
+    assert!(matches!(
+        xml_reader_to_json(&b"<a>\xFF</a>"[..], &config),
+        Err(ConversionError::Io(_))
+    ));
+}
+
+#[test]
+fn test_trim_text() {
+    let xml = "<a><b>  42  </b><c>\n</c><d>42</d><e x=\"v \" y=\"7\"/></a>";
+    let mut config = Config::new_with_defaults();
+    let expected = json!({"a": {"b": 42, "c": {}, "d": 42, "e": {"@x": "v", "@y": 7}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // only the values without whitespace around them are inferred
+    config.text.trim_text = false;
+    let expected = json!({"a": {"b": "  42  ", "c": "\n", "d": 42, "e": {"@x": "v ", "@y": 7}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // the indentation around child elements is not text
+    let json = xml_str_to_json("<a>\n  <b> x </b>\n</a>", &config).unwrap();
+    assert_eq!(json!({"a": {"b": " x "}}), json);
+}
+
+#[cfg(feature = "extract")]
+#[test]
+fn test_extract_values() {
+    let mut xml = String::from(r#"<?xml version="1.0"?><order id="A-1">"#);
+    for i in 0..20_000 {
+        xml.push_str(&format!(r#"<item sku="{}"><qty>{}</qty><note>n<![CDATA[&]]></note></item>"#, i, i % 7));
+    }
+    xml.push_str("<status>007</status><paid>true</paid></order>");
+    let paths = ["/order/@id", "order/status", "/order/paid", "/order/*/qty", "/order/item/@sku", "/order/missing"];
+    let mut config = Config::new_with_defaults();
+
+    let values = extract_values(xml.as_bytes(), &paths, &config).unwrap();
+    assert_eq!(vec![json!("A-1")], values["/order/@id"]);
+    assert_eq!(vec![json!(7)], values["order/status"]);
+    assert_eq!(vec![json!(true)], values["/order/paid"]);
+    assert_eq!(20_000, values["/order/*/qty"].len());
+    assert_eq!(json!(6), values["/order/*/qty"][6]);
+    assert_eq!(json!(19_999), values["/order/item/@sku"][19_999]);
+    assert!(values["/order/missing"].is_empty());
+
+    // the values are the same as in the converted document
+    let json = xml_str_to_json(&xml, &config).unwrap();
+    let notes = extract_values(xml.as_bytes(), &["/order/item/note"], &config).unwrap();
+    assert_eq!(json["order"]["item"][3]["note"], notes["/order/item/note"][3]);
+
+    #[cfg(feature = "json_types")]
+    {
+        let config = config
+            .clone()
+            .add_json_type_override("/order/status", JsonArray::Infer(JsonType::AlwaysString));
+        let values = extract_values(xml.as_bytes(), &["/order/status"], &config).unwrap();
+        assert_eq!(vec![json!("007")], values["/order/status"]);
+    }
+
+    // the rest of the document is not read once all values were found
+    let truncated = r#"<order id="A-1"><status>new</status><item>"#;
+    assert!(extract_values(truncated.as_bytes(), &["/order/@id", "/order/status"], &config).is_err());
+    config.output.stop_after_priority_paths = true;
+    let values = extract_values(truncated.as_bytes(), &["/order/@id", "/order/status"], &config).unwrap();
+    assert_eq!(vec![json!("new")], values["/order/status"]);
+}
+
+#[test]
+fn test_forced_array_paths() {
+    let config = Config::new_with_defaults()
+        .add_forced_array_path("/order/item")
+        .add_forced_array_path("order/item/tag");
+
+    // a single element becomes an array of one
+    let json = xml_str_to_json(r#"<order><item sku="1"><tag>a</tag></item><note>x</note></order>"#, &config).unwrap();
+    assert_eq!(
+        json!({"order": {"item": [{"@sku": 1, "tag": ["a"]}], "note": "x"}}),
+        json
+    );
+
+    // repeated elements are arrays as before
+    let json = xml_str_to_json(
+        r#"<order><item sku="1"><tag>a</tag><tag>b</tag></item><item sku="2"/></order>"#,
+        &config,
+    )
+    .unwrap();
+    assert_eq!(
+        json!({"order": {"item": [{"@sku": 1, "tag": ["a", "b"]}, {"@sku": 2}]}}),
+        json
+    );
+
+    // only the exact paths are forced
+    let json = xml_str_to_json(r#"<list><order><tag>a</tag></order></list>"#, &config).unwrap();
+    assert_eq!(json!({"list": {"order": {"tag": "a"}}}), json);
+}
+
+#[test]
+fn test_force_float_format() {
+    let xml = r#"<a><whole>42.0</whole><int>42</int><frac>1.5</frac><big>2e16</big><small>1e-7</small></a>"#;
+    let mut config = Config::new_with_defaults();
+    let json = serde_json::to_string(&xml_str_to_json(xml, &config).unwrap()).unwrap();
+    assert!(json.contains(r#""whole":42.0"#));
+    assert!(json.contains(r#""big":2e+16"#));
+    assert!(json.contains(r#""small":1e-7"#));
+
+    config.values.force_float_format = true;
+    let json = serde_json::to_string(&xml_str_to_json(xml, &config).unwrap()).unwrap();
+    assert!(json.contains(r#""whole":42.0"#));
+    assert!(json.contains(r#""int":42,"#));
+    assert!(json.contains(r#""frac":1.5"#));
+    if cfg!(feature = "arbitrary_precision") {
+        assert!(config.validate().is_ok());
+        assert!(json.contains(r#""big":20000000000000000.0"#));
+        assert!(json.contains(r#""small":0.0000001"#));
+    } else {
+        assert!(matches!(config.validate(), Err(ConversionError::MissingFeature { .. })));
+        assert!(json.contains(r#""big":2e+16"#));
+    }
+}
+
+#[test]
+fn test_duplicate_source_attributes() {
+    let xml = r#"<a href="1" id="x" href="2"><b href="3"/></a>"#;
+    let mut config = Config::new_with_defaults();
+
+    // the default parser rejects the document whatever the policy
+    config.attributes.duplicate_source_attributes = DupAttr::Last;
+    assert!(matches!(xml_str_to_json(xml, &config), Err(ConversionError::Xml(_))));
+
+    #[cfg(feature = "quickxml-parser")]
+    {
+        let convert = |dup_attr: DupAttr| {
+            let mut config = Config::new_with_defaults();
+            config.attributes.duplicate_source_attributes = dup_attr;
+            xml_string_to_json_with_parser::<QuickXmlParser>(xml.to_owned(), &config)
+        };
+        assert!(matches!(convert(DupAttr::Error), Err(ConversionError::Xml(_))));
+        assert_eq!(
+            json!({"a": {"@href": 1, "@id": "x", "b": {"@href": 3}}}),
+            convert(DupAttr::First).unwrap()
+        );
+        assert_eq!(
+            json!({"a": {"@href": 2, "@id": "x", "b": {"@href": 3}}}),
+            convert(DupAttr::Last).unwrap()
+        );
+        assert_eq!(
+            json!({"a": {"@href": [1, 2], "@id": "x", "b": {"@href": 3}}}),
+            convert(DupAttr::MergeArray).unwrap()
+        );
+
+        // the parser doesn't reject NUL, which is kept in attributes without repeats
+        let mut config = Config::new_with_defaults();
+        config.attributes.duplicate_source_attributes = DupAttr::MergeArray;
+        assert_eq!(
+            json!({"a": {"@href": ["1\u{0}2", 3], "@id": "x\u{0}y"}}),
+            xml_string_to_json_with_parser::<QuickXmlParser>(
+                "<a href=\"1\u{0}2\" id=\"x\u{0}y\" href=\"3\"/>".to_owned(),
+                &config
+            )
+            .unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_cdata_as_string() {
+    let xml = "<a><v> <![CDATA[00123]]> </v><t>true</t><p><![CDATA[ <b>1 & 2</b>\n  line 2 ]]></p>\
+               <m>id: <![CDATA[ 5 ]]></m><e><![CDATA[]]></e><!-- <![CDATA[ 7 ]]> --></a>";
+    let mut config = Config::new_with_defaults();
+    assert_eq!(json!(123), xml_str_to_json(xml, &config).unwrap()["a"]["v"]);
+
+    config.text.cdata_as_string = true;
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(
+        json!({"a": {"v": "00123", "t": true, "p": " <b>1 & 2</b>\n  line 2 ", "m": "id:  5 ", "e": ""}}),
+        json
+    );
+
+    #[cfg(feature = "extract")]
+    {
+        let values = extract_values(xml.as_bytes(), &["/a/v", "/a/p"], &config).unwrap();
+        assert_eq!(vec![json!("00123")], values["/a/v"]);
+        assert_eq!(json["a"]["p"], values["/a/p"][0]);
+    }
+
+    // the sections are found among the other nodes, and NUL in the text is not taken for one
+    let xml = "<a>\n  <!-- <c> -->\n  <b>007</b>\n  <c>x<?pi?>y <![CDATA[ 008 ]]> </c>\n  \
+               <d e=\"f>\"><![CDATA[009]]></d><g>\u{0}010\u{0}</g>\n</a>";
+    assert_eq!(
+        json!({"a": {"b": 7, "c": "xy  008 ", "d": {"@e": "f>", "#text": "009"}, "g": "\u{0}010\u{0}"}}),
+        xml_str_to_json(xml, &config).unwrap()
+    );
+}
+
+#[test]
+fn test_empty_element_as_empty_string() {
+    let config = Config::new_with_custom_values(true, "@", "#text", NullValue::EmptyString);
+    assert_eq!(json!({"a": {"x": ""}}), xml_str_to_json("<a><x/></a>", &config).unwrap());
+    assert_eq!(json!({"a": {"x": ["", 1]}}), xml_str_to_json("<a><x></x><x>1</x></a>", &config).unwrap());
+    // elements with attributes are not empty
+    assert_eq!(json!({"a": {"x": {"@y": 1}}}), xml_str_to_json(r#"<a><x y="1"/></a>"#, &config).unwrap());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_xml_string_to_json_fast() {
+    let xml = r#"<a><b c="007">010</b><d><b>020</b></d><e c="007">030</e></a>"#;
+    let mut config = Config::new_with_defaults()
+        .add_json_type_override("/a/b/@c", JsonArray::Infer(JsonType::AlwaysString))
+        .add_json_type_override("/a/b", JsonArray::Always(JsonType::AlwaysString));
+    let compiled = config.compile();
+    let json = xml_string_to_json_fast(xml.to_owned(), &compiled).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), json);
+    assert_eq!(
+        json!({"a": {"b": [{"@c": "007", "#text": "010"}], "d": {"b": 20}, "e": {"@c": 7, "#text": 30}}}),
+        json
+    );
+
+    // suffixes of the paths still match with `PathAnchor::Anywhere`
+    config.path_anchor = PathAnchor::Anywhere;
+    let json = xml_string_to_json_fast(xml.to_owned(), &config.compile()).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), json);
+}
+
+#[test]
+fn test_comment_handling_prolog() {
+    let xml = r#"<?xml version="1.0"?>
+        <!-- exported 2024-01-03 -->
+        <!DOCTYPE order [<!ENTITY x "y">]>
+        <order>
+            <note><!-- verified 2024-01-03 --></note>
+            <item>1</item><!-- between --><item>2</item>
+            <status>new<!-- one --><!-- two --></status>
+        </order>"#;
+    let mut config = Config::new_with_defaults();
+    config.text.comment_handling = CommentHandling::PreserveAsField("#comment".to_owned());
+
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(
+        json!({
+            "#comment": "exported 2024-01-03",
+            "order": {
+                "#comment": "between",
+                "note": {"#comment": "verified 2024-01-03"},
+                "item": [1, 2],
+                "status": {"#comment": "two", "#text": "new"}
+            }
+        }),
+        json
+    );
+
+    // the comments before the root element follow the same variant
+    let json = xml_str_to_json("<!-- a --><!-- b --><r/>", &config).unwrap();
+    assert_eq!(json!({"#comment": "b", "r": {}}), json);
+    config.text.comment_handling = CommentHandling::PreserveAsArray("#comments".to_owned());
+    let json = xml_str_to_json("<!-- a --><!-- b --><r/>", &config).unwrap();
+    assert_eq!(json!({"#comments": ["a", "b"], "r": {}}), json);
+    assert_eq!(json!({"r": {}}), xml_str_to_json("<r/>", &config).unwrap());
+
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!(["exported 2024-01-03"]), json["#comments"]);
+    assert_eq!(json!(["one", "two"]), json["order"]["status"]["#comments"]);
+}
+
+#[test]
+fn test_xml_string_to_json_bytes() {
+    let documents = [
+        r#"<a x="1"><b>text &amp; "quotes"</b><b>2.5</b><c/></a>"#,
+        "<r>\u{e9}\u{1f600}<e>\n</e></r>",
+    ];
+    let mut config = Config::new_with_defaults();
+    config.text.comment_handling = CommentHandling::PreserveAsField("#comment".to_owned());
+    for xml in &documents {
+        let bytes = xml_string_to_json_bytes(xml.to_string(), &config).unwrap();
+        let expected = serde_json::to_vec(&xml_str_to_json(xml, &config).unwrap()).unwrap();
+        assert_eq!(expected, bytes);
+    }
+    assert!(matches!(
+        xml_string_to_json_bytes("<a></b>".to_owned(), &config),
+        Err(ConversionError::Xml(_))
+    ));
+}
+
+#[test]
+fn test_processing_instructions_key() {
+    let xml = r#"<?xml version="1.0"?>
+        <?xml-stylesheet type="text/xsl" href="x.xsl"?>
+        <doc>
+            <p>one<?page break?>two</p>
+            <q><?a-b --x-?><?empty?><![CDATA[<?not-a-pi?>]]></q>
+            <!-- <?not-a-pi?> -->
+        </doc>"#;
+    let mut config = Config::new_with_defaults();
+    assert_eq!(
+        json!({"doc": {"p": "onetwo", "q": "<?not-a-pi?>"}}),
+        xml_str_to_json(xml, &config).unwrap()
+    );
+
+    config.text.processing_instructions_key = Some("?pi".to_owned());
+    assert_eq!(
+        json!({
+            "?pi": {"target": "xml-stylesheet", "data": r#"type="text/xsl" href="x.xsl""#},
+            "doc": {
+                "p": {"?pi": {"target": "page", "data": "break"}, "#text": "onetwo"},
+                "q": {
+                    "?pi": [{"target": "a-b", "data": "--x-"}, {"target": "empty", "data": ""}],
+                    "#text": "<?not-a-pi?>"
+                }
+            }
+        }),
+        xml_str_to_json(xml, &config).unwrap()
+    );
+
+    // the comments are still told apart from the processing instructions
+    config.text.comment_handling = CommentHandling::PreserveAsField("#comment".to_owned());
+    let json = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!("<?not-a-pi?>"), json["doc"]["#comment"]);
+    assert_eq!(json!({"target": "page", "data": "break"}), json["doc"]["p"]["?pi"]);
+
+    // the parsers don't reject control characters, which are kept as they are
+    let xml = "<a><!--\u{0}x y--><?z \u{1}?><b e=\"?>\"><?w?></b></a>";
+    assert_eq!(
+        json!({"a": {
+            "#comment": "\u{0}x y",
+            "?pi": {"target": "z", "data": "\u{1}"},
+            "b": {"@e": "?>", "?pi": {"target": "w", "data": ""}}
+        }}),
+        xml_str_to_json(xml, &config).unwrap()
+    );
+}
+
+#[test]
+fn test_root_element_conversion() {
+    let convert = |xml: &str, config: &Config| xml_str_to_json(xml, config).unwrap();
+    let mut config = Config::new_with_defaults();
+
+    // an empty root element follows the empty element handling, except for `Ignore`
+    assert_eq!(json!({"a": {}}), convert("<a/>", &config));
+    config.empty_element_handling = NullValue::Null;
+    assert_eq!(json!({"a": null}), convert("<a/>", &config));
+    config.empty_element_handling = NullValue::EmptyString;
+    assert_eq!(json!({"a": ""}), convert("<a/>", &config));
+    config.empty_element_handling = NullValue::Ignore;
+    assert_eq!(json!({"a": null}), convert("<a/>", &config));
+    assert_eq!(json!({"a": null}), convert("<a>  </a>", &config));
+
+    // text and attributes
+    config.empty_element_handling = NullValue::EmptyObject;
+    assert_eq!(json!({"a": "hi"}), convert("<a>hi</a>", &config));
+    assert_eq!(json!({"a": 42}), convert("<a>42</a>", &config));
+    assert_eq!(json!({"a": {"@x": 1}}), convert(r#"<a x="1"/>"#, &config));
+    assert_eq!(json!({"a": {"@x": 1, "#text": "hi"}}), convert(r#"<a x="1">hi</a>"#, &config));
+
+    // the root element without the promoted attributes
+    config.attributes.promote_root_attrs = true;
+    assert_eq!(json!({"@x": 1, "a": {}}), convert(r#"<a x="1"/>"#, &config));
+    assert_eq!(json!({"@x": 1, "a": "hi"}), convert(r#"<a x="1">hi</a>"#, &config));
+    config.empty_element_handling = NullValue::Ignore;
+    assert_eq!(json!({"@x": 1, "a": null}), convert(r#"<a x="1"/>"#, &config));
+
+    // the output is an object for every combination
+    for handling in [NullValue::Ignore, NullValue::Null, NullValue::EmptyObject, NullValue::EmptyString] {
+        for promote_root_attrs in [false, true] {
+            config.empty_element_handling = handling.clone();
+            config.attributes.promote_root_attrs = promote_root_attrs;
+            for xml in ["<a/>", "<a>hi</a>", r#"<a x="1"/>"#, r#"<a x="1">hi</a>"#] {
+                assert!(xml_string_to_json(xml, &config).unwrap().is_object());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_json_type_int_and_float_override() {
+    let xml = r#"<a><amount>1.00</amount><amount>42</amount><amount>-7</amount><amount>n/a</amount><amount>NaN</amount><qty>42</qty><qty>1.00</qty><qty>x</qty></a>"#;
+    let config = Config::new_with_defaults()
+        .add_json_type_override("/a/amount", JsonArray::Infer(JsonType::AlwaysFloat))
+        .add_json_type_override("/a/qty", JsonArray::Infer(JsonType::AlwaysInt));
+    let result = xml_str_to_json(xml, &config).unwrap();
+
+    assert_eq!(json!([1.0, 42.0, -7.0, "n/a", "NaN"]), result["a"]["amount"]);
+    assert!(result["a"]["amount"][1].is_f64());
+    assert_eq!(json!([42, "1.00", "x"]), result["a"]["qty"]);
+    assert!(result["a"]["qty"][0].is_u64());
+
+    let chain = JsonType::Chain(vec![JsonType::AlwaysFloat, JsonType::Null(vec!["n/a"])]);
+    assert_eq!(json!(3.0), parse_text("3", false, &chain));
+    assert_eq!(Value::Null, parse_text("n/a", false, &chain));
+
+    // negative integers and leading zeros
+    let xml = r#"<a><qty>007</qty><qty>-3</qty><qty>1.00</qty><qty>x</qty></a>"#;
+    let config = Config::new_with_defaults().add_json_type_override("/a/qty", JsonArray::Infer(JsonType::AlwaysInt));
+    let result = xml_str_to_json(xml, &config).unwrap();
+    assert_eq!(json!([7, -3, "1.00", "x"]), result["a"]["qty"]);
+    assert!(result["a"]["qty"][1].is_i64());
+    let chain = JsonType::Chain(vec![JsonType::AlwaysInt, JsonType::Null(vec!["n/a"])]);
+    assert_eq!(json!(3), parse_text("3", false, &chain));
+    assert_eq!(Value::Null, parse_text("n/a", false, &chain));
+}
+
+#[test]
+fn test_force_array_above_child_count() {
+    let xml = r#"<a><b/><b>1</b><c>x</c></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.empty_element_handling = NullValue::Ignore;
+    assert_eq!(json!({"a": {"b": 1, "c": "x"}}), xml_str_to_json(xml, &config).unwrap());
+
+    // the ignored `b` still counts
+    config.arrays.force_array_above_child_count = Some(1);
+    assert_eq!(json!({"a": {"b": [1], "c": "x"}}), xml_str_to_json(xml, &config).unwrap());
+    config.arrays.force_array_above_child_count = Some(2);
+    assert_eq!(json!({"a": {"b": 1, "c": "x"}}), xml_str_to_json(xml, &config).unwrap());
+    config.arrays.force_array_above_child_count = Some(0);
+    assert_eq!(json!({"a": {"b": [1], "c": ["x"]}}), xml_str_to_json(xml, &config).unwrap());
+
+    // objects above the count are not merged
+    let xml = r#"<a><p x="1"/><p y="2"/><p z="3"/></a>"#;
+    let mut config = Config::new_with_defaults();
+    config.arrays.merge_same_name_objects = true;
+    config.arrays.force_array_above_child_count = Some(10);
+    let merged = json!({"a": {"p": {"@x": 1, "@y": 2, "@z": 3}}});
+    assert_eq!(merged, xml_str_to_json(xml, &config).unwrap());
+    config.arrays.force_array_above_child_count = Some(2);
+    let array = json!({"a": {"p": [{"@x": 1}, {"@y": 2}, {"@z": 3}]}});
+    assert_eq!(array, xml_str_to_json(xml, &config).unwrap());
+}
+
+#[test]
+fn test_always_array_elements() {
+    let xml = r#"<feed><item>1</item><group><item id="2"/><title>t</title></group></feed>"#;
+    let config = Config::new_with_defaults().add_always_array_element("item");
+    let expected = json!({"feed": {"item": [1], "group": {"item": [{"@id": 2}], "title": "t"}}});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+
+    // an override of the path wins
+    #[cfg(feature = "json_types")]
+    {
+        let config = config.add_json_type_override("/feed/group/item", JsonArray::Infer(JsonType::Infer));
+        let expected = json!({"feed": {"item": [1], "group": {"item": {"@id": 2}, "title": "t"}}});
+        assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    }
+}
+
+#[test]
+fn test_json_type_measurement() {
+    let units = vec!["m".to_owned(), "mm".to_owned(), "°C".to_owned()];
+    let number_only = JsonType::Measurement {
+        units: units.clone(),
+        emit: MeasurementEmit::NumberOnly,
+    };
+    assert_eq!(json!(15), parse_text("15mm", false, &number_only));
+    assert_eq!(json!(15), parse_text("15 m", false, &number_only));
+    assert_eq!(json!(21.5), parse_text("21.5 °C", false, &number_only));
+    assert_eq!(json!(-3), parse_text("-3m", false, &number_only));
+    // unknown units and text without a number remain strings
+    assert_eq!(json!("15km"), parse_text("15km", false, &number_only));
+    assert_eq!(json!("15 kg"), parse_text("15 kg", false, &number_only));
+    assert_eq!(json!("15"), parse_text("15", false, &number_only));
+    assert_eq!(json!("mm"), parse_text("mm", false, &number_only));
+
+    let object = JsonType::Measurement {
+        units,
+        emit: MeasurementEmit::Object {
+            value_key: "value".to_owned(),
+            unit_key: "unit".to_owned(),
+        },
+    };
+    assert_eq!(json!({"value": 15, "unit": "mm"}), parse_text("15mm", false, &object));
+    assert_eq!(json!({"value": 2.5, "unit": "m"}), parse_text("2.5m", false, &object));
+    assert_eq!(json!("n/a"), parse_text("n/a", false, &object));
+
+    #[cfg(feature = "json_types")]
+    {
+        let config = Config::new_with_defaults().add_json_type_override("/a/len", JsonArray::Infer(object));
+        let result = xml_str_to_json("<a><len>15mm</len><len>7 m</len><len>?</len></a>", &config).unwrap();
+        let expected = json!([{"value": 15, "unit": "mm"}, {"value": 7, "unit": "m"}, "?"]);
+        assert_eq!(expected, result["a"]["len"]);
+    }
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn test_xml_string_to_json_encoded() {
+    let xml = r#"<a name="Müller">5 € 🎉</a>"#;
+    let mut config = Config::new_with_defaults();
+
+    let utf8 = xml_string_to_json_encoded(xml.to_owned(), &config).unwrap();
+    assert_eq!(r##"{"a":{"#text":"5 € 🎉","@name":"Müller"}}"##.as_bytes(), &utf8[..]);
+
+    config.output.output_encoding = OutputEncoding::Iso8859_1;
+    let latin1 = xml_string_to_json_encoded(xml.to_owned(), &config).unwrap();
+    let mut expected = br##"{"a":{"#text":"5 \u20ac \ud83c\udf89","@name":"M"##.to_vec();
+    expected.push(0xFC);
+    expected.extend_from_slice(br#"ller"}}"#);
+    assert_eq!(expected, latin1);
+
+    // the escapes decode to the same value
+    let decoded: String = latin1.iter().map(|b| *b as char).collect();
+    let value: Value = serde_json::from_str(&decoded).unwrap();
+    assert_eq!(xml_str_to_json(xml, &config).unwrap(), value);
+
+    // the C1 controls are escaped, so windows-1252 reads the same
+    let json = xml_string_to_json_encoded("<a>\u{80}\u{e9}</a>".to_owned(), &config).unwrap();
+    assert_eq!(&br#"{"a":"\u0080"#[..], &json[..12]);
+    assert_eq!(&[0xE9, b'"', b'}'][..], &json[12..]);
+
+    // other encodings by their labels
+    config.output.output_encoding = OutputEncoding::for_label("Shift_JIS").unwrap();
+    assert_eq!(OutputEncoding::Other(encoding_rs::SHIFT_JIS), config.output.output_encoding);
+    let sjis = xml_string_to_json_encoded("<a>日本 €</a>".to_owned(), &config).unwrap();
+    let (decoded, _, malformed) = encoding_rs::SHIFT_JIS.decode(&sjis);
+    assert!(!malformed);
+    assert_eq!(r#"{"a":"日本 \u20ac"}"#, decoded);
+    assert_eq!(Some(OutputEncoding::Utf8), OutputEncoding::for_label("utf8"));
+    assert_eq!(None, OutputEncoding::for_label("no-such-encoding"));
+
+    // the encoding is restored from the embedded config
+    config.injection.embed_config = Some("_config".to_owned());
+    let mut value = xml_str_to_json("<a/>", &config).unwrap();
+    let record = value.as_object_mut().unwrap().remove("_config").unwrap();
+    assert_eq!(json!({"Other": "Shift_JIS"}), record["options"]["output_encoding"]);
+    assert_eq!(config.output.output_encoding, Config::from_embedded_config(&record).unwrap().output.output_encoding);
+
+    // encodings writing ASCII with several bytes can't be used
+    config.output.output_encoding = OutputEncoding::Other(encoding_rs::UTF_16LE);
+    assert!(matches!(
+        xml_string_to_json_encoded("<a/>".to_owned(), &config),
+        Err(ConversionError::UnsupportedEncoding { .. })
+    ));
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_json_type_wildcard_overrides() {
+    let xml = r#"<report id="1"><row1 id="42"><v>5</v></row1><row2 id="7"/><total id="9"/></report>"#;
+    let config = Config::new_with_defaults()
+        .add_json_type_override("/report/*/@id", JsonArray::Infer(JsonType::AlwaysString))
+        .add_json_type_override("/report/row2/@id", JsonArray::Infer(JsonType::Infer))
+        .add_json_type_override("report/row1/*", JsonArray::Always(JsonType::AlwaysString));
+    assert!(config.json_type_overrides.contains_key("/report/row2/@id"));
+    assert_eq!(2, config.json_type_wildcard_overrides.len());
+
+    // the exact path wins and `*` doesn't match attributes
+    let expected = json!({"report": {
+        "@id": 1,
+        "row1": {"@id": "42", "v": ["5"]},
+        "row2": {"@id": 7},
+        "total": {"@id": "9"}
+    }});
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    assert_eq!(expected, xml_string_to_json_fast(xml.to_owned(), &config.compile()).unwrap());
+
+    // the wildcard may match anywhere with `PathAnchor::Anywhere`
+    let mut config = Config::new_with_defaults()
+        .add_json_type_override("/*/@id", JsonArray::Infer(JsonType::AlwaysString));
+    assert_eq!(json!("1"), xml_str_to_json(xml, &config).unwrap()["report"]["@id"]);
+    assert_eq!(json!(42), xml_str_to_json(xml, &config).unwrap()["report"]["row1"]["@id"]);
+    config.path_anchor = PathAnchor::Anywhere;
+    assert_eq!(json!("42"), xml_str_to_json(xml, &config).unwrap()["report"]["row1"]["@id"]);
+}
+
+#[test]
+fn test_max_xml_depth() {
+    let xml = "<a><b><c>1</c></b><d/></a>";
+    let mut config = Config::new_with_defaults();
+    config.input.max_xml_depth = Some(3);
+    assert_eq!(json!({"a": {"b": {"c": 1}, "d": {}}}), xml_str_to_json(xml, &config).unwrap());
+
+    config.input.max_xml_depth = Some(2);
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!("the elements are nested too deep", ConversionError::DepthLimitExceeded.to_string());
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn test_strict_json_types() {
+    let xml = "<a><i>x1</i><f>1.5</f><s>abc</s></a>";
+    let mut config = Config::new_with_defaults()
+        .add_json_type_override("/a/i", JsonArray::Infer(JsonType::AlwaysInt))
+        .add_json_type_override("/a/f", JsonArray::Infer(JsonType::AlwaysFloat));
+    assert_eq!(json!({"a": {"i": "x1", "f": 1.5, "s": "abc"}}), xml_str_to_json(xml, &config).unwrap());
+
+    // only the values with a number override fail, other strings remain strings
+    config.values.strict_json_types = true;
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::TypeConversionFailed { path, value }) => {
+            assert_eq!("/a/i", path);
+            assert_eq!("x1", value);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let xml = "<a><i>7</i><f>x</f><s>abc</s></a>";
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::TypeConversionFailed { path, value }) => {
+            assert_eq!("/a/f", path);
+            assert_eq!("x", value);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_conversion_error_from_parser_errors() {
+    // the parser error converts without `minidom_interop` feature
+    let error: ConversionError = "<a><b></a>".parse::<Element>().unwrap_err().into();
+    match &error {
+        ConversionError::Xml(e) => assert_eq!(format!("malformed XML: {}", e), error.to_string()),
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    // for parsers implemented outside of the crate
+    let error = ConversionError::ParseError("unexpected end of input".to_owned());
+    assert_eq!("malformed XML: unexpected end of input", error.to_string());
+}
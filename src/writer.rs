@@ -0,0 +1,295 @@
+//! Conversion of JSON values shaped like the output of this crate back into XML.
+//!
+//! The same `Config` that produced the JSON tells the writer which properties are attributes and
+//! which one holds the text. Values with no XML form are reported as `ConversionError::UnsupportedJson`
+//! instead of producing a document that can't be parsed.
+
+use serde_json::{Map, Value};
+
+use super::{Config, ConversionError, NullValue};
+
+/// Converts the JSON value into an XML document, reversing the rules of `xml_string_to_json`:
+/// * the value must be an object with a single property named after the root element
+/// * properties starting with `Config::xml_attr_prefix` become attributes, `Config::xml_text_node_prop_name`
+///   becomes the text and all other properties become child elements
+/// * arrays become repeated elements with the name of the property, e.g. `{"a":{"b":[1,2]}}`
+///   becomes `<a><b>1</b><b>2</b></a>`
+/// * an array under the text property repeats its element, e.g. `{"a":{"tags":{"#text":["x","y"]}}}`
+///   becomes `<a><tags>x</tags><tags>y</tags></a>`, each copy with the attributes of the element
+/// * an array under an attribute property is joined with `AttributeOptions::xml_attr_array_separator`
+/// * `null` becomes an empty element or attribute, or is skipped if `Config::empty_element_handling`
+///   is `NullValue::Ignore`, since such values were never produced by empty elements in that case
+///
+/// Arrays inside arrays, objects as attribute values or text, and arrays under the text property of
+/// an element with child elements have no XML form and are errors.
+pub fn json_to_xml(json: &Value, config: &Config) -> Result<String, ConversionError> {
+    let (name, value) = match json {
+        Value::Object(map) if map.len() == 1 => map.iter().next().expect("one property"),
+        _ => {
+            return Err(unsupported(
+                "",
+                "the root must be an object with a single property",
+            ))
+        }
+    };
+    if value.is_array() {
+        return Err(unsupported(
+            &["/", name.as_str()].concat(),
+            "the root element can't be repeated",
+        ));
+    }
+
+    let mut writer = Writer {
+        config,
+        xml: String::new(),
+        path: String::new(),
+    };
+    // an empty root element is converted into `null` even with `NullValue::Ignore`
+    match value {
+        Value::Null => writer.empty_element(name, &Map::new())?,
+        value => writer.element(name, value)?,
+    }
+    Ok(writer.xml)
+}
+
+/// Keeps the output and the JSON path of the current value for error messages.
+struct Writer<'a> {
+    config: &'a Config,
+    xml: String,
+    path: String,
+}
+
+impl<'a> Writer<'a> {
+    /// Writes the property as elements, one for every array item.
+    fn element(&mut self, name: &str, value: &Value) -> Result<(), ConversionError> {
+        let path_len = self.path.len();
+        self.path.push('/');
+        self.path.push_str(name);
+
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    match item {
+                        Value::Array(_) => {
+                            return Err(self.unsupported("nested arrays have no XML form"))
+                        }
+                        item => self.single_element(name, item)?,
+                    }
+                }
+            }
+            value => self.single_element(name, value)?,
+        }
+
+        self.path.truncate(path_len);
+        Ok(())
+    }
+
+    /// Writes a single element, or several if its text is an array.
+    fn single_element(&mut self, name: &str, value: &Value) -> Result<(), ConversionError> {
+        let map = match value {
+            Value::Object(map) => map,
+            Value::Null if self.skips_nulls() => return Ok(()),
+            Value::Null => return self.empty_element(name, &Map::new()),
+            scalar => {
+                self.start_tag(name, &Map::new())?;
+                escape_text(&scalar_text(scalar), &mut self.xml);
+                return self.end_tag(name);
+            }
+        };
+
+        match map.get(&self.config.xml_text_node_prop_name) {
+            Some(Value::Array(items)) => {
+                if map.keys().any(|k| is_child(k, self.config)) {
+                    return Err(
+                        self.unsupported("an array of texts can't be combined with child elements")
+                    );
+                }
+                for item in items {
+                    match item {
+                        Value::Null if self.skips_nulls() => (),
+                        item => self.text_element(name, map, item)?,
+                    }
+                }
+                Ok(())
+            }
+            Some(text) => self.text_element(name, map, text),
+            None if map.keys().any(|k| is_child(k, self.config)) => {
+                self.start_tag(name, map)?;
+                self.children(map)?;
+                self.end_tag(name)
+            }
+            None => self.empty_element(name, map),
+        }
+    }
+
+    /// Writes an element with the attributes of the map, the text and the child elements of the map.
+    fn text_element(
+        &mut self,
+        name: &str,
+        map: &Map<String, Value>,
+        text: &Value,
+    ) -> Result<(), ConversionError> {
+        match text {
+            Value::Array(_) | Value::Object(_) => {
+                Err(self.unsupported("the text must be a scalar value"))
+            }
+            Value::Null if !map.keys().any(|k| is_child(k, self.config)) => {
+                self.empty_element(name, map)
+            }
+            text => {
+                self.start_tag(name, map)?;
+                if !text.is_null() {
+                    escape_text(&scalar_text(text), &mut self.xml);
+                }
+                self.children(map)?;
+                self.end_tag(name)
+            }
+        }
+    }
+
+    /// Writes `<name attr="value"/>`.
+    fn empty_element(
+        &mut self,
+        name: &str,
+        map: &Map<String, Value>,
+    ) -> Result<(), ConversionError> {
+        self.start_tag(name, map)?;
+        // replace `>` of the start tag
+        self.xml.pop();
+        self.xml.push_str("/>");
+        Ok(())
+    }
+
+    /// Writes the start tag with the attributes found in the map.
+    fn start_tag(&mut self, name: &str, map: &Map<String, Value>) -> Result<(), ConversionError> {
+        self.xml.push('<');
+        self.xml.push_str(name);
+        let config = self.config;
+        for (key, value) in map.iter().filter(|(k, _)| is_attribute(k, config)) {
+            let attr_name = &key[config.xml_attr_prefix.len()..];
+            let attr_value = match value {
+                Value::Null if self.skips_nulls() => continue,
+                Value::Null => String::new(),
+                Value::Array(items) => self.attr_array(attr_name, items)?,
+                Value::Object(_) => {
+                    return Err(self.unsupported_attr(attr_name, "objects have no XML form"))
+                }
+                scalar => scalar_text(scalar),
+            };
+            self.xml.push(' ');
+            self.xml.push_str(attr_name);
+            self.xml.push_str("=\"");
+            escape_attr(&attr_value, &mut self.xml);
+            self.xml.push('"');
+        }
+        self.xml.push('>');
+        Ok(())
+    }
+
+    /// Joins the items of an array under an attribute key.
+    fn attr_array(&self, attr_name: &str, items: &[Value]) -> Result<String, ConversionError> {
+        let separator = match self.config.attributes.xml_attr_array_separator {
+            Some(ref separator) => separator,
+            None => {
+                return Err(
+                    self.unsupported_attr(attr_name, "arrays need `xml_attr_array_separator`")
+                )
+            }
+        };
+
+        let mut values = Vec::with_capacity(items.len());
+        for item in items {
+            match item {
+                Value::Null if self.skips_nulls() => (),
+                Value::Null => values.push(String::new()),
+                Value::Array(_) | Value::Object(_) => {
+                    return Err(
+                        self.unsupported_attr(attr_name, "the array items must be scalar values")
+                    )
+                }
+                scalar => values.push(scalar_text(scalar)),
+            }
+        }
+        Ok(values.join(separator))
+    }
+
+    /// Writes the child elements found in the map.
+    fn children(&mut self, map: &Map<String, Value>) -> Result<(), ConversionError> {
+        let config = self.config;
+        for (key, value) in map.iter().filter(|(k, _)| is_child(k, config)) {
+            self.element(key, value)?;
+        }
+        Ok(())
+    }
+
+    fn end_tag(&mut self, name: &str) -> Result<(), ConversionError> {
+        self.xml.push_str("</");
+        self.xml.push_str(name);
+        self.xml.push('>');
+        Ok(())
+    }
+
+    /// `null` values are skipped because the forward conversion never produces them.
+    fn skips_nulls(&self) -> bool {
+        matches!(self.config.empty_element_handling, NullValue::Ignore)
+    }
+
+    fn unsupported(&self, reason: &str) -> ConversionError {
+        unsupported(&self.path, reason)
+    }
+
+    fn unsupported_attr(&self, attr_name: &str, reason: &str) -> ConversionError {
+        unsupported(&[self.path.as_str(), "/@", attr_name].concat(), reason)
+    }
+}
+
+fn is_attribute(key: &str, config: &Config) -> bool {
+    !config.xml_attr_prefix.is_empty()
+        && key != config.xml_text_node_prop_name
+        && key.starts_with(&config.xml_attr_prefix)
+}
+
+fn is_child(key: &str, config: &Config) -> bool {
+    key != config.xml_text_node_prop_name && !is_attribute(key, config)
+}
+
+fn unsupported(path: &str, reason: &str) -> ConversionError {
+    ConversionError::UnsupportedJson {
+        path: path.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+/// Returns the text of a JSON string, number or boolean.
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn escape_text(text: &str, xml: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+/// Escapes the attribute value, including the whitespace characters parsers normalize into spaces.
+fn escape_attr(value: &str, xml: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '"' => xml.push_str("&quot;"),
+            '\n' => xml.push_str("&#10;"),
+            '\r' => xml.push_str("&#13;"),
+            '\t' => xml.push_str("&#9;"),
+            c => xml.push(c),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Type inference guided by an XML Schema (XSD).
+//! Only the basic building blocks are understood: global and local element and attribute declarations,
+//! named and anonymous simple and complex types, `sequence`, `choice`, `all`, `extension` and `restriction`.
+//! Requires `json_types` feature.
+
+use minidom::Element;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::{parse_xml, xml_string_to_json, Config, ConversionError, JsonArray, JsonType};
+
+/// The namespace of XSD elements.
+const XS_NS: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// Converts the given XML string into `serde::Value` using the types declared in the XSD.
+/// The schema declarations are turned into JSON type overrides:
+/// * numeric types like `xs:integer` or `xs:decimal` are inferred as numbers
+/// * `xs:boolean` values `true` and `1` become JSON `true`, anything else `false`
+/// * all other built-in types, e.g. `xs:string` or `xs:date`, are always converted into strings
+/// * elements with `maxOccurs` greater than 1 are always converted into arrays
+///
+/// Overrides already present in `Config::json_type_overrides` take precedence over the schema.
+/// Undeclared elements and attributes and types not understood by the parser are inferred as usual.
+/// Requires `json_types` feature.
+pub fn xml_string_to_json_schema_aware(
+    xml: String,
+    xsd: &str,
+    config: &Config,
+) -> Result<Value, ConversionError> {
+    let schema = parse_xml(xsd, config)?.root;
+    let mut config = config.clone();
+
+    for (path, json_array) in schema_overrides(&schema) {
+        config.json_type_overrides.entry(path).or_insert(json_array);
+    }
+
+    xml_string_to_json(xml, &config)
+}
+
+/// Builds a map of XML paths with their JSON types from the declarations of the schema.
+fn schema_overrides(schema: &Element) -> HashMap<String, JsonArray> {
+    let mut resolver = Resolver {
+        simple_types: HashMap::new(),
+        complex_types: HashMap::new(),
+        elements: HashMap::new(),
+        overrides: HashMap::new(),
+        stack: Vec::new(),
+        refs: Vec::new(),
+    };
+
+    for child in schema.children() {
+        if let Some(name) = child.attr("name") {
+            if child.is("simpleType", XS_NS) {
+                resolver.simple_types.insert(name, child);
+            } else if child.is("complexType", XS_NS) {
+                resolver.complex_types.insert(name, child);
+            } else if child.is("element", XS_NS) {
+                resolver.elements.insert(name, child);
+            }
+        }
+    }
+
+    // any global element can be the root of the document
+    for child in schema.children().filter(|c| c.is("element", XS_NS)) {
+        resolver.element(child, "", false);
+    }
+
+    resolver.overrides
+}
+
+/// Walks the schema declarations and collects the overrides.
+struct Resolver<'a> {
+    simple_types: HashMap<&'a str, &'a Element>,
+    complex_types: HashMap<&'a str, &'a Element>,
+    elements: HashMap<&'a str, &'a Element>,
+    overrides: HashMap<String, JsonArray>,
+    /// Names of the types being expanded, to stop at recursive declarations
+    stack: Vec<&'a str>,
+    /// Names of the referenced global elements being expanded, for the same reason
+    refs: Vec<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Adds the overrides for the element declaration and its content.
+    /// `repeated` is set if an enclosing `sequence` or `choice` can occur multiple times.
+    fn element(&mut self, el: &'a Element, parent_path: &str, repeated: bool) {
+        let repeated = repeated || is_repeated(el);
+
+        // a reference to a global element takes its name and type from it, but not its occurrence
+        if let Some(r) = el.attr("ref") {
+            let name = local_name(r);
+            match self.elements.get(name).cloned() {
+                Some(global) if !self.refs.contains(&name) => {
+                    self.refs.push(name);
+                    self.element(global, parent_path, repeated);
+                    self.refs.pop();
+                }
+                _ => (),
+            }
+            return;
+        }
+
+        let name = match el.attr("name") {
+            Some(name) => name,
+            None => return,
+        };
+        let path = [parent_path, "/", name].concat();
+
+        let json_type = match el.attr("type") {
+            Some(type_name) => {
+                if let Some(complex) = self.complex_types.get(local_name(type_name)).cloned() {
+                    self.expand(type_name, complex, &path)
+                } else {
+                    self.simple_type(type_name)
+                }
+            }
+            None => {
+                let mut json_type = JsonType::Infer;
+                for child in el.children() {
+                    if child.is("simpleType", XS_NS) {
+                        json_type = self.anonymous_simple_type(child);
+                    } else if child.is("complexType", XS_NS) {
+                        json_type = self.complex_type(child, &path);
+                    }
+                }
+                json_type
+            }
+        };
+
+        self.insert(path, repeated, json_type);
+    }
+
+    /// Adds the overrides for the attribute declaration.
+    fn attribute(&mut self, el: &'a Element, parent_path: &str) {
+        let name = match el.attr("name").or_else(|| el.attr("ref").map(local_name)) {
+            Some(name) => name,
+            None => return,
+        };
+        let json_type = match el.attr("type") {
+            Some(type_name) => self.simple_type(type_name),
+            None => el
+                .children()
+                .find(|c| c.is("simpleType", XS_NS))
+                .map_or(JsonType::Infer, |c| self.anonymous_simple_type(c)),
+        };
+
+        self.insert([parent_path, "/@", name].concat(), false, json_type);
+    }
+
+    /// Expands a named complex type unless it is already being expanded higher up the tree.
+    fn expand(&mut self, type_name: &'a str, el: &'a Element, path: &str) -> JsonType {
+        let name = local_name(type_name);
+        if self.stack.contains(&name) {
+            return JsonType::Infer;
+        }
+        self.stack.push(name);
+        let json_type = self.complex_type(el, path);
+        self.stack.pop();
+        json_type
+    }
+
+    /// Adds the overrides for the content of the complex type and returns the type of its text.
+    fn complex_type(&mut self, el: &'a Element, path: &str) -> JsonType {
+        let mut json_type = JsonType::Infer;
+
+        for child in el.children() {
+            match child.name() {
+                _ if !child.has_ns(XS_NS) => (),
+                "element" => self.element(child, path, false),
+                "attribute" => self.attribute(child, path),
+                "sequence" | "choice" | "all" => self.group(child, path, is_repeated(child)),
+                "complexContent" | "simpleContent" => json_type = self.complex_type(child, path),
+                "extension" | "restriction" => {
+                    if let Some(base) = child.attr("base") {
+                        json_type = match self.complex_types.get(local_name(base)).cloned() {
+                            Some(complex) => self.expand(base, complex, path),
+                            None => self.simple_type(base),
+                        };
+                    }
+                    // the content of the derived type is added to the content of the base
+                    self.complex_type(child, path);
+                }
+                _ => (),
+            }
+        }
+
+        json_type
+    }
+
+    /// Adds the overrides for the elements of `sequence`, `choice` or `all`.
+    fn group(&mut self, el: &'a Element, path: &str, repeated: bool) {
+        for child in el.children() {
+            if child.is("element", XS_NS) {
+                self.element(child, path, repeated);
+            } else if child.is("sequence", XS_NS) || child.is("choice", XS_NS) {
+                self.group(child, path, repeated || is_repeated(child));
+            }
+        }
+    }
+
+    /// Returns the JSON type of a built-in or a named simple type.
+    fn simple_type(&mut self, type_name: &'a str) -> JsonType {
+        let name = local_name(type_name);
+        match self.simple_types.get(name).cloned() {
+            Some(el) if !self.stack.contains(&name) => {
+                self.stack.push(name);
+                let json_type = self.anonymous_simple_type(el);
+                self.stack.pop();
+                json_type
+            }
+            Some(_) => JsonType::Infer,
+            None => builtin_type(name),
+        }
+    }
+
+    /// Returns the JSON type of a `simpleType` declaration.
+    fn anonymous_simple_type(&mut self, el: &'a Element) -> JsonType {
+        for child in el.children() {
+            if child.is("restriction", XS_NS) {
+                return match child.attr("base") {
+                    Some(base) => self.simple_type(base),
+                    None => child
+                        .children()
+                        .find(|c| c.is("simpleType", XS_NS))
+                        .map_or(JsonType::Infer, |c| self.anonymous_simple_type(c)),
+                };
+            } else if child.is("list", XS_NS) {
+                // a whitespace-separated list is a single text
+                return JsonType::AlwaysString;
+            }
+        }
+
+        // unions may mix any types
+        JsonType::Infer
+    }
+
+    /// Records the override for the path, skipping the ones identical to the default behavior.
+    fn insert(&mut self, path: String, repeated: bool, json_type: JsonType) {
+        let json_array = match (repeated, json_type) {
+            (false, JsonType::Infer) => return,
+            (false, json_type) => JsonArray::Infer(json_type),
+            (true, json_type) => JsonArray::Always(json_type),
+        };
+        self.overrides.insert(path, json_array);
+    }
+}
+
+/// Returns the JSON type of a built-in XSD type.
+fn builtin_type(name: &str) -> JsonType {
+    match name {
+        "integer" | "int" | "long" | "short" | "byte" | "nonNegativeInteger" | "positiveInteger"
+        | "nonPositiveInteger" | "negativeInteger" | "unsignedLong" | "unsignedInt"
+        | "unsignedShort" | "unsignedByte" | "decimal" | "float" | "double" => JsonType::Infer,
+        "boolean" => JsonType::Bool(vec!["true", "1"]),
+        "anyType" | "anySimpleType" => JsonType::Infer,
+        _ => JsonType::AlwaysString,
+    }
+}
+
+/// Returns `true` if the particle may occur more than once.
+fn is_repeated(el: &Element) -> bool {
+    match el.attr("maxOccurs") {
+        Some("unbounded") => true,
+        Some(max) => max.parse::<u64>().is_ok_and(|max| max > 1),
+        None => false,
+    }
+}
+
+/// Strips the namespace prefix from a qualified name, e.g. `xs:integer` becomes `integer`.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
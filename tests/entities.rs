@@ -0,0 +1,83 @@
+//! Entity and whitespace handling of text nodes and attribute values, checked end-to-end
+//! against the rules of the XML spec.
+extern crate quickxml_to_serde;
+extern crate serde_json;
+
+use quickxml_to_serde::{xml_str_to_json, Config};
+use serde_json::Value;
+
+/// Pairs of XML snippets and the expected JSON string of `/a/@b` (attributes) or `/a` (text).
+const ATTRIBUTES: &[(&str, &str)] = &[
+    // numeric character references
+    (r#"<a b="x&#10;y"/>"#, "x\ny"),
+    (r#"<a b="&#10;y&#10;"/>"#, "\ny\n"),
+    (r#"<a b="x&#13;y"/>"#, "x\ry"),
+    (r#"<a b="x&#9;y"/>"#, "x\ty"),
+    (r#"<a b="&#x1F600;"/>"#, "\u{1F600}"),
+    (r#"<a b="&#128512;"/>"#, "\u{1F600}"),
+    // predefined entities
+    (r#"<a b="&quot;q&quot;"/>"#, "\"q\""),
+    (r#"<a b='&apos;q&apos;'/>"#, "'q'"),
+    (r#"<a b="&lt;&gt;&amp;"/>"#, "<>&"),
+    (r#"<a b="&amp;lt;"/>"#, "&lt;"),
+    (r#"<a b='"'/>"#, "\""),
+    // literal whitespace in attribute values is normalized into spaces
+    ("<a b=\"x\ny\"/>", "x y"),
+    ("<a b=\"x\ty\"/>", "x y"),
+    ("<a b=\"x\r\ny\"/>", "x y"),
+    ("<a b=\"x\ry\"/>", "x y"),
+    ("<a b=\"\n x \n\"/>", "x"),
+    // `>` is allowed in attribute values and must not end the tag
+    ("<a b=\"1 > 0\n\"/>", "1 > 0"),
+];
+
+const TEXT: &[(&str, &str)] = &[
+    ("<a>x&#13;y</a>", "x\ry"),
+    ("<a>x&#10;y</a>", "x\ny"),
+    ("<a>&#x1F600;</a>", "\u{1F600}"),
+    ("<a>&lt;&gt;&amp;&apos;&quot;</a>", "<>&'\""),
+    ("<a>&amp;lt;</a>", "&lt;"),
+    ("<a>&amp;amp;</a>", "&amp;"),
+    ("<a>x ]]&gt; y</a>", "x ]]> y"),
+    ("<a><![CDATA[x ]]]]><![CDATA[> y]]></a>", "x ]]> y"),
+    ("<a><![CDATA[&lt;]]></a>", "&lt;"),
+    // end-of-line handling
+    ("<a>x\r\ny</a>", "x\ny"),
+    ("<a>x\ry</a>", "x\ny"),
+    ("<a>x\r\n\r\ny</a>", "x\n\ny"),
+    // literal whitespace is kept in text nodes
+    ("<a>x\ty</a>", "x\ty"),
+    // quotes and tags inside comments and CDATA don't affect attribute normalization
+    (
+        "<a><!-- b=\"x\ny\" --><![CDATA[<c d=\"\n\">]]></a>",
+        "<c d=\"\n\">",
+    ),
+];
+
+#[test]
+fn attribute_values() {
+    let config = Config::new_with_defaults();
+    for (xml, expected) in ATTRIBUTES {
+        let result = xml_str_to_json(xml, &config).unwrap();
+        assert_eq!(
+            &Value::String(expected.to_string()),
+            &result["a"]["@b"],
+            "{}",
+            xml
+        );
+    }
+}
+
+#[test]
+fn text_nodes() {
+    let config = Config::new_with_defaults();
+    for (xml, expected) in TEXT {
+        let result = xml_str_to_json(xml, &config).unwrap();
+        assert_eq!(
+            &Value::String(expected.to_string()),
+            &result["a"],
+            "{}",
+            xml
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Checks the feature capability report and the validation of options depending on features.
+//! Run `cargo test --test features` with different features, e.g. `--features json_types,quickxml-parser`,
+//! to cover both sides of the feature matrix.
+extern crate quickxml_to_serde;
+#[macro_use]
+extern crate serde_json;
+
+use quickxml_to_serde::{capabilities, Config, ConversionError, DupAttr};
+
+#[test]
+fn capability_report() {
+    let caps = capabilities();
+    assert_eq!(cfg!(feature = "json_types"), caps.json_types);
+    assert_eq!(cfg!(feature = "quickxml-parser"), caps.quickxml_parser);
+    assert_eq!(cfg!(feature = "async"), caps.async_io);
+    assert_eq!(caps.json_types, caps.features().contains(&"json_types"));
+    assert_eq!(caps.quickxml_parser, caps.features().contains(&"quickxml-parser"));
+}
+
+#[test]
+fn validate_duplicate_source_attributes() {
+    let mut config = Config::new_with_defaults();
+    assert!(config.validate().is_ok());
+
+    config.attributes.duplicate_source_attributes = DupAttr::Last;
+    let result = config.validate();
+    if cfg!(feature = "quickxml-parser") {
+        assert!(result.is_ok());
+    } else {
+        match result {
+            Err(ConversionError::MissingFeature { option, feature }) => {
+                assert_eq!("duplicate_source_attributes", option);
+                assert_eq!("quickxml-parser", feature);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn restore_options_of_disabled_features() {
+    let record = json!({"options": {"enum_mappings": {"/a/@status": ["NEW", "DONE"]}, "path_anchor": "Root"}});
+    let result = Config::from_embedded_config(&record);
+    if cfg!(feature = "json_types") {
+        assert!(result.is_ok());
+    } else {
+        assert!(matches!(result, Err(ConversionError::MissingFeature { .. })));
+    }
+
+    // default values of the options are fine without the feature
+    let record = json!({"options": {"enum_mappings": {}, "tuple_rules": {}, "path_anchor": "Root"}});
+    assert!(Config::from_embedded_config(&record).is_ok());
+}
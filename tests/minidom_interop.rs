@@ -0,0 +1,45 @@
+//! Checks the public API with and without `minidom_interop` feature.
+//! Run `cargo test` and `cargo test --features minidom_interop` to cover both sides of the feature matrix.
+extern crate quickxml_to_serde;
+#[macro_use]
+extern crate serde_json;
+
+use quickxml_to_serde::{xml_str_to_json, Config, ConversionError, Converter};
+
+/// The string, reader and bytes APIs must not require any parser types.
+#[test]
+fn parser_agnostic_api() {
+    let config = Config::new_with_defaults();
+    let mut converter = Converter::new(&config);
+    let xml = r#"<a b="1">2</a>"#;
+    let expected = json!({"a": {"@b": 1, "#text": 2}});
+
+    assert_eq!(expected, xml_str_to_json(xml, &config).unwrap());
+    assert_eq!(expected, converter.convert_bytes(xml.as_bytes()).unwrap());
+    assert_eq!(expected, converter.convert_reader(xml.as_bytes()).unwrap());
+
+    match xml_str_to_json("<a><b></a>", &config) {
+        Err(ConversionError::Xml(e)) => assert!(!e.to_string().is_empty()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "minidom_interop")]
+#[test]
+fn minidom_interop_api() {
+    use quickxml_to_serde::element_to_json;
+    use quickxml_to_serde::minidom::Element;
+
+    let config = Config::new_with_defaults();
+    let el: Element = r#"<a b="1">2</a>"#.parse().unwrap();
+    assert_eq!(json!({"a": {"@b": 1, "#text": 2}}), element_to_json(&el, &config).unwrap());
+    assert_eq!(
+        json!({"a": {"@b": 1, "#text": 2}}),
+        Converter::new(&config).convert_element(&el).unwrap()
+    );
+
+    match xml_str_to_json("<a><b></a>", &config) {
+        Err(ConversionError::Xml(e)) => assert_eq!(e.to_string(), e.as_minidom().to_string()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,52 @@
+//! Golden-file tests for `ConversionProfile`. Every XML file in `test_xml_files` is converted with each profile
+//! and compared to `tests/golden/<profile>/<file>.json`, so changes to the conversion rules can't silently alter
+//! the output of the older profiles.
+//! Run with `UPDATE_GOLDEN=1 cargo test --test profiles` to create the golden files for new XML files.
+//! Existing golden files of the old profiles must never change.
+extern crate quickxml_to_serde;
+extern crate serde_json;
+
+use quickxml_to_serde::{xml_string_to_json, Config, ConversionProfile};
+use std::fs;
+use std::path::Path;
+
+fn check_profile(profile: ConversionProfile, dir: &str) {
+    let mut config = Config::new_with_defaults();
+    config.profile = profile;
+    let golden_dir = Path::new("tests/golden").join(dir);
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut entries: Vec<_> = fs::read_dir("test_xml_files")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    entries.sort();
+    assert!(!entries.is_empty());
+
+    for entry in entries {
+        let xml = fs::read_to_string(&entry).unwrap();
+        let json = xml_string_to_json(xml, &config).unwrap();
+        let golden = golden_dir.join(entry.with_extension("json").file_name().unwrap());
+
+        if update && !golden.exists() {
+            fs::create_dir_all(&golden_dir).unwrap();
+            fs::write(&golden, serde_json::to_string_pretty(&json).unwrap() + "\n").unwrap();
+        }
+
+        let expected = fs::read_to_string(&golden)
+            .unwrap_or_else(|_| panic!("missing golden file {:?}", golden));
+        let expected: serde_json::Value = serde_json::from_str(&expected).unwrap();
+        assert_eq!(expected, json, "{:?} with {:?}", entry, profile);
+    }
+}
+
+#[test]
+fn profile_v0_6() {
+    check_profile(ConversionProfile::V0_6, "v0_6");
+}
+
+#[test]
+fn profile_latest() {
+    check_profile(ConversionProfile::Latest, "latest");
+}
@@ -0,0 +1,156 @@
+//! Checks that `xml_to_json_writer` writes the same output with and without spilling large arrays to disk,
+//! and that the temporary files are removed in every case. Run with `cargo test --features spill`.
+#![cfg(feature = "spill")]
+extern crate quickxml_to_serde;
+extern crate serde_json;
+
+use quickxml_to_serde::{
+    xml_str_to_json, xml_to_json_writer, Config, ConversionError, SpillConfig, TextNodeMapper,
+};
+use serde_json::Value;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const XML: &str = concat!(
+    r#"<orders><order id="1"><line sku="a"/><line sku="b"/><line sku="c"/><line sku="d"/></order>"#,
+    r#"<order id="2"><line sku="e"/></order><order id="3"/><note>x</note></orders>"#
+);
+
+/// Creates an empty temporary directory for a single test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("quickxml_to_serde-spill-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn is_empty(dir: &PathBuf) -> bool {
+    fs::read_dir(dir).unwrap().next().is_none()
+}
+
+#[test]
+fn spilled_output_is_unchanged() {
+    let dir = temp_dir("output");
+    let mut config = Config::new_with_defaults();
+    let expected = xml_str_to_json(XML, &config).unwrap();
+
+    for threshold_members in 0..5 {
+        config.output.spill = Some(SpillConfig::new(threshold_members, &dir));
+        let mut output = Vec::new();
+        xml_to_json_writer(XML.as_bytes(), &mut output, &config).unwrap();
+        assert_eq!(expected, serde_json::from_slice::<Value>(&output).unwrap());
+        assert_eq!(serde_json::to_vec(&expected).unwrap(), output);
+        assert!(is_empty(&dir));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn flattened_and_ordered_arrays_are_spilled() {
+    let dir = temp_dir("arrays");
+    let xml = r#"<doc><items><item>1</item><item>2</item><item>3</item></items><p>a<b>x</b>b<b>y</b>c</p></doc>"#;
+    let mut config = Config::new_with_defaults();
+    config.arrays.auto_flatten_plural_wrappers = true;
+    config.text.ordered_content = true;
+    let expected = xml_str_to_json(xml, &config).unwrap();
+
+    config.output.spill = Some(SpillConfig::new(1, &dir));
+    match xml_str_to_json(xml, &config) {
+        Err(ConversionError::SpillRequired { path }) => assert_eq!("/doc/items/item", path),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let mut output = Vec::new();
+    xml_to_json_writer(xml.as_bytes(), &mut output, &config).unwrap();
+    assert_eq!(serde_json::to_vec(&expected).unwrap(), output);
+    assert!(is_empty(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "json_types")]
+#[test]
+fn tuples_are_spilled() {
+    let dir = temp_dir("tuples");
+    let xml = r#"<path><pt x="1" y="2"/><pt x="3" y="4"/><pt x="5" y="6"/></path>"#;
+    let mut config = Config::new_with_defaults().add_tuple_rule("/path/pt", &["x", "y"]);
+    let expected = xml_str_to_json(xml, &config).unwrap();
+
+    config.output.spill = Some(SpillConfig::new(1, &dir));
+    let mut output = Vec::new();
+    xml_to_json_writer(xml.as_bytes(), &mut output, &config).unwrap();
+    assert_eq!(serde_json::to_vec(&expected).unwrap(), output);
+    assert!(is_empty(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn strings_are_not_taken_for_spilled_arrays() {
+    let dir = temp_dir("strings");
+    let mut config = Config::new_with_defaults();
+    config.values.text_node_mapper = Some(TextNodeMapper(Arc::new(|_: &str, text: &str| {
+        Value::String(format!("\0spill:0{}", text))
+    })));
+    let expected = xml_str_to_json(XML, &config).unwrap();
+
+    config.output.spill = Some(SpillConfig::new(1, &dir));
+    let mut output = Vec::new();
+    xml_to_json_writer(XML.as_bytes(), &mut output, &config).unwrap();
+    assert_eq!(serde_json::to_vec(&expected).unwrap(), output);
+    assert!(is_empty(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn value_functions_refuse_to_spill() {
+    let dir = temp_dir("value");
+    let mut config = Config::new_with_defaults();
+    config.output.spill = Some(SpillConfig::new(3, &dir));
+    match xml_str_to_json(XML, &config) {
+        Err(ConversionError::SpillRequired { path }) => assert_eq!("/orders/order/line", path),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // below the threshold nothing is spilled
+    config.output.spill = Some(SpillConfig::new(4, &dir));
+    assert!(xml_str_to_json(XML, &config).is_ok());
+
+    config.output.emit_type_annotations = true;
+    match xml_to_json_writer(XML.as_bytes(), Vec::new(), &config) {
+        Err(ConversionError::SpillUnsupported { option }) => assert_eq!("emit_type_annotations", option),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    config.output.emit_type_annotations = false;
+    config.diagnostics.array_homogeneity_check = true;
+    match xml_to_json_writer(XML.as_bytes(), Vec::new(), &config) {
+        Err(ConversionError::SpillUnsupported { option }) => assert_eq!("array_homogeneity_check", option),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(is_empty(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn files_are_removed_on_errors_and_panics() {
+    let dir = temp_dir("cleanup");
+    let mut config = Config::new_with_defaults();
+    config.output.spill = Some(SpillConfig::new(1, &dir));
+
+    // the document is parsed before converting, so nothing is spilled for malformed documents
+    let result = xml_to_json_writer(&XML.as_bytes()[..XML.len() - 3], Vec::new(), &config);
+    assert!(result.is_err());
+    assert!(is_empty(&dir));
+
+    // the conversion panics after the array was spilled
+    config.values.text_node_mapper = Some(TextNodeMapper(Arc::new(|_: &str, text: &str| {
+        if text == "x" {
+            panic!("mapper failed");
+        }
+        Value::String(text.to_owned())
+    })));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| xml_to_json_writer(XML.as_bytes(), Vec::new(), &config)));
+    assert!(result.is_err());
+    assert!(is_empty(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+}